@@ -0,0 +1,817 @@
+//! An HTTP API for operators to inspect and control the operations queued by
+//! the relayer's submitters.
+//!
+//! The API is served on 127.0.0.1 unless another address is configured.
+//! Every request must carry the configured token as a bearer token in the
+//! `Authorization` header. Routes:
+//!
+//! - `GET /operations`: list the queued operations of each destination.
+//! - `POST /operations/{id}/retry`: clear the backoff of a queued operation.
+//! - `DELETE /operations/{id}`: drop a queued operation. The message is
+//!   dead-lettered so it isn't picked up again after a restart; re-inject it
+//!   to relay it again.
+//! - `POST /messages/{origin}/{nonce}/enqueue`: queue a message that was
+//!   skipped by the message processor, e.g. because of the whitelist.
+//!   Messages which were delivered, which the processor hasn't reached yet or
+//!   which the submitter holds are rejected.
+//! - `GET /dead-letters`: export the messages the relayer gave up on as JSON,
+//!   including the reason and the full message.
+//! - `POST /dead-letters/{origin}/{nonce}/reinject`: queue a dead-lettered
+//!   message again from scratch, e.g. after the recipient was deployed.
+//! - `GET /profitability`: export the gas payments received and the gas spent
+//!   per route and per sender as JSON.
+//! - `DELETE /ism-cache`: clear the cache of ISM lookups.
+//! - `DELETE /ism-cache/{destination}/{ism}`: clear the cached lookups of a
+//!   single ISM.
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use eyre::{eyre, WrapErr};
+use serde::Serialize;
+use tokio::{
+    sync::{mpsc::UnboundedSender, RwLock},
+    task::JoinHandle,
+};
+use tracing::{info, info_span, instrument::Instrumented, warn, Instrument};
+use warp::{
+    http::StatusCode,
+    reply::{self, WithStatus},
+    Filter, Rejection, Reply,
+};
+
+use hyperlane_base::db::HyperlaneRocksDB;
+use hyperlane_core::{HyperlaneDomain, H256};
+
+use crate::{
+    merkle_tree_builder::MerkleTreeBuilder,
+    msg::{
+        dead_letter::{dead_letter, DeadLetterClass, DeadLetterInfo},
+        metadata::IsmCache,
+        pending_message::{MessageContext, PendingMessage},
+        pending_operation::{DynPendingOperation, PendingOperation},
+        profitability::ProfitabilityTracker,
+        serial_submitter::{OpQueue, SubmitterQueues},
+    },
+    relayer::ContextKey,
+    settings::AdminConf,
+};
+
+/// State shared by the admin API handlers.
+pub(crate) struct AdminState {
+    /// Submitter queues by destination domain id
+    pub queues: HashMap<u32, (HyperlaneDomain, SubmitterQueues)>,
+    /// Channels to the submitters by destination domain id
+    pub send_channels: HashMap<u32, UnboundedSender<Box<DynPendingOperation>>>,
+    pub msg_ctxs: HashMap<ContextKey, Arc<MessageContext>>,
+    /// Databases by origin domain id
+    pub dbs: HashMap<u32, HyperlaneRocksDB>,
+    /// Provers by origin domain id
+    pub prover_syncs: HashMap<u32, Arc<RwLock<MerkleTreeBuilder>>>,
+    /// Nonce of the next message each message processor will process, by
+    /// origin domain id
+    pub processor_nonces: HashMap<u32, Arc<AtomicU32>>,
+    pub ism_cache: Arc<IsmCache>,
+    pub profitability: Arc<ProfitabilityTracker>,
+}
+
+/// A queued operation as reported by `GET /operations`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationInfo {
+    id: String,
+    queue: &'static str,
+    origin: u32,
+    nonce: u32,
+    num_retries: u32,
+    /// Seconds until the operation is next attempted, or None if it can be
+    /// attempted right away.
+    next_attempt_in_secs: Option<u64>,
+    last_failure: Option<String>,
+}
+
+impl OperationInfo {
+    fn new(op: &DynPendingOperation, queue: &'static str, now: Instant) -> Self {
+        let DynPendingOperation::PendingMessage(pm) = op;
+        Self {
+            id: format!("{:?}", op.id()),
+            queue,
+            origin: pm.message.origin,
+            nonce: pm.message.nonce,
+            num_retries: op.num_retries(),
+            next_attempt_in_secs: op
+                ._next_attempt_after()
+                .map(|t| t.saturating_duration_since(now).as_secs()),
+            last_failure: op.last_failure().map(str::to_owned),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Run the admin API server until it exits.
+pub(crate) fn run_admin_server(
+    conf: AdminConf,
+    state: AdminState,
+) -> Instrumented<JoinHandle<eyre::Result<()>>> {
+    let routes = routes(conf.token, state);
+    tokio::spawn(async move {
+        let (addr, server) = warp::serve(routes)
+            .try_bind_ephemeral((conf.host, conf.port))
+            .wrap_err("Failed to start admin server")?;
+        info!(%addr, "Admin server listening");
+        server.await;
+        Err(eyre!("Admin server exited"))
+    })
+    .instrument(info_span!("AdminServer"))
+}
+
+/// The routes of the admin API, all of which require the token.
+fn routes(
+    token: String,
+    state: AdminState,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let state = Arc::new(state);
+    let token = Arc::new(token);
+    let with_state = warp::any().map(move || state.clone());
+
+    let list = warp::path!("operations")
+        .and(warp::get())
+        .and(with_state.clone())
+        .then(list_operations);
+    let retry = warp::path!("operations" / H256 / "retry")
+        .and(warp::post())
+        .and(with_state.clone())
+        .then(retry_operation);
+    let drop = warp::path!("operations" / H256)
+        .and(warp::delete())
+        .and(with_state.clone())
+        .then(drop_operation);
+    let enqueue = warp::path!("messages" / u32 / u32 / "enqueue")
+        .and(warp::post())
+        .and(with_state.clone())
+        .then(enqueue_message);
+    let dead_letters = warp::path!("dead-letters")
+        .and(warp::get())
+        .and(with_state.clone())
+        .then(list_dead_letters);
+    let reinject = warp::path!("dead-letters" / u32 / u32 / "reinject")
+        .and(warp::post())
+        .and(with_state.clone())
+        .then(reinject_dead_letter);
+    let profitability = warp::path!("profitability")
+        .and(warp::get())
+        .and(with_state.clone())
+        .then(profitability_summary);
+    let clear_cache = warp::path!("ism-cache")
+        .and(warp::delete())
+        .and(with_state.clone())
+        .map(|state: Arc<AdminState>| {
+            state.ism_cache.clear();
+            status_reply(StatusCode::OK, "ISM cache cleared")
+        });
+    let invalidate_cache = warp::path!("ism-cache" / u32 / H256)
+        .and(warp::delete())
+        .and(with_state)
+        .map(|destination: u32, ism: H256, state: Arc<AdminState>| {
+            state.ism_cache.invalidate(destination, ism);
+            status_reply(StatusCode::OK, "ISM cache entries invalidated")
+        });
+
+    with_auth(token)
+        .and(
+            list.map(Reply::into_response)
+                .or(retry.map(Reply::into_response))
+                .unify()
+                .or(drop.map(Reply::into_response))
+                .unify()
+                .or(enqueue.map(Reply::into_response))
+                .unify()
+                .or(dead_letters.map(Reply::into_response))
+                .unify()
+                .or(reinject.map(Reply::into_response))
+                .unify()
+                .or(profitability.map(Reply::into_response))
+                .unify()
+                .or(clear_cache.map(Reply::into_response))
+                .unify()
+                .or(invalidate_cache.map(Reply::into_response))
+                .unify(),
+        )
+        .recover(handle_rejection)
+}
+
+/// Reject any request which does not present the expected bearer token.
+fn with_auth(token: Arc<String>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let token = token.clone();
+            async move {
+                match header.as_deref().and_then(|h| h.strip_prefix("Bearer ")) {
+                    Some(provided) if tokens_match(provided, &token) => Ok(()),
+                    _ => Err(warp::reject::custom(Unauthorized)),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Compare tokens in constant time so the token can't be guessed byte by
+/// byte from response timings.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    provided.len() == expected.len()
+        && provided
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let status = if err.find::<Unauthorized>().is_some() {
+        StatusCode::UNAUTHORIZED
+    } else if err.is_not_found() {
+        StatusCode::NOT_FOUND
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        StatusCode::METHOD_NOT_ALLOWED
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    Ok(status_reply(
+        status,
+        status.canonical_reason().unwrap_or_default(),
+    ))
+}
+
+fn status_reply(status: StatusCode, msg: impl Into<String>) -> WithStatus<String> {
+    reply::with_status(msg.into(), status)
+}
+
+async fn list_operations(state: Arc<AdminState>) -> impl Reply {
+    let now = Instant::now();
+    let mut operations = HashMap::with_capacity(state.queues.len());
+    for (domain, queues) in state.queues.values() {
+        let mut infos = Vec::new();
+        for (name, queue) in [("prepare", &queues.prepare), ("confirm", &queues.confirm)] {
+            infos.extend(
+                queue
+                    .lock()
+                    .await
+                    .iter()
+                    .map(|Reverse(op)| OperationInfo::new(op, name, now)),
+            );
+        }
+        operations.insert(domain.name().to_owned(), infos);
+    }
+    reply::json(&operations)
+}
+
+async fn retry_operation(id: H256, state: Arc<AdminState>) -> WithStatus<String> {
+    match state.remove_operation(id).await {
+        Some((queue, mut op)) => {
+            op.retry_now();
+            queue.lock().await.push(Reverse(op));
+            info!(?id, "Operation scheduled for retry through the admin API");
+            status_reply(StatusCode::OK, "Operation will be retried")
+        }
+        None => status_reply(StatusCode::NOT_FOUND, "Operation is not queued"),
+    }
+}
+
+/// Drop a queued operation and dead-letter its message, which also forgets
+/// its delivery state, so it isn't restored or relayed again after a restart.
+async fn drop_operation(id: H256, state: Arc<AdminState>) -> WithStatus<String> {
+    let Some((queue, op)) = state.remove_operation(id).await else {
+        return status_reply(StatusCode::NOT_FOUND, "Operation is not queued");
+    };
+    let DynPendingOperation::PendingMessage(pm) = op.as_ref();
+    let message = &pm.message;
+    let stored: eyre::Result<()> = match state.dbs.get(&message.origin) {
+        Some(db) => db
+            .store_dead_letter_by_message_id(
+                &id,
+                &dead_letter(
+                    message,
+                    DeadLetterClass::DroppedByOperator,
+                    "Dropped through the admin API",
+                ),
+            )
+            .and_then(|_| db.delete_pending_message_state_by_message_id(&id))
+            .map_err(Into::into),
+        None => Err(eyre!("No db for origin {}", message.origin)),
+    };
+    if let Err(e) = stored {
+        warn!(error=?e, ?id, "Failed to dead-letter dropped operation");
+        // Keep the operation rather than losing track of the message
+        queue.lock().await.push(Reverse(op));
+        return status_reply(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to dead-letter operation",
+        );
+    }
+    state.release_operation(id).await;
+    info!(?id, "Operation dropped through the admin API");
+    status_reply(StatusCode::OK, "Operation dropped")
+}
+
+async fn enqueue_message(origin: u32, nonce: u32, state: Arc<AdminState>) -> WithStatus<String> {
+    enqueue(origin, nonce, &state, false).await
+}
+
+async fn list_dead_letters(state: Arc<AdminState>) -> WithStatus<reply::Json> {
+    let mut dead_letters = Vec::new();
+    for (origin, db) in &state.dbs {
+        match db.retrieve_dead_letters() {
+            Ok(dls) => dead_letters.extend(dls),
+            Err(e) => {
+                warn!(error=?e, origin, "Failed to read dead letters from db");
+                return reply::with_status(
+                    reply::json(&"Failed to read dead letters"),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                );
+            }
+        }
+    }
+    dead_letters.sort_by_key(|dl| (dl.message.origin, dl.message.nonce));
+    let infos: Vec<DeadLetterInfo> = dead_letters.into_iter().map(Into::into).collect();
+    reply::with_status(reply::json(&infos), StatusCode::OK)
+}
+
+async fn profitability_summary(state: Arc<AdminState>) -> WithStatus<reply::Json> {
+    match state.profitability.summary() {
+        Ok(summary) => reply::with_status(reply::json(&summary), StatusCode::OK),
+        Err(e) => {
+            warn!(error=?e, "Failed to read profitability totals from db");
+            reply::with_status(
+                reply::json(&"Failed to read profitability totals"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        }
+    }
+}
+
+async fn reinject_dead_letter(
+    origin: u32,
+    nonce: u32,
+    state: Arc<AdminState>,
+) -> WithStatus<String> {
+    enqueue(origin, nonce, &state, true).await
+}
+
+/// Send a message to the submitter of its destination. Dead-lettered messages
+/// can only be queued if `from_dead_letters` is set, in which case their
+/// delivery state is reset. Messages which were delivered, which the message
+/// processor will send to the submitter itself or which a submitter already
+/// holds are rejected.
+async fn enqueue(
+    origin: u32,
+    nonce: u32,
+    state: &AdminState,
+    from_dead_letters: bool,
+) -> WithStatus<String> {
+    let Some(db) = state.dbs.get(&origin) else {
+        return status_reply(StatusCode::NOT_FOUND, "Unknown origin");
+    };
+    let message = match db.retrieve_message_by_nonce(nonce) {
+        Ok(Some(message)) => message,
+        Ok(None) => return status_reply(StatusCode::NOT_FOUND, "Message has not been indexed"),
+        Err(e) => {
+            warn!(error=?e, origin, nonce, "Failed to read message from db");
+            return status_reply(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read message");
+        }
+    };
+    let key = ContextKey {
+        origin,
+        destination: message.destination,
+    };
+    let (Some(ctx), Some(send_channel)) = (
+        state.msg_ctxs.get(&key),
+        state.send_channels.get(&message.destination),
+    ) else {
+        return status_reply(
+            StatusCode::BAD_REQUEST,
+            "Message destination is not relayed to",
+        );
+    };
+    let id = message.id();
+    if state.is_held(id).await {
+        return status_reply(StatusCode::CONFLICT, "Message is already queued");
+    }
+    match db.retrieve_processed_by_nonce(&nonce) {
+        Ok(Some(true)) => {
+            return status_reply(StatusCode::CONFLICT, "Message has already been delivered")
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!(error=?e, origin, nonce, "Failed to read message status from db");
+            return status_reply(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read message status",
+            );
+        }
+    }
+    // The processor sends messages it hasn't reached yet to the submitter
+    // itself, queueing them here would deliver them twice
+    let processor_nonce = state
+        .processor_nonces
+        .get(&origin)
+        .map_or(0, |nonce| nonce.load(Ordering::Relaxed));
+    if nonce >= processor_nonce {
+        return status_reply(
+            StatusCode::CONFLICT,
+            "Message has not been reached by the message processor yet",
+        );
+    }
+
+    let is_dead_letter = match db.retrieve_dead_letter_by_message_id(&id) {
+        Ok(dl) => dl.is_some(),
+        Err(e) => {
+            warn!(error=?e, origin, nonce, "Failed to read dead letter from db");
+            return status_reply(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to read dead letter",
+            );
+        }
+    };
+    match (from_dead_letters, is_dead_letter) {
+        (true, false) => {
+            return status_reply(StatusCode::NOT_FOUND, "Message is not dead-lettered")
+        }
+        (false, true) => {
+            return status_reply(
+                StatusCode::CONFLICT,
+                "Message is dead-lettered, re-inject it instead",
+            )
+        }
+        (true, true) => {
+            // Start from scratch so the message isn't given up on right away
+            let reset = db
+                .delete_pending_message_state_by_message_id(&id)
+                .and_then(|_| db.delete_dead_letter_by_message_id(&id));
+            if let Err(e) = reset {
+                warn!(error=?e, origin, nonce, "Failed to remove dead letter");
+                return status_reply(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to remove dead letter",
+                );
+            }
+        }
+        (false, false) => {}
+    }
+
+    let Some(prover_sync) = state.prover_syncs.get(&origin) else {
+        return status_reply(StatusCode::NOT_FOUND, "Unknown origin");
+    };
+    // The metadata builder needs the merkle tree to include the message
+    if let Err(e) = prover_sync.write().await.update_to_index(nonce).await {
+        warn!(error=?e, origin, nonce, "Failed to update merkle tree");
+        return status_reply(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to update merkle tree",
+        );
+    }
+
+    let op = PendingMessage::from_persisted_state(message, ctx.clone());
+    if send_channel.send(Box::new(op.into())).is_err() {
+        return status_reply(StatusCode::SERVICE_UNAVAILABLE, "Submitter has stopped");
+    }
+    info!(?id, origin, nonce, "Message enqueued through the admin API");
+    status_reply(StatusCode::ACCEPTED, "Message enqueued")
+}
+
+impl AdminState {
+    /// Take the operation with the given id out of whichever queue holds it.
+    /// Operations which are currently being worked on by the submitter are
+    /// not in any queue and can't be found.
+    async fn remove_operation(&self, id: H256) -> Option<(OpQueue, Box<DynPendingOperation>)> {
+        for (_, queues) in self.queues.values() {
+            for queue in [&queues.prepare, &queues.confirm] {
+                let mut heap = queue.lock().await;
+                if !heap.iter().any(|Reverse(op)| op.id() == id) {
+                    continue;
+                }
+                let (mut matching, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut *heap)
+                    .into_iter()
+                    .partition(|Reverse(op)| op.id() == id);
+                *heap = rest.into_iter().collect();
+                return matching.pop().map(|Reverse(op)| (queue.clone(), op));
+            }
+        }
+        None
+    }
+
+    /// Whether a submitter holds the operation with the given id, whether it
+    /// is queued or being prepared, submitted or confirmed.
+    async fn is_held(&self, id: H256) -> bool {
+        for (_, queues) in self.queues.values() {
+            if queues.held.lock().await.contains(&id) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Tell the submitters an operation which was taken out of their queues
+    /// is no longer held.
+    async fn release_operation(&self, id: H256) {
+        for (_, queues) in self.queues.values() {
+            queues.held.lock().await.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use tokio::sync::mpsc;
+    use warp::hyper::body::Bytes;
+
+    use hyperlane_base::{
+        db::{test_utils, PendingMessageState},
+        ChainConf, CoreMetrics,
+    };
+    use hyperlane_core::{HyperlaneLogStore, HyperlaneMessage, LogMeta, U256};
+    use hyperlane_test::mocks::{
+        MockHyperlaneProvider, MockMailboxContract, MockValidatorAnnounceContract,
+    };
+
+    use super::*;
+    use crate::msg::{
+        gas_payment::GasPaymentEnforcer, metadata::BaseMetadataBuilder,
+        pending_message::MessageSubmissionMetrics,
+    };
+
+    const TOKEN: &str = "secret";
+
+    /// Admin state relaying messages from the db's domain to itself. Returns
+    /// the submitter queues and the receiving end of the submitter channel.
+    fn admin_state(
+        db: HyperlaneRocksDB,
+    ) -> (
+        AdminState,
+        SubmitterQueues,
+        mpsc::UnboundedReceiver<Box<DynPendingOperation>>,
+    ) {
+        let domain = db.domain().clone();
+        let metrics = Arc::new(CoreMetrics::new("test", 0, prometheus::Registry::new()).unwrap());
+        let prover_sync = Arc::new(RwLock::new(MerkleTreeBuilder::new(db.clone()).unwrap()));
+        let ism_cache = Arc::new(IsmCache::new(Duration::from_secs(60), &metrics));
+        let profitability = Arc::new(ProfitabilityTracker::new(
+            HashMap::from([(domain.id(), db.clone())]),
+            [&domain],
+            &metrics,
+        ));
+        let chain_conf = ChainConf {
+            domain: domain.clone(),
+            signer: None,
+            signers: vec![],
+            signer_pool: Default::default(),
+            finality_blocks: 0,
+            addresses: Default::default(),
+            connection: None,
+            metrics_conf: Default::default(),
+            index: Default::default(),
+            transaction_replacement: Default::default(),
+        };
+        let ctx = MessageContext {
+            destination_mailbox: Arc::new(MockMailboxContract::new()),
+            origin_db: db.clone(),
+            origin_provider: Arc::new(MockHyperlaneProvider::new()),
+            metadata_builder: BaseMetadataBuilder::new(
+                chain_conf,
+                prover_sync.clone(),
+                Arc::new(MockValidatorAnnounceContract::new()),
+                false,
+                metrics.clone(),
+                ism_cache.clone(),
+                5,
+            ),
+            origin_gas_payment_enforcer: Arc::new(
+                GasPaymentEnforcer::new(vec![], db.clone()).unwrap(),
+            ),
+            transaction_gas_limit: None,
+            destination_finality_blocks: 0,
+            max_retries: None,
+            retry_policies: vec![],
+            dry_run: None,
+            profitability: profitability.clone(),
+            metrics: MessageSubmissionMetrics::new(&metrics, &domain, &domain),
+        };
+        let queues = SubmitterQueues::default();
+        let (send_channel, receiver) = mpsc::unbounded_channel();
+        let key = ContextKey {
+            origin: domain.id(),
+            destination: domain.id(),
+        };
+        let state = AdminState {
+            queues: HashMap::from([(domain.id(), (domain.clone(), queues.clone()))]),
+            send_channels: HashMap::from([(domain.id(), send_channel)]),
+            msg_ctxs: HashMap::from([(key, Arc::new(ctx))]),
+            dbs: HashMap::from([(domain.id(), db)]),
+            prover_syncs: HashMap::from([(domain.id(), prover_sync)]),
+            processor_nonces: HashMap::from([(domain.id(), Arc::new(AtomicU32::new(0)))]),
+            ism_cache,
+            profitability,
+        };
+        (state, queues, receiver)
+    }
+
+    /// Store a message from the domain to itself with nonce 0.
+    async fn store_message(db: &HyperlaneRocksDB, domain: &HyperlaneDomain) -> HyperlaneMessage {
+        let message = HyperlaneMessage {
+            origin: domain.id(),
+            destination: domain.id(),
+            ..Default::default()
+        };
+        let meta = LogMeta {
+            address: H256::zero(),
+            block_number: 1,
+            block_hash: H256::zero(),
+            transaction_hash: H256::zero(),
+            transaction_index: 0,
+            log_index: U256::zero(),
+        };
+        db.store_logs(&[(message.clone(), meta)]).await.unwrap();
+        message
+    }
+
+    async fn request(
+        api: &(impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone + 'static),
+        method: &str,
+        path: &str,
+        auth: Option<&str>,
+    ) -> warp::http::Response<Bytes> {
+        let mut request = warp::test::request().method(method).path(path);
+        if let Some(auth) = auth {
+            request = request.header("authorization", auth);
+        }
+        request.reply(api).await
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secreT", "secret"));
+        assert!(!tokens_match("secret2", "secret"));
+        assert!(!tokens_match("", "secret"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_requests_without_token() {
+        test_utils::run_test_db(|db| async move {
+            let domain = HyperlaneDomain::new_test_domain("test_rejects_requests_without_token");
+            let (state, _, _receiver) = admin_state(HyperlaneRocksDB::new(&domain, db));
+            let api = routes(TOKEN.to_owned(), state);
+            let bearer = format!("Bearer {TOKEN}");
+
+            for auth in [None, Some("Bearer wrong"), Some(TOKEN)] {
+                let res = request(&api, "GET", "/operations", auth).await;
+                assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+                // Unknown routes don't reveal anything either
+                let res = request(&api, "GET", "/unknown", auth).await;
+                assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            }
+
+            let res = request(&api, "GET", "/operations", Some(&bearer)).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let res = request(&api, "GET", "/unknown", Some(&bearer)).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_and_retry_message() {
+        test_utils::run_test_db(|db| async move {
+            let domain = HyperlaneDomain::new_test_domain("test_enqueue_and_retry_message");
+            let db = HyperlaneRocksDB::new(&domain, db);
+            let message = store_message(&db, &domain).await;
+            // The submitter backed off from the message before a restart
+            let backoff_until = SystemTime::now() + Duration::from_secs(3600);
+            let state = PendingMessageState {
+                next_attempt_after: Some(
+                    backoff_until.duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                ),
+                ..Default::default()
+            };
+            db.store_pending_message_state_by_message_id(&message.id(), &state)
+                .unwrap();
+            let (state, queues, mut receiver) = admin_state(db.clone());
+            let origin = domain.id();
+            let processor_nonce = state.processor_nonces[&origin].clone();
+            let api = routes(TOKEN.to_owned(), state);
+            let auth = format!("Bearer {TOKEN}");
+            let auth = Some(auth.as_str());
+
+            let res = request(&api, "POST", &format!("/messages/{origin}/1/enqueue"), auth).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+            let res = request(&api, "POST", "/messages/1234/0/enqueue", auth).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+            // The processor will send the message to the submitter itself
+            let enqueue = format!("/messages/{origin}/0/enqueue");
+            let res = request(&api, "POST", &enqueue, auth).await;
+            assert_eq!(res.status(), StatusCode::CONFLICT);
+            processor_nonce.store(1, Ordering::Relaxed);
+
+            let res = request(&api, "POST", &enqueue, auth).await;
+            assert_eq!(res.status(), StatusCode::ACCEPTED);
+            let op = receiver.try_recv().unwrap();
+            assert_eq!(op.id(), message.id());
+            assert!(op._next_attempt_after().is_some());
+            queues.held.lock().await.insert(op.id());
+            queues.prepare.lock().await.push(Reverse(op));
+            let res = request(&api, "POST", &enqueue, auth).await;
+            assert_eq!(res.status(), StatusCode::CONFLICT);
+
+            let retry = format!("/operations/{:?}/retry", message.id());
+            let res = request(&api, "POST", &retry, auth).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            {
+                let queue = queues.prepare.lock().await;
+                let Reverse(op) = queue.peek().unwrap();
+                assert_eq!(op.id(), message.id());
+                assert_eq!(op._next_attempt_after(), None);
+            }
+
+            let retry = format!("/operations/{:?}/retry", H256::repeat_byte(1));
+            let res = request(&api, "POST", &retry, auth).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+            // Operations which are being worked on are not in a queue but are
+            // still held by the submitter
+            let Reverse(op) = queues.prepare.lock().await.pop().unwrap();
+            let res = request(&api, "POST", &enqueue, auth).await;
+            assert_eq!(res.status(), StatusCode::CONFLICT);
+
+            // Delivered messages can't be queued again
+            queues.held.lock().await.remove(&op.id());
+            db.store_processed_by_nonce(&0, &true).unwrap();
+            let res = request(&api, "POST", &enqueue, auth).await;
+            assert_eq!(res.status(), StatusCode::CONFLICT);
+            assert!(receiver.try_recv().is_err());
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_drop_operation_dead_letters_message() {
+        test_utils::run_test_db(|db| async move {
+            let domain =
+                HyperlaneDomain::new_test_domain("test_drop_operation_dead_letters_message");
+            let db = HyperlaneRocksDB::new(&domain, db);
+            let message = store_message(&db, &domain).await;
+            db.store_pending_message_state_by_message_id(
+                &message.id(),
+                &PendingMessageState::default(),
+            )
+            .unwrap();
+            let (state, queues, _receiver) = admin_state(db.clone());
+            let ctx = state.msg_ctxs.values().next().unwrap().clone();
+            let op = PendingMessage::from_persisted_state(message.clone(), ctx);
+            queues.held.lock().await.insert(message.id());
+            queues
+                .prepare
+                .lock()
+                .await
+                .push(Reverse(Box::new(op.into())));
+            let api = routes(TOKEN.to_owned(), state);
+            let auth = format!("Bearer {TOKEN}");
+            let auth = Some(auth.as_str());
+
+            let drop = format!("/operations/{:?}", message.id());
+            let res = request(&api, "DELETE", &drop, auth).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(queues.prepare.lock().await.is_empty());
+            assert!(queues.held.lock().await.is_empty());
+
+            // The message is not restored or relayed again after a restart
+            let dead_letter = db
+                .retrieve_dead_letter_by_message_id(&message.id())
+                .unwrap()
+                .unwrap();
+            assert_eq!(dead_letter.error_class, "dropped_by_operator");
+            assert!(db
+                .retrieve_pending_message_state_by_message_id(&message.id())
+                .unwrap()
+                .is_none());
+
+            let res = request(&api, "DELETE", &drop, auth).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        })
+        .await
+    }
+}
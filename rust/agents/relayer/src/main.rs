@@ -13,6 +13,7 @@ use hyperlane_base::agent_main;
 
 use crate::relayer::Relayer;
 
+mod admin;
 mod merkle_tree_builder;
 mod msg;
 mod prover;
@@ -1,9 +1,9 @@
 use std::fmt::Display;
 
 use eyre::Result;
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
-use hyperlane_base::db::{DbError, HyperlaneRocksDB};
+use hyperlane_base::db::{DbError, HyperlaneRocksDB, MerkleTreeSnapshot};
 use hyperlane_core::{
     accumulator::{incremental::IncrementalMerkle, merkle::Proof},
     ChainCommunicationError, H256,
@@ -11,12 +11,22 @@ use hyperlane_core::{
 
 use crate::prover::{Prover, ProverError};
 
+/// Number of leaves between the snapshots of the tree which are persisted so
+/// it can be restored on startup without re-ingesting every message.
+const SNAPSHOT_INTERVAL: u32 = 1_000;
+
 /// Struct to sync prover.
+///
+/// The tree is restored from the latest snapshot in the db, so proofs can
+/// only be built for the messages after it until the tree is rebuilt from
+/// every message, which happens when an earlier message is requested.
 #[derive(Debug)]
 pub struct MerkleTreeBuilder {
     db: HyperlaneRocksDB,
     prover: Prover,
     incremental: IncrementalMerkle,
+    /// Number of message rollbacks in the db which have been accounted for
+    seen_rollbacks: u32,
 }
 
 impl Display for MerkleTreeBuilder {
@@ -68,14 +78,22 @@ pub enum MerkleTreeBuilderError {
 }
 
 impl MerkleTreeBuilder {
-    pub fn new(db: HyperlaneRocksDB) -> Self {
+    /// Create a builder, restoring the tree from the latest snapshot in the db
+    /// which is consistent with the messages stored in it.
+    pub fn new(db: HyperlaneRocksDB) -> Result<Self, MerkleTreeBuilderError> {
         let prover = Prover::default();
         let incremental = IncrementalMerkle::default();
-        Self {
+        let mut builder = Self {
             prover,
             incremental,
             db,
+            seen_rollbacks: 0,
+        };
+        if let Some(latest) = builder.db.retrieve_latest_merkle_tree_snapshot_count()? {
+            let rollbacks = builder.db.retrieve_message_rollback_count()?;
+            builder.restore_snapshot(latest, rollbacks)?;
         }
+        Ok(builder)
     }
 
     #[instrument(err, skip(self), level="debug", fields(prover_latest_index=self.count()-1))]
@@ -96,6 +114,12 @@ impl MerkleTreeBuilder {
                 self.prover.ingest(leaf).expect("!tree full");
                 self.incremental.ingest(leaf);
                 assert_eq!(self.prover.root(), self.incremental.root());
+                if self.count() % SNAPSHOT_INTERVAL == 0 {
+                    self.db.store_merkle_tree_snapshot(&MerkleTreeSnapshot {
+                        tree: self.incremental,
+                        rollbacks: self.seen_rollbacks,
+                    })?;
+                }
                 Ok(())
             }
             Ok(None) => {
@@ -110,8 +134,151 @@ impl MerkleTreeBuilder {
         self.prover.count() as u32
     }
 
+    /// Replace the tree with `tree` followed by `leaves`, e.g. when they were
+    /// fetched from the chain rather than the db. Nothing is persisted, so
+    /// this must not be mixed with syncing the tree from the db.
+    pub fn reset_to_leaves(
+        &mut self,
+        tree: IncrementalMerkle,
+        leaves: impl IntoIterator<Item = H256>,
+    ) -> Result<(), MerkleTreeBuilderError> {
+        self.prover = Prover::from_incremental(&tree);
+        self.incremental = tree;
+        for leaf in leaves {
+            self.prover.ingest(leaf)?;
+            self.incremental.ingest(leaf);
+        }
+        Ok(())
+    }
+
+    /// Reset the tree to the latest snapshot with at most `max_count` leaves
+    /// which is consistent with the messages in the db, or to an empty tree
+    /// if there is none. `rollbacks` is the number of message rollbacks the
+    /// db had recorded before the snapshots are checked.
+    fn restore_snapshot(
+        &mut self,
+        max_count: u32,
+        rollbacks: u32,
+    ) -> Result<(), MerkleTreeBuilderError> {
+        self.prover = Prover::default();
+        self.incremental = IncrementalMerkle::default();
+        self.seen_rollbacks = rollbacks;
+
+        let mut count = max_count - max_count % SNAPSHOT_INTERVAL;
+        while count > 0 {
+            if let Some(snapshot) = self.consistent_snapshot(count)? {
+                info!(count, "Restored merkle tree from snapshot");
+                self.prover = Prover::from_incremental(&snapshot.tree);
+                self.incremental = snapshot.tree;
+                break;
+            }
+            count -= SNAPSHOT_INTERVAL;
+        }
+        Ok(())
+    }
+
+    /// The snapshot with `count` leaves, if there is one and it is consistent
+    /// with the messages in the db. To keep this cheap, only the messages
+    /// since the previous snapshot are checked by ingesting them into it.
+    fn consistent_snapshot(
+        &self,
+        count: u32,
+    ) -> Result<Option<MerkleTreeSnapshot>, MerkleTreeBuilderError> {
+        let Some(snapshot) = self.db.retrieve_merkle_tree_snapshot_by_count(&count)? else {
+            return Ok(None);
+        };
+        let (_, reorged_nonce) = self
+            .db
+            .retrieve_message_rollbacks_since(snapshot.rollbacks)?;
+        if let Some(reorged_nonce) = reorged_nonce.filter(|nonce| *nonce < count) {
+            debug!(
+                count,
+                reorged_nonce, "Merkle tree snapshot includes reorged messages"
+            );
+            return Ok(None);
+        }
+
+        let previous_count = count - SNAPSHOT_INTERVAL;
+        let mut tree = if previous_count == 0 {
+            IncrementalMerkle::default()
+        } else {
+            match self
+                .db
+                .retrieve_merkle_tree_snapshot_by_count(&previous_count)?
+            {
+                Some(previous) => previous.tree,
+                None => {
+                    debug!(
+                        count,
+                        "Merkle tree snapshot has no previous snapshot to check it against"
+                    );
+                    return Ok(None);
+                }
+            }
+        };
+        for nonce in previous_count..count {
+            let Some(leaf) = self.db.retrieve_message_id_by_nonce(&nonce)? else {
+                warn!(count, nonce, "Message in merkle tree snapshot is missing from the db");
+                return Ok(None);
+            };
+            tree.ingest(leaf);
+        }
+        if tree.root() != snapshot.tree.root() {
+            warn!(
+                count,
+                "Merkle tree snapshot does not match the messages in the db"
+            );
+            return Ok(None);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Rebuild the tree from every message so the messages before the
+    /// snapshot it was restored from can be proven.
+    fn rebuild_pruned(&mut self) -> Result<(), MerkleTreeBuilderError> {
+        let count = self.count();
+        info!(
+            pruned = self.prover.pruned(),
+            count, "Rebuilding the merkle tree to prove a message before its snapshot"
+        );
+        self.prover = Prover::default();
+        self.incremental = IncrementalMerkle::default();
+        for nonce in 0..count {
+            self.ingest_nonce(nonce)?;
+        }
+        Ok(())
+    }
+
+    /// If messages the tree was built from were rolled back because of a
+    /// reorg on the origin, rebuild the tree up to the first of them so the
+    /// re-indexed messages can be ingested.
+    fn handle_rollbacks(&mut self) -> Result<(), MerkleTreeBuilderError> {
+        let (rollbacks, reorged_nonce) = self
+            .db
+            .retrieve_message_rollbacks_since(self.seen_rollbacks)?;
+        self.seen_rollbacks = rollbacks;
+        let Some(reorged_nonce) = reorged_nonce.filter(|nonce| *nonce < self.count()) else {
+            return Ok(());
+        };
+
+        warn!(
+            reorged_nonce,
+            count = self.count(),
+            "Messages in the merkle tree were reorged out, rebuilding it"
+        );
+        self.restore_snapshot(reorged_nonce, rollbacks)?;
+        for nonce in self.count()..reorged_nonce {
+            self.ingest_nonce(nonce)?;
+        }
+        Ok(())
+    }
+
     #[instrument(err, skip(self), level = "debug")]
     pub async fn update_to_index(&mut self, index: u32) -> Result<(), MerkleTreeBuilderError> {
+        self.handle_rollbacks()?;
+        if index < self.prover.pruned() as u32 {
+            self.rebuild_pruned()?;
+        }
         if index >= self.count() {
             let starting_index = self.prover.count() as u32;
             for i in starting_index..=index {
@@ -132,3 +299,71 @@ impl MerkleTreeBuilder {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use hyperlane_base::db::test_utils;
+    use hyperlane_core::HyperlaneDomain;
+
+    use super::*;
+
+    fn store_leaves(db: &HyperlaneRocksDB, nonces: std::ops::Range<u32>, salt: u64) {
+        for nonce in nonces {
+            let leaf = H256::from_low_u64_be((salt << 32) | nonce as u64);
+            db.store_message_id_by_nonce(&nonce, &leaf).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restores_from_snapshot() {
+        test_utils::run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("test_restores_from_snapshot"),
+                db,
+            );
+            store_leaves(&db, 0..2500, 1);
+
+            let mut builder = MerkleTreeBuilder::new(db.clone()).unwrap();
+            builder.update_to_index(2499).await.unwrap();
+            let root = builder.prover.root();
+            let proof = builder.get_proof(100, 2499).unwrap();
+
+            let mut restored = MerkleTreeBuilder::new(db.clone()).unwrap();
+            assert_eq!(restored.count(), 2000);
+            restored.update_to_index(2499).await.unwrap();
+            assert_eq!(restored.prover.root(), root);
+            assert!(restored.get_proof(2100, 2499).is_ok());
+            assert!(restored.get_proof(100, 2499).is_err());
+
+            // proving a message before the snapshot rebuilds the whole tree
+            restored.update_to_index(100).await.unwrap();
+            assert_eq!(restored.get_proof(100, 2499).unwrap(), proof);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_skips_inconsistent_snapshots() {
+        test_utils::run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("test_skips_inconsistent_snapshots"),
+                db,
+            );
+            store_leaves(&db, 0..2500, 1);
+            let mut builder = MerkleTreeBuilder::new(db.clone()).unwrap();
+            builder.update_to_index(2499).await.unwrap();
+
+            // the latest snapshot includes reorged messages
+            db.rollback_messages(1500).unwrap();
+            store_leaves(&db, 1500..2500, 2);
+            let restored = MerkleTreeBuilder::new(db.clone()).unwrap();
+            assert_eq!(restored.count(), 1000);
+
+            // the earlier snapshot doesn't match the stored messages
+            store_leaves(&db, 500..501, 3);
+            let restored = MerkleTreeBuilder::new(db.clone()).unwrap();
+            assert_eq!(restored.count(), 0);
+        })
+        .await;
+    }
+}
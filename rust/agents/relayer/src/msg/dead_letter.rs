@@ -0,0 +1,73 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use hyperlane_base::db::DeadLetter;
+use hyperlane_core::HyperlaneMessage;
+
+/// Why the relayer gave up on a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum DeadLetterClass {
+    /// The recipient is not a contract so the message can't be delivered.
+    RecipientNotContract,
+    /// Delivery failed more times than the configured max retries.
+    RetriesExhausted,
+    /// The message was dispatched longer ago than the max age of its retry
+    /// policy.
+    MaxAgeExceeded,
+    /// An operator dropped the message through the admin API.
+    DroppedByOperator,
+}
+
+/// Build the dead letter to store for a message.
+pub fn dead_letter(
+    message: &HyperlaneMessage,
+    class: DeadLetterClass,
+    reason: impl Into<String>,
+) -> DeadLetter {
+    DeadLetter {
+        message: message.clone(),
+        error_class: <&str>::from(class).to_owned(),
+        reason: reason.into(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }
+}
+
+/// A dead-lettered message as exported through the admin API. It contains
+/// everything needed to inspect or deliver the message by other means.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterInfo {
+    id: String,
+    origin: u32,
+    destination: u32,
+    nonce: u32,
+    sender: String,
+    recipient: String,
+    /// Hex encoded message body
+    body: String,
+    error_class: String,
+    reason: String,
+    timestamp: u64,
+}
+
+impl From<DeadLetter> for DeadLetterInfo {
+    fn from(dl: DeadLetter) -> Self {
+        Self {
+            id: format!("{:?}", dl.message.id()),
+            origin: dl.message.origin,
+            destination: dl.message.destination,
+            nonce: dl.message.nonce,
+            sender: format!("{:?}", dl.message.sender),
+            recipient: format!("{:?}", dl.message.recipient),
+            body: format!("0x{}", hex::encode(&dl.message.body)),
+            error_class: dl.error_class,
+            reason: dl.reason,
+            timestamp: dl.timestamp,
+        }
+    }
+}
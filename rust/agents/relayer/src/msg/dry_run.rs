@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::{Context, Result};
+use prometheus::IntCounterVec;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{info_span, instrument::Instrumented, warn, Instrument};
+
+use hyperlane_base::CoreMetrics;
+use hyperlane_core::{HyperlaneDomain, HyperlaneMessage, U256};
+
+/// How often the report file is rewritten.
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What the relayer would have done with a message if it was not in dry-run
+/// mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::IntoStaticStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DryRunOutcome {
+    /// The message was already delivered by someone else.
+    AlreadyDelivered,
+    /// The message would have been dropped.
+    Dropped,
+    /// The message is not ready to be delivered yet, e.g. because there is no
+    /// quorum or the gas payment is insufficient.
+    NotReady,
+    /// The message would have been submitted.
+    WouldDeliver,
+}
+
+/// The most recent dry-run outcome of a message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DryRunRecord {
+    message_id: String,
+    origin: u32,
+    destination: u32,
+    nonce: u32,
+    outcome: DryRunOutcome,
+    reason: Option<String>,
+    gas_limit: Option<String>,
+}
+
+/// Collects what the relayer would have done with each message in dry-run
+/// mode. Outcomes are counted in metrics and the latest outcome of each
+/// message is periodically written to a JSON report, sorted by origin and
+/// nonce so reports from different runs can be diffed.
+#[derive(Debug)]
+pub struct DryRunReporter {
+    path: PathBuf,
+    records: Mutex<BTreeMap<(u32, u32), DryRunRecord>>,
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain the message is destined for.
+    /// - `outcome`: What the relayer would have done.
+    outcomes: IntCounterVec,
+}
+
+impl DryRunReporter {
+    pub fn new(path: PathBuf, metrics: &CoreMetrics) -> Self {
+        let outcomes = metrics
+            .new_int_counter(
+                "dry_run_outcomes",
+                "Number of times each outcome was reached while preparing messages in dry-run mode",
+                &["origin", "remote", "outcome"],
+            )
+            .expect("failed to register dry_run_outcomes metric");
+        Self {
+            path,
+            records: Default::default(),
+            outcomes,
+        }
+    }
+
+    pub fn record(
+        &self,
+        origin: &HyperlaneDomain,
+        destination: &HyperlaneDomain,
+        message: &HyperlaneMessage,
+        outcome: DryRunOutcome,
+        reason: Option<String>,
+        gas_limit: Option<U256>,
+    ) {
+        self.outcomes
+            .with_label_values(&[origin.name(), destination.name(), outcome.into()])
+            .inc();
+        let record = DryRunRecord {
+            message_id: format!("{:?}", message.id()),
+            origin: message.origin,
+            destination: message.destination,
+            nonce: message.nonce,
+            outcome,
+            reason,
+            gas_limit: gas_limit.map(|l| l.to_string()),
+        };
+        self.records
+            .lock()
+            .unwrap()
+            .insert((message.origin, message.nonce), record);
+    }
+
+    /// Write the latest outcome of every message to the report file.
+    pub fn write_report(&self) -> Result<()> {
+        let report = {
+            let records = self.records.lock().unwrap();
+            serde_json::to_vec_pretty(&records.values().collect::<Vec<_>>())?
+        };
+        // Write to a temporary file first so the report is never partially
+        // written
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, report)
+            .and_then(|_| std::fs::rename(&tmp_path, &self.path))
+            .with_context(|| format!("When writing dry-run report to {:?}", self.path))
+    }
+
+    /// Periodically write the report file.
+    pub fn spawn(self: Arc<Self>) -> Instrumented<JoinHandle<Result<()>>> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REPORT_INTERVAL).await;
+                if let Err(e) = self.write_report() {
+                    warn!(error=?e, "Failed to write dry-run report");
+                }
+            }
+        })
+        .instrument(info_span!("DryRunReporter"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report_keeps_latest_outcome_sorted() {
+        let path = std::env::temp_dir().join(format!("dry_run_report_{}.json", std::process::id()));
+        let metrics = CoreMetrics::new("test", 0, prometheus::Registry::new()).unwrap();
+        let reporter = DryRunReporter::new(path.clone(), &metrics);
+
+        let origin = HyperlaneDomain::new_test_domain("origin");
+        let destination = HyperlaneDomain::new_test_domain("destination");
+        let message = |nonce| HyperlaneMessage {
+            nonce,
+            origin: origin.id(),
+            destination: destination.id(),
+            ..Default::default()
+        };
+        reporter.record(
+            &origin,
+            &destination,
+            &message(2),
+            DryRunOutcome::WouldDeliver,
+            None,
+            Some(100.into()),
+        );
+        reporter.record(
+            &origin,
+            &destination,
+            &message(1),
+            DryRunOutcome::NotReady,
+            Some("Could not fetch metadata".into()),
+            None,
+        );
+        reporter.record(
+            &origin,
+            &destination,
+            &message(1),
+            DryRunOutcome::WouldDeliver,
+            None,
+            Some(50.into()),
+        );
+        reporter.write_report().unwrap();
+
+        let report: Vec<serde_json::Value> =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0]["nonce"], 1);
+        assert_eq!(report[0]["outcome"], "would_deliver");
+        assert_eq!(report[0]["gasLimit"], "50");
+        assert_eq!(report[1]["nonce"], 2);
+        assert_eq!(
+            reporter
+                .outcomes
+                .with_label_values(&["origin", "destination", "would_deliver"])
+                .get(),
+            2
+        );
+    }
+}
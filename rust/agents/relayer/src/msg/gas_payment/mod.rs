@@ -1,4 +1,5 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use eyre::Result;
@@ -13,11 +14,14 @@ use hyperlane_core::{
 use crate::msg::gas_payment::policies::GasPaymentPolicyOnChainFeeQuoting;
 use crate::settings::{
     matching_list::MatchingList, GasPaymentEnforcementConf, GasPaymentEnforcementPolicy,
+    TokenPriceSourceConf,
 };
 
-use self::policies::{GasPaymentPolicyMinimum, GasPaymentPolicyNone};
+use self::policies::{GasPaymentPolicyMinimum, GasPaymentPolicyNone, GasPaymentPolicyUsdCost};
+use self::price_source::{HttpTokenPriceSource, StaticTokenPriceSource, TokenPriceSource};
 
 mod policies;
+mod price_source;
 
 #[async_trait]
 pub trait GasPaymentPolicy: Debug + Send + Sync {
@@ -47,7 +51,7 @@ impl GasPaymentEnforcer {
     pub fn new(
         policy_configs: impl IntoIterator<Item = GasPaymentEnforcementConf>,
         db: HyperlaneRocksDB,
-    ) -> Self {
+    ) -> Result<Self> {
         let policies = policy_configs
             .into_iter()
             .map(|cfg| {
@@ -60,12 +64,26 @@ impl GasPaymentEnforcer {
                         gas_fraction_numerator: n,
                         gas_fraction_denominator: d,
                     } => Box::new(GasPaymentPolicyOnChainFeeQuoting::new(n, d)),
+                    GasPaymentEnforcementPolicy::UsdCost {
+                        margin_percent,
+                        price_source,
+                    } => {
+                        let price_source: Arc<dyn TokenPriceSource> = match price_source {
+                            TokenPriceSourceConf::Static { prices } => {
+                                Arc::new(StaticTokenPriceSource::new(prices))
+                            }
+                            TokenPriceSourceConf::Http { url, cache_ttl } => {
+                                Arc::new(HttpTokenPriceSource::new(url, cache_ttl)?)
+                            }
+                        };
+                        Box::new(GasPaymentPolicyUsdCost::new(price_source, margin_percent))
+                    }
                 };
-                (p, cfg.matching_list)
+                Ok((p, cfg.matching_list))
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
-        Self { policies, db }
+        Ok(Self { policies, db })
     }
 }
 
@@ -162,7 +180,8 @@ mod test {
                     matching_list: Default::default(),
                 }],
                 hyperlane_db,
-            );
+            )
+            .unwrap();
 
             // Ensure that message without any payment is considered as not meeting the
             // requirement because it doesn't match the GasPaymentEnforcementPolicy
@@ -194,7 +213,8 @@ mod test {
                     matching_list,
                 }],
                 hyperlane_db,
-            );
+            )
+            .unwrap();
 
             assert!(matches!(
                 enforcer
@@ -237,7 +257,8 @@ mod test {
                     },
                 ],
                 hyperlane_db,
-            );
+            )
+            .unwrap();
 
             let sender: H256 = H160::from_str(sender_address).unwrap().into();
             let recipient: H256 = H160::from_str(recipient_address).unwrap().into();
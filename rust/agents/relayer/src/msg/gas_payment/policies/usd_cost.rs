@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use eyre::Result;
+use tracing::{debug, warn};
+
+use hyperlane_core::{
+    HyperlaneMessage, InterchainGasExpenditure, InterchainGasPayment, TxCostEstimate, U256,
+};
+
+use crate::msg::gas_payment::{price_source::TokenPriceSource, GasPaymentPolicy};
+
+/// Requires the payment on the origin chain to cover the cost of delivering
+/// the message on the destination chain, with both converted to USD.
+#[derive(Debug, new)]
+pub struct GasPaymentPolicyUsdCost {
+    price_source: Arc<dyn TokenPriceSource>,
+    /// Percentage added on top of the estimated cost which must also be paid.
+    margin_percent: u32,
+}
+
+#[async_trait]
+impl GasPaymentPolicy for GasPaymentPolicyUsdCost {
+    async fn message_meets_gas_payment_requirement(
+        &self,
+        message: &HyperlaneMessage,
+        current_payment: &InterchainGasPayment,
+        current_expenditure: &InterchainGasExpenditure,
+        tx_cost_estimate: &TxCostEstimate,
+    ) -> Result<Option<U256>> {
+        let origin_price = self.price_source.native_token_price(message.origin).await?;
+        let destination_price = self
+            .price_source
+            .native_token_price(message.destination)
+            .await?;
+        let (Some(origin_price), Some(destination_price)) = (origin_price, destination_price) else {
+            warn!(
+                origin = message.origin,
+                destination = message.destination,
+                "Missing native token price, unable to check if message paid for its delivery"
+            );
+            return Ok(None);
+        };
+
+        let paid = origin_price.usd_value(current_payment.payment);
+        // Tokens already spent attempting to deliver the message were spent
+        // on the destination
+        let spent = destination_price.usd_value(current_expenditure.tokens_used);
+        let cost = destination_price.usd_value(
+            tx_cost_estimate
+                .gas_limit
+                .saturating_mul(tx_cost_estimate.gas_price),
+        );
+        let required = cost * (100 + self.margin_percent) as f64 / 100.0;
+
+        if paid - spent >= required {
+            Ok(Some(tx_cost_estimate.gas_limit))
+        } else {
+            debug!(
+                paid_usd = paid,
+                spent_usd = spent,
+                required_usd = required,
+                "Payment does not cover the USD cost of delivery"
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use hyperlane_core::H256;
+
+    use super::*;
+    use crate::msg::gas_payment::price_source::StaticTokenPriceSource;
+    use crate::settings::TokenPrice;
+
+    const ORIGIN: u32 = 1;
+    const DESTINATION: u32 = 2;
+
+    fn policy(margin_percent: u32) -> GasPaymentPolicyUsdCost {
+        let prices = HashMap::from([
+            (
+                ORIGIN,
+                TokenPrice {
+                    usd: 2.0,
+                    decimals: 0,
+                },
+            ),
+            (
+                DESTINATION,
+                TokenPrice {
+                    usd: 1.0,
+                    decimals: 0,
+                },
+            ),
+        ]);
+        GasPaymentPolicyUsdCost::new(
+            Arc::new(StaticTokenPriceSource::new(prices)),
+            margin_percent,
+        )
+    }
+
+    fn message() -> HyperlaneMessage {
+        HyperlaneMessage {
+            origin: ORIGIN,
+            destination: DESTINATION,
+            ..Default::default()
+        }
+    }
+
+    fn current_payment(payment: u32) -> InterchainGasPayment {
+        InterchainGasPayment {
+            message_id: H256::zero(),
+            payment: payment.into(),
+            gas_amount: U256::zero(),
+        }
+    }
+
+    fn current_expenditure(tokens_used: u32) -> InterchainGasExpenditure {
+        InterchainGasExpenditure {
+            message_id: H256::zero(),
+            gas_used: U256::zero(),
+            tokens_used: tokens_used.into(),
+        }
+    }
+
+    /// Costs 1000 destination tokens, i.e. $1000 or 500 origin tokens
+    const COST_ESTIMATE: TxCostEstimate = TxCostEstimate {
+        gas_limit: U256([100, 0, 0, 0]),
+        gas_price: U256([10, 0, 0, 0]),
+        l2_gas_limit: None,
+    };
+
+    #[tokio::test]
+    async fn test_payment_covers_cost() {
+        let policy = policy(0);
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message(),
+                    &current_payment(500),
+                    &current_expenditure(0),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            Some(COST_ESTIMATE.gas_limit)
+        );
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message(),
+                    &current_payment(499),
+                    &current_expenditure(0),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_applies_margin() {
+        let policy = policy(10);
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message(),
+                    &current_payment(500),
+                    &current_expenditure(0),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message(),
+                    &current_payment(550),
+                    &current_expenditure(0),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            Some(COST_ESTIMATE.gas_limit)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accounts_for_expenditure() {
+        let policy = policy(0);
+        // $1100 paid, $200 already spent, $1000 needed
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message(),
+                    &current_payment(550),
+                    &current_expenditure(200),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_price() {
+        let policy = policy(0);
+        let message = HyperlaneMessage {
+            destination: 3,
+            ..message()
+        };
+        assert_eq!(
+            policy
+                .message_meets_gas_payment_requirement(
+                    &message,
+                    &current_payment(u32::MAX),
+                    &current_expenditure(0),
+                    &COST_ESTIMATE,
+                )
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use derive_new::new;
+use eyre::{Context, Result};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::settings::TokenPrice;
+
+/// Provides the prices of chains' native tokens so payments and costs on
+/// different chains can be compared.
+#[async_trait]
+pub trait TokenPriceSource: Debug + Send + Sync {
+    /// The price of the native token of the given domain, or None if the
+    /// price is unknown.
+    async fn native_token_price(&self, domain: u32) -> Result<Option<TokenPrice>>;
+}
+
+/// A price source with fixed prices from the config.
+#[derive(Debug, new)]
+pub struct StaticTokenPriceSource {
+    prices: HashMap<u32, TokenPrice>,
+}
+
+#[async_trait]
+impl TokenPriceSource for StaticTokenPriceSource {
+    async fn native_token_price(&self, domain: u32) -> Result<Option<TokenPrice>> {
+        Ok(self.prices.get(&domain).copied())
+    }
+}
+
+/// Timeout for requests to the token price endpoint.
+const HTTP_CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A price source which fetches prices from an HTTP endpoint and caches them
+/// for a while.
+///
+/// The endpoint must respond with a JSON object from domain ids to prices,
+/// e.g. `{"1": {"usd": 1850.5, "decimals": 18}}`. If refreshing the prices
+/// fails, the last fetched prices keep being used until a refresh succeeds.
+#[derive(Debug)]
+pub struct HttpTokenPriceSource {
+    client: reqwest::Client,
+    url: String,
+    cache_ttl: Duration,
+    cache: Mutex<Option<(Instant, Arc<HashMap<u32, TokenPrice>>)>>,
+}
+
+impl HttpTokenPriceSource {
+    pub fn new(url: String, cache_ttl: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_CLIENT_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            client,
+            url,
+            cache_ttl,
+            cache: Mutex::new(None),
+        })
+    }
+
+    async fn fetch_prices(&self) -> Result<HashMap<u32, TokenPrice>> {
+        let prices: HashMap<String, TokenPrice> = self
+            .client
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        prices
+            .into_iter()
+            .map(|(domain, price)| {
+                let domain = domain
+                    .parse()
+                    .with_context(|| format!("Invalid domain id `{domain}` in token prices"))?;
+                Ok((domain, price))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TokenPriceSource for HttpTokenPriceSource {
+    async fn native_token_price(&self, domain: u32) -> Result<Option<TokenPrice>> {
+        // Don't hold the lock across the request so a slow endpoint doesn't
+        // block lookups for other messages.
+        let cached = self.cache.lock().await.clone();
+        let prices = match cached {
+            Some((fetched_at, prices)) if fetched_at.elapsed() < self.cache_ttl => prices,
+            cached => match self.fetch_prices().await {
+                Ok(prices) => {
+                    debug!(?prices, "Fetched token prices");
+                    let prices = Arc::new(prices);
+                    *self.cache.lock().await = Some((Instant::now(), prices.clone()));
+                    prices
+                }
+                Err(err) => match cached {
+                    Some((fetched_at, prices)) => {
+                        warn!(
+                            error=?err,
+                            url=%self.url,
+                            age=?fetched_at.elapsed(),
+                            "Failed to refresh token prices, using stale prices"
+                        );
+                        prices
+                    }
+                    None => {
+                        return Err(err).with_context(|| {
+                            format!("When fetching token prices from {}", self.url)
+                        })
+                    }
+                },
+            },
+        };
+        Ok(prices.get(&domain).copied())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::U256;
+
+    use super::*;
+
+    #[test]
+    fn test_usd_value() {
+        let price = TokenPrice {
+            usd: 2000.0,
+            decimals: 18,
+        };
+        let half_a_token = U256::from(5u64) * U256::exp10(17);
+        assert_eq!(price.usd_value(half_a_token), 1000.0);
+
+        let price = TokenPrice {
+            usd: 0.5,
+            decimals: 6,
+        };
+        assert_eq!(price.usd_value(U256::from(3_000_000u64)), 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_http_source_serves_stale_prices_on_failure() {
+        let price = TokenPrice {
+            usd: 2000.0,
+            decimals: 18,
+        };
+        // Nothing listens on this port, so every refresh fails
+        let source =
+            HttpTokenPriceSource::new("http://127.0.0.1:1".to_owned(), Duration::ZERO).unwrap();
+        assert!(source.native_token_price(1).await.is_err());
+
+        *source.cache.lock().await = Some((Instant::now(), Arc::new(HashMap::from([(1, price)]))));
+        assert_eq!(source.native_token_price(1).await.unwrap(), Some(price));
+        assert_eq!(source.native_token_price(2).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_usd_value_of_large_amounts() {
+        let price = TokenPrice {
+            usd: 1.0,
+            decimals: 0,
+        };
+        let amount = U256::from(u64::MAX) + 1;
+        assert_eq!(price.usd_value(amount), 2f64.powi(64));
+    }
+}
@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use derive_more::Deref;
+use futures_util::future::join_all;
+
+use derive_new::new;
+use eyre::Context;
+use tracing::{info, instrument};
+
+use hyperlane_core::{HyperlaneMessage, H256};
+
+use super::{base::threshold_subsets, BaseMetadataBuilder, MetadataBuilder};
+
+/// Bytes used to store one member of the (start, end) range tuple.
+/// Copied from `AggregationIsmMetadata.sol`
+const METADATA_RANGE_SIZE: usize = 4;
+
+#[derive(Clone, Debug, new, Deref)]
+pub struct AggregationIsmMetadataBuilder {
+    base: BaseMetadataBuilder,
+}
+
+#[derive(Clone, Debug, new, PartialEq, Eq)]
+struct SubModuleMetadata {
+    /// The index of the sub-module (ISM) in the aggregation ISM.
+    index: usize,
+    /// The metadata for the sub-module.
+    metadata: Vec<u8>,
+}
+
+impl AggregationIsmMetadataBuilder {
+    /// Encode the sub-module metadatas in the format expected by
+    /// `AggregationIsmMetadata.sol`:
+    ///
+    /// [????:????] Metadata start/end uint32 ranges, packed as uint64
+    /// [????:????] ISM metadata, packed encoding
+    ///
+    /// Sub-modules without metadata keep a zeroed range, which the contract
+    /// interprets as "no metadata provided".
+    fn format_metadata(metadatas: Vec<SubModuleMetadata>, ism_count: usize) -> Vec<u8> {
+        fn encode_byte_index(i: usize) -> [u8; METADATA_RANGE_SIZE] {
+            (i as u32).to_be_bytes()
+        }
+
+        let range_tuples_size = METADATA_RANGE_SIZE * 2 * ism_count;
+        // Initialize the range tuple part of the buffer so the sub-module
+        // metadatas can simply be appended to it.
+        let mut buffer = vec![0; range_tuples_size];
+        for SubModuleMetadata { index, metadata } in metadatas {
+            let range_start = buffer.len();
+            buffer.extend(metadata);
+            let range_end = buffer.len();
+
+            let encoded_range_start = METADATA_RANGE_SIZE * 2 * index;
+            buffer.splice(
+                encoded_range_start..(encoded_range_start + METADATA_RANGE_SIZE * 2),
+                [encode_byte_index(range_start), encode_byte_index(range_end)].concat(),
+            );
+        }
+        buffer
+    }
+
+    /// Pick the sub-module metadatas to submit. Only `threshold` of them are
+    /// needed, so we take the first ones by index and leave the rest out to
+    /// save on calldata and verification gas.
+    fn select_metadatas(
+        metadatas: Vec<SubModuleMetadata>,
+        threshold: usize,
+    ) -> Option<Vec<SubModuleMetadata>> {
+        if metadatas.len() < threshold {
+            return None;
+        }
+        Some(metadatas.into_iter().take(threshold).collect())
+    }
+
+    /// Build the metadata of every sub-module of the ISM which metadata is
+    /// available for. Returns them along with the sub-modules and the
+    /// threshold.
+    async fn sub_module_metadatas(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> eyre::Result<(Vec<SubModuleMetadata>, Vec<H256>, usize)> {
+        const CTX: &str = "When fetching AggregationIsm metadata";
+        let ism = self.build_aggregation_ism(ism_address).await.context(CTX)?;
+        let (modules, threshold) = ism.modules_and_threshold(message).await.context(CTX)?;
+
+        let sub_module_results = join_all(
+            modules
+                .iter()
+                .map(|module| self.base.build(*module, message)),
+        )
+        .await;
+
+        let available_metadatas = sub_module_results
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, result)| match result {
+                Ok(Some(metadata)) => Some(SubModuleMetadata::new(index, metadata)),
+                Ok(None) => None,
+                Err(err) => {
+                    info!(error=?err, module=?modules[index], "Could not build metadata for sub-module");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok((available_metadatas, modules, threshold as usize))
+    }
+}
+
+#[async_trait]
+impl MetadataBuilder for AggregationIsmMetadataBuilder {
+    #[instrument(err, skip(self))]
+    async fn build(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> eyre::Result<Option<Vec<u8>>> {
+        let (available_metadatas, modules, threshold) =
+            self.sub_module_metadatas(ism_address, message).await?;
+        let available = available_metadatas.len();
+
+        let Some(selected) = Self::select_metadatas(available_metadatas, threshold) else {
+            info!(
+                ?modules, threshold, available,
+                "Could not fetch metadata: Unable to reach quorum of sub-modules"
+            );
+            return Ok(None);
+        };
+        Ok(Some(Self::format_metadata(selected, modules.len())))
+    }
+
+    /// Alternatives swap one of the selected sub-modules for another one
+    /// which metadata is available for.
+    #[instrument(err, skip(self))]
+    async fn build_candidates(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> eyre::Result<Vec<Vec<u8>>> {
+        let (available_metadatas, modules, threshold) =
+            self.sub_module_metadatas(ism_address, message).await?;
+        Ok(threshold_subsets(&available_metadatas, threshold)
+            .into_iter()
+            .map(|selected| Self::format_metadata(selected, modules.len()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_n_of_n_metadata_works_correctly() {
+        let metadatas = vec![
+            SubModuleMetadata::new(0, vec![255u8, 1u8]),
+            SubModuleMetadata::new(1, vec![2u8, 3u8, 4u8]),
+            SubModuleMetadata::new(2, vec![5u8, 6u8, 7u8, 8u8]),
+        ];
+        let expected = [
+            // ranges: 3 * (start, end) = 24 bytes
+            0, 0, 0, 24, 0, 0, 0, 26, //
+            0, 0, 0, 26, 0, 0, 0, 29, //
+            0, 0, 0, 29, 0, 0, 0, 33, //
+            // metadatas
+            255, 1, 2, 3, 4, 5, 6, 7, 8,
+        ];
+        assert_eq!(
+            AggregationIsmMetadataBuilder::format_metadata(metadatas, 3),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_format_n_of_m_metadata_leaves_missing_ranges_zeroed() {
+        let metadatas = vec![
+            SubModuleMetadata::new(0, vec![1u8]),
+            SubModuleMetadata::new(2, vec![2u8, 3u8]),
+        ];
+        let expected = [
+            0, 0, 0, 24, 0, 0, 0, 25, //
+            0, 0, 0, 0, 0, 0, 0, 0, //
+            0, 0, 0, 25, 0, 0, 0, 27, //
+            1, 2, 3,
+        ];
+        assert_eq!(
+            AggregationIsmMetadataBuilder::format_metadata(metadatas, 3),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_select_metadatas_requires_threshold() {
+        let metadatas = vec![
+            SubModuleMetadata::new(1, vec![1u8]),
+            SubModuleMetadata::new(3, vec![2u8]),
+            SubModuleMetadata::new(4, vec![3u8]),
+        ];
+        assert_eq!(
+            AggregationIsmMetadataBuilder::select_metadatas(metadatas.clone(), 4),
+            None
+        );
+        assert_eq!(
+            AggregationIsmMetadataBuilder::select_metadatas(metadatas.clone(), 2),
+            Some(metadatas[..2].to_vec())
+        );
+    }
+}
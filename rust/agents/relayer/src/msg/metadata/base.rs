@@ -13,16 +13,17 @@ use hyperlane_base::{
 };
 use hyperlane_core::accumulator::merkle::Proof;
 use hyperlane_core::{
-    Checkpoint, HyperlaneDomain, HyperlaneMessage, ModuleType, MultisigIsm, RoutingIsm,
-    ValidatorAnnounce, H160, H256,
+    AggregationIsm, Checkpoint, HyperlaneDomain, HyperlaneMessage, IsmVerification, ModuleType,
+    MultisigIsm, RoutingIsm, ValidatorAnnounce, H160, H256,
 };
 
 use crate::merkle_tree_builder::MerkleTreeBuilder;
+use crate::msg::metadata::cache::{IsmCache, IsmCacheKey};
 use crate::msg::metadata::multisig::{
     LegacyMultisigMetadataBuilder, MerkleRootMultisigMetadataBuilder,
     MessageIdMultisigMetadataBuilder,
 };
-use crate::msg::metadata::RoutingIsmMetadataBuilder;
+use crate::msg::metadata::{AggregationIsmMetadataBuilder, RoutingIsmMetadataBuilder};
 
 #[derive(Debug, thiserror::Error)]
 pub enum MetadataBuilderError {
@@ -32,11 +33,40 @@ pub enum MetadataBuilderError {
     MaxDepthExceeded(u32),
 }
 
+/// Outcome of building metadata and checking it with the ISM.
+#[derive(Debug)]
+pub enum VerifiedMetadata {
+    /// Metadata the ISM accepts
+    Verified(Vec<u8>),
+    /// Not enough is known yet, e.g. signatures, to build any metadata
+    Unavailable,
+    /// The ISM rejected every candidate, with the reason it gave for the
+    /// preferred one
+    Rejected(String),
+}
+
 #[async_trait]
 pub trait MetadataBuilder: Send + Sync {
     #[allow(clippy::async_yields_async)]
     async fn build(&self, ism_address: H256, message: &HyperlaneMessage)
         -> Result<Option<Vec<u8>>>;
+
+    /// All the metadata that can be built for the message, in order of
+    /// preference. The first candidate is what `build` returns, the others
+    /// are alternatives, e.g. using a different checkpoint or subset of
+    /// signers, to try if the ISM rejects it.
+    #[allow(clippy::async_yields_async)]
+    async fn build_candidates(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> Result<Vec<Vec<u8>>> {
+        Ok(self
+            .build(ism_address, message)
+            .await?
+            .into_iter()
+            .collect())
+    }
 }
 
 #[derive(Clone, new)]
@@ -46,6 +76,8 @@ pub struct BaseMetadataBuilder {
     origin_validator_announce: Arc<dyn ValidatorAnnounce>,
     allow_local_checkpoint_syncers: bool,
     metrics: Arc<CoreMetrics>,
+    /// Cache of ISM lookups shared across all destinations.
+    ism_cache: Arc<IsmCache>,
     /// ISMs can be structured recursively. We keep track of the depth
     /// of the recursion to avoid infinite loops.
     #[new(default)]
@@ -72,12 +104,36 @@ impl MetadataBuilder for BaseMetadataBuilder {
         message: &HyperlaneMessage,
     ) -> Result<Option<Vec<u8>>> {
         const CTX: &str = "When fetching module type";
-        let ism = self
-            .destination_chain_setup
-            .build_ism(ism_address, &self.metrics)
+        self.module_metadata_builder(ism_address)
+            .await?
+            .build(ism_address, message)
             .await
-            .context(CTX)?;
-        let module_type = ism.module_type().await.context(CTX)?;
+            .context(CTX)
+    }
+
+    #[instrument(err, skip(self), fields(domain=self.domain().name()))]
+    async fn build_candidates(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> Result<Vec<Vec<u8>>> {
+        const CTX: &str = "When fetching module type";
+        self.module_metadata_builder(ism_address)
+            .await?
+            .build_candidates(ism_address, message)
+            .await
+            .context(CTX)
+    }
+}
+
+impl BaseMetadataBuilder {
+    pub fn domain(&self) -> &HyperlaneDomain {
+        &self.destination_chain_setup.domain
+    }
+
+    /// Get the builder for the type of the ISM at the given address.
+    async fn module_metadata_builder(&self, ism_address: H256) -> Result<Box<dyn MetadataBuilder>> {
+        let module_type = self.ism_module_type(ism_address).await?;
         let base = self.clone_with_incremented_depth()?;
 
         let metadata_builder: Box<dyn MetadataBuilder> = match module_type {
@@ -87,18 +143,127 @@ impl MetadataBuilder for BaseMetadataBuilder {
             }
             ModuleType::MessageIdMultisig => Box::new(MessageIdMultisigMetadataBuilder::new(base)),
             ModuleType::Routing => Box::new(RoutingIsmMetadataBuilder::new(base)),
+            ModuleType::Aggregation => Box::new(AggregationIsmMetadataBuilder::new(base)),
             _ => return Err(MetadataBuilderError::UnsupportedModuleType(module_type).into()),
         };
-        metadata_builder
-            .build(ism_address, message)
+        Ok(metadata_builder)
+    }
+
+    /// Build metadata for the message and simulate the ISM verifying it
+    /// before any gas is spent on it. If the ISM rejects the preferred
+    /// metadata, the alternatives are tried in order.
+    #[instrument(err, skip(self), fields(domain=self.domain().name()))]
+    pub async fn build_verified(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> Result<VerifiedMetadata> {
+        const CTX: &str = "When verifying metadata";
+        let candidates = self.build_candidates(ism_address, message).await?;
+        if candidates.is_empty() {
+            return Ok(VerifiedMetadata::Unavailable);
+        }
+
+        let ism = self
+            .destination_chain_setup
+            .build_ism(ism_address, &self.metrics)
             .await
-            .context(CTX)
+            .context(CTX)?;
+        let mut first_rejection = None;
+        for (candidate, metadata) in candidates.into_iter().enumerate() {
+            match ism.dry_run_verify(message, &metadata).await.context(CTX)? {
+                IsmVerification::Verified => {
+                    if candidate > 0 {
+                        info!(candidate, "ISM accepted alternative metadata");
+                    }
+                    return Ok(VerifiedMetadata::Verified(metadata));
+                }
+                IsmVerification::Rejected(reason) => {
+                    debug!(candidate, %reason, "ISM rejected metadata");
+                    first_rejection.get_or_insert(reason);
+                }
+            }
+        }
+        Ok(VerifiedMetadata::Rejected(first_rejection.unwrap()))
     }
-}
 
-impl BaseMetadataBuilder {
-    pub fn domain(&self) -> &HyperlaneDomain {
-        &self.destination_chain_setup.domain
+    /// Fetch the module type of the ISM at the given address.
+    pub async fn ism_module_type(&self, ism_address: H256) -> Result<ModuleType> {
+        const CTX: &str = "When fetching module type";
+        let key = self.cache_key(ism_address, None);
+        if let Some(module_type) = self.ism_cache.module_type(&key) {
+            return Ok(module_type);
+        }
+        let ism = self
+            .destination_chain_setup
+            .build_ism(ism_address, &self.metrics)
+            .await
+            .context(CTX)?;
+        let module_type = ism.module_type().await.context(CTX)?;
+        self.ism_cache.set_module_type(key, module_type);
+        Ok(module_type)
+    }
+
+    /// Fetch the module a routing ISM routes the message to. Routing is
+    /// assumed to only depend on the origin of the message.
+    pub async fn route(&self, ism_address: H256, message: &HyperlaneMessage) -> Result<H256> {
+        let key = self.cache_key(ism_address, Some(message.origin));
+        if let Some(module) = self.ism_cache.route(&key) {
+            return Ok(module);
+        }
+        let module = self
+            .build_routing_ism(ism_address)
+            .await?
+            .route(message)
+            .await?;
+        self.ism_cache.set_route(key, module);
+        Ok(module)
+    }
+
+    /// Fetch the validators and threshold of a multisig ISM for the message.
+    /// These are assumed to only depend on the origin of the message.
+    pub async fn validators_and_threshold(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> Result<(Vec<H256>, u8)> {
+        let key = self.cache_key(ism_address, Some(message.origin));
+        if let Some(validator_set) = self.ism_cache.validator_set(&key) {
+            return Ok(validator_set);
+        }
+        let (validators, threshold) = self
+            .build_multisig_ism(ism_address)
+            .await?
+            .validators_and_threshold(message)
+            .await?;
+        self.ism_cache
+            .set_validator_set(key, validators.clone(), threshold);
+        Ok((validators, threshold))
+    }
+
+    /// Get a checkpoint syncer for the validators of a multisig ISM, which
+    /// must be the validators the ISM has for messages from `origin`.
+    pub async fn checkpoint_syncer(
+        &self,
+        ism_address: H256,
+        origin: u32,
+        validators: &[H256],
+    ) -> Result<Arc<MultisigCheckpointSyncer>> {
+        let key = self.cache_key(ism_address, Some(origin));
+        if let Some(syncer) = self.ism_cache.checkpoint_syncer(&key) {
+            return Ok(syncer);
+        }
+        let syncer = Arc::new(self.build_checkpoint_syncer(validators).await?);
+        self.ism_cache.set_checkpoint_syncer(key, syncer.clone());
+        Ok(syncer)
+    }
+
+    fn cache_key(&self, ism: H256, origin: Option<u32>) -> IsmCacheKey {
+        IsmCacheKey {
+            destination: self.domain().id(),
+            ism,
+            origin,
+        }
     }
 
     pub fn clone_with_incremented_depth(&self) -> Result<BaseMetadataBuilder> {
@@ -144,6 +309,12 @@ impl BaseMetadataBuilder {
             .await
     }
 
+    pub async fn build_aggregation_ism(&self, address: H256) -> Result<Box<dyn AggregationIsm>> {
+        self.destination_chain_setup
+            .build_aggregation_ism(address, &self.metrics)
+            .await
+    }
+
     pub async fn build_multisig_ism(&self, address: H256) -> Result<Box<dyn MultisigIsm>> {
         self.destination_chain_setup
             .build_multisig_ism(address, &self.metrics)
@@ -211,3 +382,42 @@ impl BaseMetadataBuilder {
         Ok(MultisigCheckpointSyncer::new(checkpoint_syncers))
     }
 }
+
+/// The first `threshold` of `items`, followed by the alternatives which leave
+/// out one of those in turn and take the next available item instead. Empty
+/// if there are fewer than `threshold` items.
+pub(crate) fn threshold_subsets<T: Clone>(items: &[T], threshold: usize) -> Vec<Vec<T>> {
+    if items.len() < threshold {
+        return vec![];
+    }
+    let mut subsets = vec![items[..threshold].to_vec()];
+    if items.len() > threshold {
+        for left_out in 0..threshold {
+            subsets.push(
+                items
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != left_out)
+                    .map(|(_, item)| item.clone())
+                    .take(threshold)
+                    .collect(),
+            );
+        }
+    }
+    subsets
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_threshold_subsets() {
+        assert!(threshold_subsets(&[1, 2], 3).is_empty());
+        assert_eq!(threshold_subsets(&[1, 2], 2), vec![vec![1, 2]]);
+        assert_eq!(
+            threshold_subsets(&[1, 2, 3, 4], 2),
+            vec![vec![1, 2], vec![2, 3], vec![1, 3]]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use prometheus::IntCounterVec;
+
+use hyperlane_base::{CoreMetrics, MultisigCheckpointSyncer};
+use hyperlane_core::{ModuleType, H256};
+
+/// Identifies an ISM on a destination chain, optionally along with the origin
+/// of the message being verified for lookups which depend on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IsmCacheKey {
+    pub destination: u32,
+    pub ism: H256,
+    pub origin: Option<u32>,
+}
+
+/// A cache of ISM lookups shared by all metadata builders, so that retrying a
+/// backlog of messages does not repeat the same RPC calls for every message.
+/// Entries expire after a TTL so changes to ISMs and validator announcements
+/// are eventually picked up.
+#[derive(Debug)]
+pub struct IsmCache {
+    /// Module type by ISM.
+    module_types: TtlMap<IsmCacheKey, ModuleType>,
+    /// Module a routing ISM routes messages from an origin to.
+    routes: TtlMap<IsmCacheKey, H256>,
+    /// Validators and threshold of a multisig ISM for messages from an origin.
+    validator_sets: TtlMap<IsmCacheKey, (Vec<H256>, u8)>,
+    /// Checkpoint syncers for the validators of a multisig ISM for messages
+    /// from an origin.
+    checkpoint_syncers: TtlMap<IsmCacheKey, Arc<MultisigCheckpointSyncer>>,
+    /// Cache lookups.
+    ///
+    /// Labels:
+    /// - `cache`: Which kind of entry was looked up.
+    /// - `result`: `hit` or `miss`.
+    lookups: IntCounterVec,
+}
+
+impl IsmCache {
+    pub fn new(ttl: Duration, metrics: &CoreMetrics) -> Self {
+        let lookups = metrics
+            .new_int_counter(
+                "ism_cache_lookups",
+                "Number of lookups in the relayer's ISM cache",
+                &["cache", "result"],
+            )
+            .expect("failed to register ism_cache_lookups metric");
+        Self {
+            module_types: TtlMap::new(ttl),
+            routes: TtlMap::new(ttl),
+            validator_sets: TtlMap::new(ttl),
+            checkpoint_syncers: TtlMap::new(ttl),
+            lookups,
+        }
+    }
+
+    pub fn module_type(&self, key: &IsmCacheKey) -> Option<ModuleType> {
+        self.record("module_type", self.module_types.get(key))
+    }
+
+    pub fn set_module_type(&self, key: IsmCacheKey, module_type: ModuleType) {
+        self.module_types.insert(key, module_type);
+    }
+
+    pub fn route(&self, key: &IsmCacheKey) -> Option<H256> {
+        self.record("route", self.routes.get(key))
+    }
+
+    pub fn set_route(&self, key: IsmCacheKey, module: H256) {
+        self.routes.insert(key, module);
+    }
+
+    pub fn validator_set(&self, key: &IsmCacheKey) -> Option<(Vec<H256>, u8)> {
+        self.record("validator_set", self.validator_sets.get(key))
+    }
+
+    pub fn set_validator_set(&self, key: IsmCacheKey, validators: Vec<H256>, threshold: u8) {
+        self.validator_sets.insert(key, (validators, threshold));
+    }
+
+    pub fn checkpoint_syncer(&self, key: &IsmCacheKey) -> Option<Arc<MultisigCheckpointSyncer>> {
+        self.record("checkpoint_syncer", self.checkpoint_syncers.get(key))
+    }
+
+    pub fn set_checkpoint_syncer(&self, key: IsmCacheKey, syncer: Arc<MultisigCheckpointSyncer>) {
+        self.checkpoint_syncers.insert(key, syncer);
+    }
+
+    /// Remove all entries for the given ISM on the given destination.
+    pub fn invalidate(&self, destination: u32, ism: H256) {
+        let matches = |key: &IsmCacheKey| key.destination == destination && key.ism == ism;
+        self.module_types.remove_where(matches);
+        self.routes.remove_where(matches);
+        self.validator_sets.remove_where(matches);
+        self.checkpoint_syncers.remove_where(matches);
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        self.module_types.remove_where(|_| true);
+        self.routes.remove_where(|_| true);
+        self.validator_sets.remove_where(|_| true);
+        self.checkpoint_syncers.remove_where(|_| true);
+    }
+
+    fn record<T>(&self, cache: &str, value: Option<T>) -> Option<T> {
+        let result = if value.is_some() { "hit" } else { "miss" };
+        self.lookups.with_label_values(&[cache, result]).inc();
+        value
+    }
+}
+
+/// A map whose entries expire a fixed duration after they were inserted.
+#[derive(Debug)]
+struct TtlMap<K, V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlMap<K, V> {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((inserted_at, value)) if inserted_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: K, value: V) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), value));
+    }
+
+    fn remove_where(&self, predicate: impl Fn(&K) -> bool) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| !predicate(key));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ttl_map_expires_entries() {
+        let map = TtlMap::new(Duration::from_millis(50));
+        map.insert(1, "a");
+        assert_eq!(map.get(&1), Some("a"));
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[test]
+    fn test_ttl_map_remove_where() {
+        let map = TtlMap::new(Duration::from_secs(60));
+        map.insert(1, "a");
+        map.insert(2, "b");
+        map.remove_where(|k| *k == 1);
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.get(&2), Some("b"));
+    }
+}
@@ -1,7 +1,12 @@
+mod aggregation;
 mod base;
+mod cache;
 mod multisig;
 mod routing;
 
+use aggregation::AggregationIsmMetadataBuilder;
 pub(crate) use base::BaseMetadataBuilder;
 pub(crate) use base::MetadataBuilder;
+pub(crate) use base::VerifiedMetadata;
+pub(crate) use cache::IsmCache;
 use routing::RoutingIsmMetadataBuilder;
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::iter;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use derive_new::new;
@@ -12,12 +14,13 @@ use hyperlane_core::{Checkpoint, HyperlaneMessage, SignatureWithSigner, H256};
 use strum::Display;
 use tracing::{debug, info};
 
+use crate::msg::metadata::base::threshold_subsets;
 use crate::msg::metadata::BaseMetadataBuilder;
 use crate::msg::metadata::MetadataBuilder;
 
-#[derive(new)]
+#[derive(new, Clone)]
 pub struct MultisigMetadata {
-    checkpoint: Checkpoint,
+    pub(super) checkpoint: Checkpoint,
     signatures: Vec<SignatureWithSigner>,
     message_id: Option<H256>,
     proof: Option<Proof>,
@@ -45,6 +48,19 @@ pub trait MultisigIsmMetadataBuilder: AsRef<BaseMetadataBuilder> + Send + Sync {
         checkpoint_syncer: &MultisigCheckpointSyncer,
     ) -> Result<Option<MultisigMetadata>>;
 
+    /// Metadata for a different checkpoint than the `preferred` one
+    /// returned by `fetch_metadata`, to fall back on if the ISM rejects it.
+    async fn fetch_fallback_metadata(
+        &self,
+        _validators: &[H256],
+        _threshold: u8,
+        _message: &HyperlaneMessage,
+        _checkpoint_syncer: &MultisigCheckpointSyncer,
+        _preferred: &MultisigMetadata,
+    ) -> Result<Option<MultisigMetadata>> {
+        Ok(None)
+    }
+
     fn token_layout(&self) -> Vec<MetadataToken>;
 
     fn format_metadata(
@@ -89,6 +105,58 @@ pub trait MultisigIsmMetadataBuilder: AsRef<BaseMetadataBuilder> + Send + Sync {
     }
 }
 
+/// Metadata with a quorum of signatures, along with what it was fetched with.
+struct QuorumMetadata {
+    validators: Vec<H256>,
+    threshold: u8,
+    checkpoint_syncer: Arc<MultisigCheckpointSyncer>,
+    metadata: MultisigMetadata,
+}
+
+async fn fetch_quorum_metadata<T: MultisigIsmMetadataBuilder>(
+    builder: &T,
+    ism_address: H256,
+    message: &HyperlaneMessage,
+) -> Result<Option<QuorumMetadata>> {
+    const CTX: &str = "When fetching MultisigIsm metadata";
+    let (validators, threshold) = builder
+        .as_ref()
+        .validators_and_threshold(ism_address, message)
+        .await
+        .context(CTX)?;
+
+    if validators.is_empty() {
+        info!("Could not fetch metadata: No validator set found for ISM");
+        return Ok(None);
+    }
+
+    let checkpoint_syncer = builder
+        .as_ref()
+        .checkpoint_syncer(ism_address, message.origin, &validators)
+        .await
+        .context(CTX)?;
+
+    if let Some(metadata) = builder
+        .fetch_metadata(&validators, threshold, message, &checkpoint_syncer)
+        .await
+        .context(CTX)?
+    {
+        debug!(?message, ?metadata.checkpoint, "Found checkpoint with quorum");
+        Ok(Some(QuorumMetadata {
+            validators,
+            threshold,
+            checkpoint_syncer,
+            metadata,
+        }))
+    } else {
+        info!(
+            ?message, ?validators, threshold, ism=?ism_address,
+            "Could not fetch metadata: Unable to reach quorum"
+        );
+        Ok(None)
+    }
+}
+
 #[async_trait]
 impl<T: MultisigIsmMetadataBuilder> MetadataBuilder for T {
     #[allow(clippy::async_yields_async)]
@@ -97,43 +165,55 @@ impl<T: MultisigIsmMetadataBuilder> MetadataBuilder for T {
         ism_address: H256,
         message: &HyperlaneMessage,
     ) -> Result<Option<Vec<u8>>> {
-        const CTX: &str = "When fetching MultisigIsm metadata";
-        let multisig_ism = self
-            .as_ref()
-            .build_multisig_ism(ism_address)
-            .await
-            .context(CTX)?;
-
-        let (validators, threshold) = multisig_ism
-            .validators_and_threshold(message)
-            .await
-            .context(CTX)?;
-
-        if validators.is_empty() {
-            info!("Could not fetch metadata: No validator set found for ISM");
-            return Ok(None);
-        }
+        Ok(fetch_quorum_metadata(self, ism_address, message)
+            .await?
+            .map(|quorum| {
+                self.format_metadata(&quorum.validators, quorum.threshold, quorum.metadata)
+            }))
+    }
 
-        let checkpoint_syncer = self
-            .as_ref()
-            .build_checkpoint_syncer(&validators)
+    /// Alternatives use a different subset of the signatures when there are
+    /// more than the threshold, and a fallback checkpoint where the builder
+    /// has one.
+    #[allow(clippy::async_yields_async)]
+    async fn build_candidates(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> Result<Vec<Vec<u8>>> {
+        const CTX: &str = "When fetching MultisigIsm metadata";
+        let Some(QuorumMetadata {
+            validators,
+            threshold,
+            checkpoint_syncer,
+            metadata,
+        }) = fetch_quorum_metadata(self, ism_address, message).await?
+        else {
+            return Ok(vec![]);
+        };
+        let fallback = self
+            .fetch_fallback_metadata(
+                &validators,
+                threshold,
+                message,
+                &checkpoint_syncer,
+                &metadata,
+            )
             .await
             .context(CTX)?;
 
-        if let Some(metadata) = self
-            .fetch_metadata(&validators, threshold, message, &checkpoint_syncer)
-            .await
-            .context(CTX)?
-        {
-            debug!(?message, ?metadata.checkpoint, "Found checkpoint with quorum");
-            Ok(Some(self.format_metadata(&validators, threshold, metadata)))
-        } else {
-            info!(
-                ?message, ?validators, threshold, ism=%multisig_ism.address(),
-                "Could not fetch metadata: Unable to reach quorum"
-            );
-            Ok(None)
+        let mut candidates = vec![];
+        for metadata in iter::once(metadata).chain(fallback) {
+            let signatures = order_signers(&validators, &metadata.signatures);
+            for signatures in threshold_subsets(&signatures, threshold as usize) {
+                let metadata = MultisigMetadata {
+                    signatures,
+                    ..metadata.clone()
+                };
+                candidates.push(self.format_metadata(&validators, threshold, metadata));
+            }
         }
+        Ok(candidates)
     }
 }
 
@@ -141,6 +221,18 @@ impl<T: MultisigIsmMetadataBuilder> MetadataBuilder for T {
 /// Returns a Vec of the signature raw bytes in the correct order.
 /// Panics if any signers in `signatures` are not present in `desired_order`
 fn order_signatures(desired_order: &[H256], signatures: &[SignatureWithSigner]) -> Vec<Vec<u8>> {
+    order_signers(desired_order, signatures)
+        .into_iter()
+        .map(|s| s.signature.to_vec())
+        .collect()
+}
+
+/// Orders `signatures` by the signers according to the `desired_order`.
+/// Panics if any signers in `signatures` are not present in `desired_order`
+fn order_signers(
+    desired_order: &[H256],
+    signatures: &[SignatureWithSigner],
+) -> Vec<SignatureWithSigner> {
     // Signer address => index to sort by
     let ordering_map: HashMap<H256, usize> = desired_order
         .iter()
@@ -159,9 +251,5 @@ fn order_signatures(desired_order: &[H256], signatures: &[SignatureWithSigner])
         .collect::<Vec<_>>();
     // Sort by the index
     ordered_signatures.sort_by_key(|s| s.1);
-    // Now collect only the raw signature bytes
-    ordered_signatures
-        .into_iter()
-        .map(|s| s.0.signature.to_vec())
-        .collect()
+    ordered_signatures.into_iter().map(|s| s.0).collect()
 }
@@ -66,4 +66,42 @@ impl MultisigIsmMetadataBuilder for LegacyMultisigMetadataBuilder {
             Some(proof),
         )))
     }
+
+    /// Falls back on the checkpoint at the message's own index, the earliest
+    /// one which commits to the message.
+    async fn fetch_fallback_metadata(
+        &self,
+        validators: &[H256],
+        threshold: u8,
+        message: &HyperlaneMessage,
+        checkpoint_syncer: &MultisigCheckpointSyncer,
+        preferred: &MultisigMetadata,
+    ) -> Result<Option<MultisigMetadata>> {
+        const CTX: &str = "When fetching fallback LegacyMultisig metadata";
+        if preferred.checkpoint.index == message.nonce {
+            return Ok(None);
+        }
+        let Some(quorum_checkpoint) = checkpoint_syncer
+            .legacy_fetch_checkpoint(message.nonce, validators, threshold as usize)
+            .await
+            .context(CTX)?
+        else {
+            return Ok(None);
+        };
+
+        let Some(proof) = self
+            .get_proof(message.nonce, quorum_checkpoint.checkpoint)
+            .await
+            .context(CTX)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(MultisigMetadata::new(
+            quorum_checkpoint.checkpoint,
+            quorum_checkpoint.signatures,
+            None,
+            Some(proof),
+        )))
+    }
 }
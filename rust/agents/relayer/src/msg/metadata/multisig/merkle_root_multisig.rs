@@ -58,4 +58,42 @@ impl MultisigIsmMetadataBuilder for MerkleRootMultisigMetadataBuilder {
             Some(proof),
         )))
     }
+
+    /// Falls back on the checkpoint at the message's own index, the earliest
+    /// one which commits to the message.
+    async fn fetch_fallback_metadata(
+        &self,
+        validators: &[H256],
+        threshold: u8,
+        message: &HyperlaneMessage,
+        checkpoint_syncer: &MultisigCheckpointSyncer,
+        preferred: &MultisigMetadata,
+    ) -> Result<Option<MultisigMetadata>> {
+        const CTX: &str = "When fetching fallback MerkleRootMultisig metadata";
+        if preferred.checkpoint.index == message.nonce {
+            return Ok(None);
+        }
+        let Some(quorum_checkpoint) = checkpoint_syncer
+            .fetch_checkpoint(validators, threshold as usize, message.nonce)
+            .await
+            .context(CTX)?
+        else {
+            return Ok(None);
+        };
+
+        let Some(proof) = self
+            .get_proof(message.nonce, quorum_checkpoint.checkpoint.checkpoint)
+            .await
+            .context(CTX)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(MultisigMetadata::new(
+            quorum_checkpoint.checkpoint.checkpoint,
+            quorum_checkpoint.signatures,
+            Some(quorum_checkpoint.checkpoint.message_id),
+            Some(proof),
+        )))
+    }
 }
@@ -31,8 +31,21 @@ impl MetadataBuilder for RoutingIsmMetadataBuilder {
         message: &HyperlaneMessage,
     ) -> eyre::Result<Option<Vec<u8>>> {
         const CTX: &str = "When fetching RoutingIsm metadata";
-        let ism = self.build_routing_ism(ism_address).await.context(CTX)?;
-        let module = ism.route(message).await.context(CTX)?;
+        let module = self.route(ism_address, message).await.context(CTX)?;
         self.base.build(module, message).await.context(CTX)
     }
+
+    #[instrument(err, skip(self))]
+    async fn build_candidates(
+        &self,
+        ism_address: H256,
+        message: &HyperlaneMessage,
+    ) -> eyre::Result<Vec<Vec<u8>>> {
+        const CTX: &str = "When fetching RoutingIsm metadata";
+        let module = self.route(ism_address, message).await.context(CTX)?;
+        self.base
+            .build_candidates(module, message)
+            .await
+            .context(CTX)
+    }
 }
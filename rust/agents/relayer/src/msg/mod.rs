@@ -25,9 +25,14 @@
 //!   - FallbackProviderSubmitter (Serialized, but if some RPC provider sucks,
 //!   switch everyone to new one)
 
+pub(crate) mod dead_letter;
+pub(crate) mod dry_run;
 pub(crate) mod gas_payment;
 pub(crate) mod metadata;
+pub(crate) mod one_shot;
 pub(crate) mod pending_message;
 pub(crate) mod pending_operation;
+pub(crate) mod priority;
 pub(crate) mod processor;
+pub(crate) mod profitability;
 pub(crate) mod serial_submitter;
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+use eyre::{bail, eyre, Context, Result};
+use tokio::sync::RwLock;
+use tracing::{debug, info, instrument};
+
+use hyperlane_base::{db::HyperlaneRocksDB, settings::chains::IndexSettings};
+use hyperlane_core::{
+    accumulator::incremental::IncrementalMerkle, HyperlaneMessage, Mailbox, MessageIndexer, H256,
+};
+
+use super::{metadata::VerifiedMetadata, pending_message::MessageContext};
+use crate::{
+    merkle_tree_builder::MerkleTreeBuilder,
+    settings::{MessageSelector, RelayMessageConf},
+};
+
+/// Number of times to look up the merkle tree before the message's dispatch
+/// block if the chain moved on in the meantime.
+const TREE_LOOKUP_ATTEMPTS: usize = 3;
+
+/// The origin contracts a message is fetched from in one-shot mode.
+#[derive(Debug)]
+pub(crate) struct OriginChain {
+    pub mailbox: Box<dyn Mailbox>,
+    pub indexer: Box<dyn MessageIndexer>,
+    pub index: IndexSettings,
+}
+
+/// Deliver a single message, then return. The message and the data to prove
+/// it are fetched from the origin chain, so it doesn't need to have been
+/// indexed. Used to push a stuck message through by hand, so the gas payment
+/// requirement is not enforced.
+#[instrument(skip_all, fields(origin=%conf.origin, message=?conf.message))]
+pub(crate) async fn relay_message(
+    conf: RelayMessageConf,
+    origin: OriginChain,
+    db: HyperlaneRocksDB,
+    prover_sync: Arc<RwLock<MerkleTreeBuilder>>,
+    destination_ctxs: HashMap<u32, Arc<MessageContext>>,
+) -> Result<()> {
+    let (message, tree, leaves) = fetch_message(&origin, conf.message).await?;
+    info!(%message, "Relaying message");
+    message.check_version()?;
+
+    let ctx = destination_ctxs.get(&message.destination).ok_or_else(|| {
+        eyre!(
+            "Message destination {} is not a configured destination chain",
+            message.destination
+        )
+    })?;
+    let mailbox = &ctx.destination_mailbox;
+
+    if mailbox
+        .delivered(message.id())
+        .await
+        .context("When checking message delivery status")?
+    {
+        info!("Message has already been delivered");
+        return Ok(());
+    }
+
+    prover_sync.write().await.reset_to_leaves(tree, leaves)?;
+
+    let ism_address = mailbox
+        .recipient_ism(message.recipient)
+        .await
+        .context("When fetching ISM address")?;
+    let metadata = match ctx
+        .metadata_builder
+        .build_verified(ism_address, &message)
+        .await
+        .context("When building metadata")?
+    {
+        VerifiedMetadata::Verified(metadata) => metadata,
+        VerifiedMetadata::Unavailable => bail!("Could not build metadata for message"),
+        VerifiedMetadata::Rejected(reason) => bail!("ISM rejected metadata: {reason}"),
+    };
+
+    let gas_limit = match conf.gas_limit {
+        Some(gas_limit) => gas_limit,
+        None => {
+            mailbox
+                .process_estimate_costs(&message, &metadata)
+                .await
+                .context("When estimating costs for process call")?
+                .gas_limit
+        }
+    };
+
+    let tx_outcome = mailbox
+        .process(&message, &metadata, Some(gas_limit))
+        .await
+        .context("When processing message")?;
+    ctx.origin_gas_payment_enforcer
+        .record_tx_outcome(&message, tx_outcome)?;
+    if !tx_outcome.executed {
+        bail!(
+            "Transaction {:?} attempting to process message reverted",
+            tx_outcome.txid
+        );
+    }
+
+    db.store_processed_by_nonce(&message.nonce, &true)?;
+    info!(hash=?tx_outcome.txid, "Message successfully processed by transaction");
+    Ok(())
+}
+
+/// Find the selected message in the finalized dispatch logs of the origin
+/// mailbox, searching backwards from the tip. Returns it together with the
+/// on-chain merkle tree from before it was dispatched and the ids of the
+/// messages dispatched since then, which is everything needed to prove it
+/// against any checkpoint up to the tip.
+async fn fetch_message(
+    origin: &OriginChain,
+    selector: MessageSelector,
+) -> Result<(HyperlaneMessage, IncrementalMerkle, Vec<H256>)> {
+    let (count, tip) = origin
+        .indexer
+        .fetch_count_at_tip()
+        .await
+        .context("When fetching the origin mailbox count")?;
+    if let MessageSelector::Nonce(nonce) = selector {
+        if nonce >= count {
+            bail!("Message with nonce {nonce} was not dispatched on the origin or is not final yet, the finalized count is {count}");
+        }
+    }
+
+    let chunk_size = origin.index.chunk_size.max(1);
+    let mut messages = Vec::new();
+    let mut to = tip;
+    let (message, chunk_from) = loop {
+        if to < origin.index.from {
+            bail!(
+                "Message not found on the origin since block {}",
+                origin.index.from
+            );
+        }
+        let chunk_from = to.saturating_sub(chunk_size - 1).max(origin.index.from);
+        debug!(
+            from = chunk_from,
+            to, "Searching for message in dispatch logs"
+        );
+        let logs = origin
+            .indexer
+            .fetch_logs(chunk_from, to)
+            .await
+            .context("When fetching dispatched messages")?;
+        messages.extend(logs.into_iter().map(|(message, _)| message));
+        if let Some(message) = messages.iter().find(|m| selector_matches(selector, m)) {
+            break (message.clone(), chunk_from);
+        }
+        if chunk_from == 0 {
+            bail!("Message not found on the origin");
+        }
+        to = chunk_from - 1;
+    };
+
+    messages.sort_by_key(|m| m.nonce);
+    messages.dedup_by_key(|m| m.nonce);
+    let first_nonce = messages[0].nonce;
+    let tree = if first_nonce == 0 {
+        IncrementalMerkle::default()
+    } else {
+        tree_before_block(origin, chunk_from, first_nonce, message.nonce).await?
+    };
+
+    let mut next_nonce = tree.count() as u32;
+    let mut leaves = Vec::new();
+    for m in messages.iter().filter(|m| m.nonce >= next_nonce) {
+        if m.nonce != next_nonce {
+            bail!("Message with nonce {next_nonce} is missing from the origin dispatch logs");
+        }
+        leaves.push(m.id());
+        next_nonce += 1;
+    }
+    Ok((message, tree, leaves))
+}
+
+/// The on-chain merkle tree as of the block before `block`. Its count must be
+/// between `min_count` and `max_count` so the fetched messages continue it
+/// and the message being relayed is not already part of it.
+async fn tree_before_block(
+    origin: &OriginChain,
+    block: u32,
+    min_count: u32,
+    max_count: u32,
+) -> Result<IncrementalMerkle> {
+    let Some(target) = block.checked_sub(1) else {
+        bail!("Messages were dispatched on the origin before the first block");
+    };
+    let provider = origin.mailbox.provider();
+    for _ in 0..TREE_LOOKUP_ATTEMPTS {
+        let latest = provider
+            .get_block_number()
+            .await
+            .context("When fetching the origin block number")?;
+        let lag = NonZeroU64::new(latest.saturating_sub(target as u64));
+        let tree = origin
+            .mailbox
+            .tree(lag)
+            .await
+            .context("When fetching the origin merkle tree")?;
+        let tree_count = tree.count() as u32;
+        if (min_count..=max_count).contains(&tree_count) {
+            return Ok(tree);
+        }
+        debug!(
+            tree_count,
+            min_count, max_count, "Origin merkle tree was not fetched at the expected block"
+        );
+    }
+    bail!("Could not fetch the origin merkle tree from before block {block}")
+}
+
+fn selector_matches(selector: MessageSelector, message: &HyperlaneMessage) -> bool {
+    match selector {
+        MessageSelector::Nonce(nonce) => message.nonce == nonce,
+        MessageSelector::Id(id) => message.id() == id,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+
+    use hyperlane_core::{ChainResult, Indexer, LogMeta};
+    use hyperlane_test::mocks::MockMailboxContract;
+
+    use super::*;
+
+    /// Dispatch logs of a mailbox, as `(block, message)` pairs.
+    #[derive(Debug)]
+    struct FakeIndexer {
+        tip: u32,
+        dispatched: Vec<(u32, HyperlaneMessage)>,
+    }
+
+    #[async_trait]
+    impl Indexer<HyperlaneMessage> for FakeIndexer {
+        async fn fetch_logs(
+            &self,
+            from: u32,
+            to: u32,
+        ) -> ChainResult<Vec<(HyperlaneMessage, LogMeta)>> {
+            Ok(self
+                .dispatched
+                .iter()
+                .filter(|(block, _)| (from..=to).contains(block))
+                .map(|(block, message)| {
+                    let meta = LogMeta {
+                        address: H256::zero(),
+                        block_number: *block as u64,
+                        block_hash: H256::zero(),
+                        transaction_hash: H256::zero(),
+                        transaction_index: 0,
+                        log_index: Default::default(),
+                    };
+                    (message.clone(), meta)
+                })
+                .collect())
+        }
+
+        async fn get_finalized_block_number(&self) -> ChainResult<u32> {
+            Ok(self.tip)
+        }
+    }
+
+    #[async_trait]
+    impl MessageIndexer for FakeIndexer {
+        async fn fetch_count_at_tip(&self) -> ChainResult<(u32, u32)> {
+            Ok((self.dispatched.len() as u32, self.tip))
+        }
+    }
+
+    fn dispatch(blocks: &[u32]) -> Vec<(u32, HyperlaneMessage)> {
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(nonce, block)| {
+                let message = HyperlaneMessage {
+                    nonce: nonce as u32,
+                    body: vec![nonce as u8],
+                    ..Default::default()
+                };
+                (*block, message)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fetches_message_and_leaves_from_chain() {
+        let dispatched = dispatch(&[3, 3, 12, 30, 44]);
+        let origin = OriginChain {
+            // the tree is never fetched since the search reaches the first message
+            mailbox: Box::new(MockMailboxContract::new()),
+            indexer: Box::new(FakeIndexer {
+                tip: 45,
+                dispatched: dispatched.clone(),
+            }),
+            index: IndexSettings {
+                from: 0,
+                chunk_size: 10,
+            },
+        };
+
+        let (message, tree, leaves) = fetch_message(&origin, MessageSelector::Nonce(1))
+            .await
+            .unwrap();
+        assert_eq!(message.nonce, 1);
+        assert_eq!(tree.count(), 0);
+        let ids: Vec<_> = dispatched.iter().map(|(_, m)| m.id()).collect();
+        assert_eq!(leaves, ids);
+
+        let (message, _, _) = fetch_message(&origin, MessageSelector::Id(ids[0]))
+            .await
+            .unwrap();
+        assert_eq!(message.nonce, 0);
+
+        assert!(fetch_message(&origin, MessageSelector::Nonce(5))
+            .await
+            .is_err());
+        assert!(fetch_message(&origin, MessageSelector::Id(H256::zero()))
+            .await
+            .is_err());
+    }
+}
@@ -1,30 +1,41 @@
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use derive_new::new;
 use eyre::{Context, Result};
-use hyperlane_base::db::HyperlaneRocksDB;
-use prometheus::{IntCounter, IntGauge};
+use hyperlane_base::db::{HyperlaneRocksDB, PendingMessageState};
+use prometheus::{HistogramVec, IntCounter, IntGauge};
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use hyperlane_base::CoreMetrics;
-use hyperlane_core::{HyperlaneChain, HyperlaneDomain, HyperlaneMessage, Mailbox, U256};
+use hyperlane_core::{
+    BatchItem, ChainResult, HyperlaneChain, HyperlaneDomain, HyperlaneMessage, HyperlaneProvider,
+    Mailbox, ModuleType, TxOutcome, H256, U256,
+};
 
 use super::{
+    dead_letter::{dead_letter, DeadLetterClass},
+    dry_run::{DryRunOutcome, DryRunReporter},
     gas_payment::GasPaymentEnforcer,
-    metadata::{BaseMetadataBuilder, MetadataBuilder},
+    metadata::{BaseMetadataBuilder, VerifiedMetadata},
     pending_operation::*,
+    priority::PriorityInputs,
+    profitability::ProfitabilityTracker,
 };
+use crate::settings::{RetryPolicy, RetryPolicyConf};
 
-const CONFIRM_DELAY: Duration = if cfg!(any(test, feature = "test-utils")) {
-    // Wait 5 seconds after submitting the message before confirming in test mode
-    Duration::from_secs(5)
-} else {
-    // Wait 10 min after submitting the message before confirming in normal/production mode
-    Duration::from_secs(60 * 10)
-};
+/// How long to wait before checking again whether the block a message was
+/// delivered in has reached finality.
+const FINALITY_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Gas used by the multicall contract for a batch on top of the calls it
+/// makes.
+const BATCH_BASE_GAS_OVERHEAD: u64 = 50_000;
+
+/// Gas used by the multicall contract for each call in a batch.
+const BATCH_PER_CALL_GAS_OVERHEAD: u64 = 10_000;
 
 /// The message context contains the links needed to submit a message. Each
 /// instance is for a unique origin -> destination pairing.
@@ -33,6 +44,9 @@ pub struct MessageContext {
     pub destination_mailbox: Arc<dyn Mailbox>,
     /// Origin chain database to verify gas payments.
     pub origin_db: HyperlaneRocksDB,
+    /// Provider for the origin chain, used to look up when messages were
+    /// dispatched.
+    pub origin_provider: Arc<dyn HyperlaneProvider>,
     /// Used to construct the ISM metadata needed to verify a message from the
     /// origin.
     pub metadata_builder: BaseMetadataBuilder,
@@ -42,6 +56,18 @@ pub struct MessageContext {
     /// Hard limit on transaction gas when submitting a transaction to the
     /// destination.
     pub transaction_gas_limit: Option<U256>,
+    /// Number of blocks after which a block on the destination is final.
+    pub destination_finality_blocks: u32,
+    /// Number of failed attempts after which a message is dead-lettered
+    /// instead of retried.
+    pub max_retries: Option<u32>,
+    /// How messages are retried, the first policy a message matches is used.
+    pub retry_policies: Vec<RetryPolicyConf>,
+    /// Set in dry-run mode, in which case messages are prepared but never
+    /// submitted and the outcomes are recorded here instead.
+    pub dry_run: Option<Arc<DryRunReporter>>,
+    /// Running totals of gas payments received and gas spent.
+    pub profitability: Arc<ProfitabilityTracker>,
     pub metrics: MessageSubmissionMetrics,
 }
 
@@ -60,6 +86,40 @@ pub struct PendingMessage {
     last_attempted_at: Instant,
     #[new(default)]
     next_attempt_after: Option<Instant>,
+    #[new(default)]
+    submitted_tx: Option<H256>,
+    /// Number and hash of the block the delivery transaction was included in.
+    #[new(default)]
+    submitted_block: Option<(u64, H256)>,
+    /// Set when the delivery was reorged out, so the metadata built before
+    /// is submitted again without preparing the message from scratch.
+    #[new(default)]
+    resubmit_after_reorg: bool,
+    #[new(default)]
+    last_failure: Option<String>,
+    /// Module type of the recipient's ISM, used to label latency metrics.
+    #[new(default)]
+    ism_module_type: Option<ModuleType>,
+    /// When this message was first prepared by this process.
+    #[new(default)]
+    first_prepared_at: Option<Instant>,
+    /// When metadata for this message was first built by this process.
+    #[new(default)]
+    metadata_built_at: Option<Instant>,
+    #[new(default)]
+    gas_payment_met: bool,
+    /// Unix timestamp of when the relayer first saw the message, persisted
+    /// across restarts.
+    #[new(value = "unix_now()")]
+    first_seen_at: u64,
+    /// Total gas payment for the message as of the last time it was read
+    /// from the db, used to prioritise it.
+    #[new(default)]
+    gas_payment: U256,
+    /// Unix timestamp of the block the message was dispatched in, once it
+    /// has been looked up.
+    #[new(default)]
+    dispatched_at: Option<u64>,
 }
 
 /// State for the next submission attempt generated by a prepare call.
@@ -83,8 +143,8 @@ impl Debug for PendingMessage {
                 }
             })
             .unwrap_or(0);
-        write!(f, "PendingMessage {{ num_retries: {}, since_last_attempt_s: {last_attempt}, next_attempt_after_s: {next_attempt}, message: {:?} }}",
-               self.num_retries, self.message)
+        write!(f, "PendingMessage {{ num_retries: {}, since_last_attempt_s: {last_attempt}, next_attempt_after_s: {next_attempt}, submitted_tx: {:?}, last_failure: {:?}, message: {:?} }}",
+               self.num_retries, self.submitted_tx, self.last_failure, self.message)
     }
 }
 
@@ -106,13 +166,15 @@ impl PendingOperation for PendingMessage {
 
     #[instrument]
     async fn prepare(&mut self) -> PendingOperationResult {
-        make_op_try!(|| self.on_reprepare());
+        make_op_try!(|reason| self.on_reprepare(reason));
 
         if !self.is_ready() {
             trace!("Message is not ready to be submitted yet");
             return PendingOperationResult::NotReady;
         }
 
+        self.refresh_gas_payment();
+
         // If the message has already been processed, e.g. due to another relayer having
         // already processed, then mark it as already-processed, and move on to
         // the next tick.
@@ -125,8 +187,54 @@ impl PendingOperation for PendingMessage {
         );
         if is_already_delivered {
             debug!("Message has already been delivered, marking as submitted.");
+            self.record_dry_run(DryRunOutcome::AlreadyDelivered, None, None);
             self.submitted = true;
-            self.next_attempt_after = Some(Instant::now() + CONFIRM_DELAY);
+            // The block of the delivery is unknown, so only its delivery is
+            // confirmed
+            self.submitted_block = None;
+            self.resubmit_after_reorg = false;
+            self.next_attempt_after = Some(Instant::now() + self.retry_policy().confirm_delay);
+            self.persist_state();
+            return PendingOperationResult::Success;
+        }
+
+        // The dispatch time is used as the age of the message when
+        // prioritising it
+        if self.dispatched_at.is_none() {
+            match self.dispatch_timestamp().await {
+                Ok(timestamp) => self.dispatched_at = timestamp,
+                Err(e) => warn!(error=?e, "Failed to fetch dispatch block of message"),
+            }
+        }
+
+        if let Some(max_age) = self.retry_policy().max_age {
+            let too_old = op_try!(
+                self.exceeds_max_age(max_age).await,
+                "checking the age of the message"
+            );
+            if too_old {
+                info!(
+                    ?max_age,
+                    "Dropping message because it is older than its retry policy allows"
+                );
+                self.record_dry_run(
+                    DryRunOutcome::Dropped,
+                    Some("Message exceeded its max age".into()),
+                    None,
+                );
+                self.store_dead_letter(
+                    DeadLetterClass::MaxAgeExceeded,
+                    format!("Message was dispatched more than {max_age:?} ago"),
+                );
+                return PendingOperationResult::Drop;
+            }
+        }
+
+        if self.resubmit_after_reorg && self.submission_data.is_some() {
+            self.resubmit_after_reorg = false;
+            debug!(
+                "Resubmitting message with the metadata built before its delivery was reorged out"
+            );
             return PendingOperationResult::Success;
         }
 
@@ -142,6 +250,15 @@ impl PendingOperation for PendingMessage {
                 recipient=?self.message.recipient,
                 "Dropping message because recipient is not a contract"
             );
+            self.record_dry_run(
+                DryRunOutcome::Dropped,
+                Some("Recipient is not a contract".into()),
+                None,
+            );
+            self.store_dead_letter(
+                DeadLetterClass::RecipientNotContract,
+                "Recipient is not a contract",
+            );
             return PendingOperationResult::Drop;
         }
 
@@ -153,16 +270,42 @@ impl PendingOperation for PendingMessage {
             "fetching ISM address"
         );
 
-        let Some(metadata) = op_try!(
+        if self.ism_module_type.is_none() {
+            self.ism_module_type = Some(op_try!(
+                self.ctx.metadata_builder.ism_module_type(ism_address).await,
+                "fetching ISM module type"
+            ));
+        }
+        let first_prepared_at = *self.first_prepared_at.get_or_insert_with(Instant::now);
+
+        // Check the metadata with the ISM up front, so that invalid metadata
+        // is reported as such rather than as a failed gas estimation.
+        let metadata = match op_try!(
             self.ctx
                 .metadata_builder
-                .build(ism_address, &self.message)
+                .build_verified(ism_address, &self.message)
                 .await,
             "building metadata"
-        ) else {
-            info!("Could not fetch metadata");
-            return self.on_reprepare();
+        ) {
+            VerifiedMetadata::Verified(metadata) => metadata,
+            VerifiedMetadata::Unavailable => {
+                info!("Could not fetch metadata");
+                return self.on_reprepare("Could not fetch metadata");
+            }
+            VerifiedMetadata::Rejected(reason) => {
+                warn!(%reason, ism=?ism_address, "ISM rejected all metadata candidates");
+                return self.on_reprepare(format!("ISM rejected metadata: {reason}"));
+            }
         };
+        if self.metadata_built_at.is_none() {
+            let now = Instant::now();
+            self.ctx.metrics.observe(
+                &self.ctx.metrics.metadata_wait,
+                self.ism_label(),
+                now - first_prepared_at,
+            );
+            self.metadata_built_at = Some(now);
+        }
 
         // Estimate transaction costs for the process call. If there are issues, it's
         // likely that gas estimation has failed because the message is
@@ -186,8 +329,18 @@ impl PendingOperation for PendingMessage {
         )
             else {
                 info!(?tx_cost_estimate, "Gas payment requirement not met yet");
-                return self.on_reprepare();
+                return self.on_reprepare("Gas payment requirement not met yet");
             };
+        if !self.gas_payment_met {
+            if let Some(metadata_built_at) = self.metadata_built_at {
+                self.ctx.metrics.observe(
+                    &self.ctx.metrics.gas_payment_wait,
+                    self.ism_label(),
+                    metadata_built_at.elapsed(),
+                );
+            }
+            self.gas_payment_met = true;
+        }
 
         // Go ahead and attempt processing of message to destination chain.
         debug!(
@@ -200,20 +353,27 @@ impl PendingOperation for PendingMessage {
         if let Some(max_limit) = self.ctx.transaction_gas_limit {
             if gas_limit > max_limit {
                 info!("Message delivery estimated gas exceeds max gas limit");
-                return self.on_reprepare();
+                return self.on_reprepare("Message delivery estimated gas exceeds max gas limit");
             }
         }
 
+        if self.ctx.dry_run.is_some() {
+            info!(?gas_limit, "Dry run, not submitting message");
+            self.record_dry_run(DryRunOutcome::WouldDeliver, None, Some(gas_limit));
+            return PendingOperationResult::Drop;
+        }
+
         self.submission_data = Some(Box::new(SubmissionData {
             metadata,
             gas_limit,
         }));
+        self.persist_state();
         PendingOperationResult::Success
     }
 
     #[instrument]
     async fn submit(&mut self) -> PendingOperationResult {
-        make_op_try!(|| self.on_reprepare());
+        make_op_try!(|reason| self.on_reprepare(reason));
 
         if self.submitted {
             // this message has already been submitted, possibly not by us
@@ -224,9 +384,11 @@ impl PendingOperation for PendingMessage {
         // been prepared successfully and we don't want to introduce any delay into the
         // submission process.
 
+        // The submission data is kept around so it is persisted alongside the
+        // submitted transaction.
         let state = self
             .submission_data
-            .take()
+            .as_ref()
             .expect("Pending message must be prepared before it can be submitted");
 
         // We use the estimated gas limit from the prior call to
@@ -239,30 +401,16 @@ impl PendingOperation for PendingMessage {
             "processing message"
         );
 
-        op_try!(critical: self.ctx.origin_gas_payment_enforcer.record_tx_outcome(&self.message, tx_outcome), "recording tx outcome");
-        if tx_outcome.executed {
-            info!(
-                hash=?tx_outcome.txid,
-                "Message successfully processed by transaction"
-            );
-            self.submitted = true;
-            self.reset_attempts();
-            self.next_attempt_after = Some(Instant::now() + CONFIRM_DELAY);
-            PendingOperationResult::Success
-        } else {
-            info!(
-                hash=?tx_outcome.txid,
-                "Transaction attempting to process message reverted"
-            );
-            self.on_reprepare()
-        }
+        self.on_tx_outcome(tx_outcome)
     }
 
     async fn confirm(&mut self) -> PendingOperationResult {
-        make_op_try!(|| {
+        make_op_try!(|reason| {
             // Provider error; just try again later
             // Note: this means that we are using `NotReady` for a retryable error case
+            self.last_failure = Some(reason);
             self.inc_attempts();
+            self.persist_state();
             PendingOperationResult::NotReady
         });
 
@@ -275,6 +423,41 @@ impl PendingOperation for PendingMessage {
             return PendingOperationResult::NotReady;
         }
 
+        // Wait for the block of the delivery transaction to be final and make
+        // sure it was not reorged out in the meantime.
+        let mut delivered_at = None;
+        if let Some((block_number, block_hash)) = self.submitted_block {
+            let provider = self.ctx.destination_mailbox.provider();
+            let latest_block = op_try!(
+                provider.get_block_number().await,
+                "fetching latest block number"
+            );
+            let confirmations = latest_block.saturating_sub(block_number);
+            if confirmations < self.ctx.destination_finality_blocks as u64 {
+                trace!(
+                    block_number,
+                    confirmations,
+                    "Block of delivery transaction is not final yet"
+                );
+                self.next_attempt_after = Some(Instant::now() + FINALITY_POLL_INTERVAL);
+                return PendingOperationResult::NotReady;
+            }
+            let canonical_block = op_try!(
+                provider.get_block_by_number(block_number).await,
+                "fetching block of delivery transaction"
+            );
+            if canonical_block.hash != block_hash {
+                warn!(
+                    block_number,
+                    ?block_hash,
+                    canonical_block_hash=?canonical_block.hash,
+                    "Block of delivery transaction was reorged out"
+                );
+                return self.on_delivery_reorged();
+            }
+            delivered_at = Some(canonical_block.timestamp);
+        }
+
         let is_delivered = op_try!(
             self.ctx
                 .destination_mailbox
@@ -287,25 +470,297 @@ impl PendingOperation for PendingMessage {
                 critical: self.record_message_process_success(),
                 "recording message process success"
             );
+            self.delete_state();
+            // Only messages we delivered ourselves say anything about the
+            // latency and profitability of this relayer.
+            if self.submitted_tx.is_some() {
+                self.observe_delivery_latency(delivered_at).await;
+                if let Err(e) = self.ctx.profitability.record_delivery(&self.message) {
+                    warn!(error=?e, "Failed to record delivery for profitability accounting");
+                }
+            }
             PendingOperationResult::Success
         } else {
             self.reset_attempts();
-            self.on_reprepare()
+            self.on_reprepare("Message was not delivered after being submitted")
         }
     }
 
     fn _next_attempt_after(&self) -> Option<Instant> {
         self.next_attempt_after
     }
+
+    fn is_submitted(&self) -> bool {
+        self.submitted
+    }
+
+    fn id(&self) -> H256 {
+        self.message.id()
+    }
+
+    fn num_retries(&self) -> u32 {
+        self.num_retries
+    }
+
+    fn last_failure(&self) -> Option<&str> {
+        self.last_failure.as_deref()
+    }
+
+    fn retry_now(&mut self) {
+        self.next_attempt_after = None;
+        self.persist_state();
+    }
+
+    fn priority_inputs(&self) -> PriorityInputs {
+        PriorityInputs {
+            origin: self.message.origin,
+            gas_payment: self.gas_payment,
+            age: self.age(),
+            num_retries: self.num_retries,
+        }
+    }
 }
 
 impl PendingMessage {
-    fn on_reprepare(&mut self) -> PendingOperationResult {
+    /// Constructor that tries reading the delivery state of a message from
+    /// the origin db, so that retries, backoff and in-flight submissions
+    /// survive a relayer restart.
+    pub fn from_persisted_state(message: HyperlaneMessage, ctx: Arc<MessageContext>) -> Self {
+        let mut pm = Self::new(message, ctx);
+        pm.refresh_gas_payment();
+        let state = match pm
+            .ctx
+            .origin_db
+            .retrieve_pending_message_state_by_message_id(&pm.message.id())
+        {
+            Ok(Some(state)) => state,
+            Ok(None) => return pm,
+            Err(e) => {
+                warn!(error=?e, message=?pm.message, "Failed to read pending message state, starting from scratch");
+                return pm;
+            }
+        };
+        debug!(?state, "Restoring pending message state from db");
+        pm.num_retries = state.num_retries;
+        pm.next_attempt_after = state.next_attempt_after.map(instant_from_unix_timestamp);
+        pm.submitted = state.submitted;
+        pm.submitted_tx = state.submitted_tx;
+        pm.submitted_block = state.submitted_block_number.zip(state.submitted_block_hash);
+        if let Some(first_seen_at) = state.first_seen_at {
+            pm.first_seen_at = first_seen_at;
+        }
+        if let (Some(metadata), Some(gas_limit)) = (state.metadata, state.gas_limit) {
+            pm.submission_data = Some(Box::new(SubmissionData {
+                metadata,
+                gas_limit,
+            }));
+        }
+        pm
+    }
+
+    /// Write the current delivery state to the origin db. Failing to do so
+    /// only affects how much work is repeated after a restart, so errors are
+    /// logged rather than propagated. Nothing is stored in dry-run mode.
+    fn persist_state(&self) {
+        if self.ctx.dry_run.is_some() {
+            return;
+        }
+        let state = PendingMessageState {
+            num_retries: self.num_retries,
+            next_attempt_after: self.next_attempt_after.map(unix_timestamp_from_instant),
+            submitted: self.submitted,
+            submitted_tx: self.submitted_tx,
+            submitted_block_number: self.submitted_block.map(|(number, _)| number),
+            submitted_block_hash: self.submitted_block.map(|(_, hash)| hash),
+            gas_limit: self.submission_data.as_ref().map(|d| d.gas_limit),
+            first_seen_at: Some(self.first_seen_at),
+            metadata: self.submission_data.as_ref().map(|d| d.metadata.clone()),
+        };
+        if let Err(e) = self
+            .ctx
+            .origin_db
+            .store_pending_message_state_by_message_id(&self.message.id(), &state)
+        {
+            warn!(error=?e, ?state, "Failed to persist pending message state");
+        }
+    }
+
+    /// Remove the persisted delivery state once the relayer is done with the
+    /// message, so it isn't restored into the submitter queues on startup.
+    fn delete_state(&self) {
+        if self.ctx.dry_run.is_some() {
+            return;
+        }
+        if let Err(e) = self
+            .ctx
+            .origin_db
+            .delete_pending_message_state_by_message_id(&self.message.id())
+        {
+            warn!(error=?e, "Failed to delete pending message state");
+        }
+    }
+
+    /// Whether this message can be delivered together with other messages in a
+    /// single transaction.
+    fn can_batch(&self) -> bool {
+        !self.submitted
+            && self.submission_data.is_some()
+            && self.ctx.destination_mailbox.supports_batching()
+    }
+
+    fn batch_item(&self) -> BatchItem {
+        let state = self
+            .submission_data
+            .as_ref()
+            .expect("Pending message must be prepared before it can be submitted");
+        BatchItem {
+            message: self.message.clone(),
+            metadata: state.metadata.clone(),
+        }
+    }
+
+    fn gas_limit(&self) -> U256 {
+        self.submission_data
+            .as_ref()
+            .map(|d| d.gas_limit)
+            .unwrap_or_default()
+    }
+
+    /// Record the outcome of a transaction which attempted to process this
+    /// message and decide what to do next.
+    fn on_tx_outcome(&mut self, tx_outcome: TxOutcome) -> PendingOperationResult {
+        make_op_try!(|reason| self.on_reprepare(reason));
+
+        op_try!(critical: self.ctx.origin_gas_payment_enforcer.record_tx_outcome(&self.message, tx_outcome), "recording tx outcome");
+        if let Err(e) = self
+            .ctx
+            .profitability
+            .record_submission(&self.message, &tx_outcome)
+        {
+            warn!(error=?e, "Failed to record submission for profitability accounting");
+        }
+        self.submitted_tx = Some(tx_outcome.txid);
+        if tx_outcome.executed {
+            info!(
+                hash=?tx_outcome.txid,
+                "Message successfully processed by transaction"
+            );
+            self.submitted = true;
+            self.submitted_block = tx_outcome.block_number.zip(tx_outcome.block_hash);
+            self.reset_attempts();
+            self.next_attempt_after = Some(Instant::now() + self.retry_policy().confirm_delay);
+            self.persist_state();
+            PendingOperationResult::Success
+        } else {
+            info!(
+                hash=?tx_outcome.txid,
+                "Transaction attempting to process message reverted"
+            );
+            self.on_reprepare("Transaction attempting to process message reverted")
+        }
+    }
+
+    /// Record the outcome of a batch transaction this message was part of.
+    /// The gas used by the transaction is attributed to each message in
+    /// proportion to its estimated gas limit.
+    fn on_batch_tx_outcome(
+        &mut self,
+        tx_outcome: TxOutcome,
+        batch_gas_limit: U256,
+    ) -> PendingOperationResult {
+        let gas_used = if batch_gas_limit.is_zero() {
+            U256::zero()
+        } else {
+            tx_outcome.gas_used * self.gas_limit() / batch_gas_limit
+        };
+        self.on_tx_outcome(TxOutcome {
+            gas_used,
+            ..tx_outcome
+        })
+    }
+
+    fn on_reprepare(&mut self, reason: impl Into<String>) -> PendingOperationResult {
+        let reason = reason.into();
+        self.record_dry_run(DryRunOutcome::NotReady, Some(reason.clone()), None);
+        self.last_failure = Some(reason.clone());
         self.inc_attempts();
         self.submitted = false;
+        self.submitted_block = None;
+        self.persist_state();
+        if self
+            .ctx
+            .max_retries
+            .map_or(false, |max| self.num_retries > max)
+        {
+            warn!(num_retries = self.num_retries, %reason, "Giving up on message after too many failed attempts");
+            self.store_dead_letter(DeadLetterClass::RetriesExhausted, reason);
+            return PendingOperationResult::Drop;
+        }
+        PendingOperationResult::Reprepare
+    }
+
+    /// The block the delivery transaction was included in is no longer part
+    /// of the destination chain. Queue the message to be submitted again with
+    /// the metadata that was built for it before.
+    fn on_delivery_reorged(&mut self) -> PendingOperationResult {
+        self.ctx.metrics.delivery_reorgs.inc();
+        self.last_failure = Some("Delivery transaction was reorged out".into());
+        self.reset_attempts();
+        self.submitted = false;
+        self.submitted_block = None;
+        self.resubmit_after_reorg = true;
+        self.persist_state();
         PendingOperationResult::Reprepare
     }
 
+    /// Record that the relayer gave up on this message so operators can find
+    /// and re-inject it, and forget its delivery state. Nothing is stored in
+    /// dry-run mode.
+    fn store_dead_letter(&self, class: DeadLetterClass, reason: impl Into<String>) {
+        if self.ctx.dry_run.is_some() {
+            return;
+        }
+        if let Err(e) = self.ctx.origin_db.store_dead_letter_by_message_id(
+            &self.message.id(),
+            &dead_letter(&self.message, class, reason),
+        ) {
+            warn!(error=?e, ?class, "Failed to store dead letter");
+        }
+        self.delete_state();
+    }
+
+    fn record_dry_run(
+        &self,
+        outcome: DryRunOutcome,
+        reason: Option<String>,
+        gas_limit: Option<U256>,
+    ) {
+        if let Some(dry_run) = &self.ctx.dry_run {
+            dry_run.record(
+                self.ctx.origin_db.domain(),
+                self.ctx.destination_mailbox.domain(),
+                &self.message,
+                outcome,
+                reason,
+                gas_limit,
+            );
+        }
+    }
+
+    /// Read the total gas payment for the message from the db. Payments can
+    /// be indexed after the message, so this is refreshed before every
+    /// attempt.
+    fn refresh_gas_payment(&mut self) {
+        match self
+            .ctx
+            .origin_db
+            .retrieve_gas_payment_by_message_id(self.message.id())
+        {
+            Ok(payment) => self.gas_payment = payment.payment,
+            Err(e) => warn!(error=?e, "Failed to read gas payment for message"),
+        }
+    }
+
     fn is_ready(&self) -> bool {
         self.next_attempt_after
             .map(|a| Instant::now() >= a)
@@ -318,16 +773,68 @@ impl PendingMessage {
     /// final processing -- after this function has been seen to
     /// `return Ok(())`, then without a wiped HyperlaneDB, we will never
     /// re-attempt processing for this message again, even after the relayer
-    /// restarts.
+    /// restarts. In dry-run mode only the metrics are updated.
     fn record_message_process_success(&mut self) -> Result<()> {
-        self.ctx
-            .origin_db
-            .store_processed_by_nonce(&self.message.nonce, &true)?;
+        if self.ctx.dry_run.is_none() {
+            self.ctx
+                .origin_db
+                .store_processed_by_nonce(&self.message.nonce, &true)?;
+        }
         self.ctx.metrics.update_nonce(&self.message);
         self.ctx.metrics.messages_processed.inc();
         Ok(())
     }
 
+    /// Observe the time from the block the message was dispatched in to the
+    /// block it was delivered in, given by its unix timestamp.
+    async fn observe_delivery_latency(&mut self, delivered_at: Option<u64>) {
+        let Some(delivered_at) = delivered_at else {
+            debug!("Delivery block of message is unknown, not recording delivery latency");
+            return;
+        };
+        if self.dispatched_at.is_none() {
+            match self.dispatch_timestamp().await {
+                Ok(timestamp) => self.dispatched_at = timestamp,
+                Err(e) => {
+                    warn!(error=?e, "Failed to fetch dispatch block of message");
+                    return;
+                }
+            }
+        }
+        let Some(dispatched_at) = self.dispatched_at else {
+            debug!("Dispatch block of message is unknown, not recording delivery latency");
+            return;
+        };
+        self.ctx.metrics.observe(
+            &self.ctx.metrics.delivery_latency,
+            self.ism_label(),
+            Duration::from_secs(delivered_at.saturating_sub(dispatched_at)),
+        );
+    }
+
+    /// Unix timestamp of the block the message was dispatched in, if known.
+    async fn dispatch_timestamp(&self) -> Result<Option<u64>> {
+        let Some(block_hash) = self
+            .ctx
+            .origin_db
+            .retrieve_dispatched_block_hash_by_nonce(&self.message.nonce)?
+        else {
+            return Ok(None);
+        };
+        let block = self
+            .ctx
+            .origin_provider
+            .get_block_by_hash(&block_hash)
+            .await?;
+        Ok(Some(block.timestamp))
+    }
+
+    fn ism_label(&self) -> String {
+        self.ism_module_type
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "unknown".to_owned())
+    }
+
     fn reset_attempts(&mut self) {
         self.num_retries = 0;
         self.next_attempt_after = None;
@@ -337,30 +844,239 @@ impl PendingMessage {
     fn inc_attempts(&mut self) {
         self.num_retries += 1;
         self.last_attempted_at = Instant::now();
-        self.next_attempt_after = PendingMessage::calculate_msg_backoff(self.num_retries)
+        self.next_attempt_after = self
+            .retry_policy()
+            .backoff(self.num_retries)
             .map(|dur| self.last_attempted_at + dur);
     }
 
-    /// Get duration we should wait before re-attempting to deliver a message
-    /// given the number of retries.
-    fn calculate_msg_backoff(num_retries: u32) -> Option<Duration> {
-        Some(Duration::from_secs(match num_retries {
-            i if i < 1 => return None,
-            // wait 10s for the first few attempts; this prevents thrashing
-            i if (1..12).contains(&i) => 10,
-            // wait 90s to 19.5min with a linear increase
-            i if (12..24).contains(&i) => (i as u64 - 11) * 90,
-            // exponential increase + 30 min; -21 makes it so that at i = 32 it will be
-            // ~60min timeout (64min to be more precise).
-            i => (2u64).pow(i - 21) + 60 * 30,
-        }))
+    /// The retry policy of the first retry policy config the message matches.
+    fn retry_policy(&self) -> RetryPolicy {
+        self.ctx
+            .retry_policies
+            .iter()
+            .find(|conf| conf.matching_list.msg_matches(&self.message, true))
+            .map(|conf| conf.policy)
+            .unwrap_or_default()
+    }
+
+    /// Whether the message was dispatched longer ago than `max_age`. Messages
+    /// whose dispatch block is unknown are never considered too old.
+    async fn exceeds_max_age(&mut self, max_age: Duration) -> Result<bool> {
+        if self.dispatched_at.is_none() {
+            self.dispatched_at = self.dispatch_timestamp().await?;
+        }
+        let Some(dispatched_at) = self.dispatched_at else {
+            return Ok(false);
+        };
+        Ok(unix_now().saturating_sub(dispatched_at) > max_age.as_secs())
+    }
+
+    /// Time since the message was dispatched, or since the relayer first saw
+    /// it if its dispatch block hasn't been looked up yet.
+    fn age(&self) -> Duration {
+        let since = self.dispatched_at.unwrap_or(self.first_seen_at);
+        Duration::from_secs(unix_now().saturating_sub(since))
+    }
+}
+
+/// Submit several prepared messages to the same destination, delivering the
+/// ones that can be batched together through the destination's multicall
+/// contract. Returns the result for each message in the order given.
+#[instrument(skip_all, fields(batch_size = batch.len()))]
+pub async fn submit_batch(batch: &mut [&mut PendingMessage]) -> Vec<PendingOperationResult> {
+    let mut results: Vec<Option<PendingOperationResult>> = batch.iter().map(|_| None).collect();
+
+    // Messages which cannot be batched are submitted on their own.
+    let mut batchable = vec![];
+    for (i, pm) in batch.iter_mut().enumerate() {
+        if pm.can_batch() {
+            batchable.push(i);
+        } else {
+            results[i] = Some(pm.submit().await);
+        }
+    }
+    if !batchable.is_empty() {
+        submit_batchable(batch, &batchable, &mut results).await;
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.expect("Every message in the batch must have a result"))
+        .collect()
+}
+
+/// Simulate the batch first so that messages which would revert are split
+/// out instead of reverting the whole transaction, then submit the rest in
+/// chunks which respect the transaction gas limit.
+async fn submit_batchable(
+    batch: &mut [&mut PendingMessage],
+    batchable: &[usize],
+    results: &mut [Option<PendingOperationResult>],
+) {
+    let mailbox = batch[batchable[0]].ctx.destination_mailbox.clone();
+    let max_gas_limit = batch[batchable[0]].ctx.transaction_gas_limit;
+    let items: Vec<BatchItem> = batchable.iter().map(|&i| batch[i].batch_item()).collect();
+
+    let simulated = simulation_outcomes(mailbox.simulate_process_batch(&items).await, items.len());
+    let Some(simulated) = simulated else {
+        // Fall back to submitting each message on its own.
+        for &i in batchable {
+            results[i] = Some(batch[i].submit().await);
+        }
+        return;
+    };
+
+    let mut succeeding = vec![];
+    for (&i, would_succeed) in batchable.iter().zip(simulated) {
+        if would_succeed {
+            succeeding.push((i, batch[i].gas_limit()));
+        } else {
+            info!(msg=%batch[i].message, "Message would revert, removing it from the batch");
+            results[i] = Some(batch[i].on_reprepare("Message would revert as part of a batch"));
+        }
+    }
+
+    for chunk in chunk_batch(succeeding, max_gas_limit) {
+        if let [i] = chunk.indices[..] {
+            results[i] = Some(batch[i].submit().await);
+            continue;
+        }
+        let items: Vec<BatchItem> = chunk
+            .indices
+            .iter()
+            .map(|&i| batch[i].batch_item())
+            .collect();
+        match mailbox
+            .process_batch(&items, Some(chunk.tx_gas_limit()))
+            .await
+        {
+            Ok(tx_outcome) => {
+                for &i in &chunk.indices {
+                    results[i] =
+                        Some(batch[i].on_batch_tx_outcome(tx_outcome, chunk.messages_gas_limit));
+                }
+            }
+            Err(e) => {
+                warn!(error=?e, "Error when processing batch");
+                for &i in &chunk.indices {
+                    results[i] =
+                        Some(batch[i].on_reprepare(format!("Error when processing batch: {e:#}")));
+                }
+            }
+        }
+    }
+}
+
+/// Whether each message of a batch would be delivered successfully, or None
+/// if the simulation failed and the messages should be submitted on their
+/// own.
+fn simulation_outcomes(simulated: ChainResult<Vec<bool>>, expected: usize) -> Option<Vec<bool>> {
+    match simulated {
+        Ok(simulated) if simulated.len() == expected => Some(simulated),
+        Ok(simulated) => {
+            warn!(
+                expected,
+                actual = simulated.len(),
+                "Unexpected number of results when simulating batch"
+            );
+            None
+        }
+        Err(e) => {
+            warn!(error=?e, "Error when simulating batch");
+            None
+        }
     }
 }
 
+/// Messages of a batch which are delivered in the same transaction.
+#[derive(Debug, PartialEq, Eq)]
+struct BatchChunk {
+    /// Indices of the messages in the batch
+    indices: Vec<usize>,
+    /// Sum of the gas limits estimated for delivering the messages
+    messages_gas_limit: U256,
+}
+
+impl BatchChunk {
+    /// Gas limit of the transaction delivering the chunk, including the
+    /// overhead of the multicall contract.
+    fn tx_gas_limit(&self) -> U256 {
+        self.messages_gas_limit
+            + BATCH_BASE_GAS_OVERHEAD
+            + U256::from(BATCH_PER_CALL_GAS_OVERHEAD) * self.indices.len()
+    }
+}
+
+/// Split messages, given as their index in the batch and estimated gas
+/// limit, into consecutive chunks whose transactions respect the gas limit.
+fn chunk_batch(
+    messages: impl IntoIterator<Item = (usize, U256)>,
+    max_gas_limit: Option<U256>,
+) -> Vec<BatchChunk> {
+    let mut chunks: Vec<BatchChunk> = vec![];
+    for (i, gas_limit) in messages {
+        let fits = |chunk: &BatchChunk| {
+            max_gas_limit
+                .map(|max| chunk.tx_gas_limit() + gas_limit + BATCH_PER_CALL_GAS_OVERHEAD <= max)
+                .unwrap_or(true)
+        };
+        match chunks.last_mut() {
+            Some(chunk) if fits(chunk) => {
+                chunk.indices.push(i);
+                chunk.messages_gas_limit += gas_limit;
+            }
+            _ => chunks.push(BatchChunk {
+                indices: vec![i],
+                messages_gas_limit: gas_limit,
+            }),
+        }
+    }
+    chunks
+}
+
+/// The current unix timestamp in seconds.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Convert an `Instant` into a unix timestamp in seconds so it can be
+/// persisted across restarts.
+fn unix_timestamp_from_instant(instant: Instant) -> u64 {
+    let now = Instant::now();
+    let now_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let unix = if instant >= now {
+        now_unix + (instant - now)
+    } else {
+        now_unix.saturating_sub(now - instant)
+    };
+    unix.as_secs()
+}
+
+/// Convert a persisted unix timestamp in seconds back into an `Instant`.
+/// Timestamps in the past map to now.
+fn instant_from_unix_timestamp(timestamp: u64) -> Instant {
+    let now_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Instant::now() + Duration::from_secs(timestamp).saturating_sub(now_unix)
+}
+
 #[derive(Debug)]
 pub struct MessageSubmissionMetrics {
+    origin: String,
+    destination: String,
     last_known_nonce: IntGauge,
     messages_processed: IntCounter,
+    delivery_reorgs: IntCounter,
+    delivery_latency: HistogramVec,
+    metadata_wait: HistogramVec,
+    gas_payment_wait: HistogramVec,
 }
 
 impl MessageSubmissionMetrics {
@@ -372,6 +1088,8 @@ impl MessageSubmissionMetrics {
         let origin = origin.name();
         let destination = destination.name();
         Self {
+            origin: origin.to_owned(),
+            destination: destination.to_owned(),
             last_known_nonce: metrics.last_known_message_nonce().with_label_values(&[
                 "message_processed",
                 origin,
@@ -380,9 +1098,22 @@ impl MessageSubmissionMetrics {
             messages_processed: metrics
                 .messages_processed_count()
                 .with_label_values(&[origin, destination]),
+            delivery_reorgs: metrics
+                .message_delivery_reorgs()
+                .with_label_values(&[origin, destination]),
+            delivery_latency: metrics.message_delivery_latency(),
+            metadata_wait: metrics.message_metadata_wait(),
+            gas_payment_wait: metrics.message_gas_payment_wait(),
         }
     }
 
+    /// Observe a duration on one of the latency histograms of this route.
+    fn observe(&self, histogram: &HistogramVec, ism_type: String, duration: Duration) {
+        histogram
+            .with_label_values(&[&self.origin, &self.destination, &ism_type])
+            .observe(duration.as_secs_f64());
+    }
+
     fn update_nonce(&self, msg: &HyperlaneMessage) {
         // this is technically a race condition between `.get` and `.set` but worst case
         // the gauge should get corrected on the next update and is not an issue
@@ -391,3 +1122,61 @@ impl MessageSubmissionMetrics {
             .set(std::cmp::max(self.last_known_nonce.get(), msg.nonce as i64));
     }
 }
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::ChainCommunicationError;
+
+    use super::*;
+
+    fn gas(amounts: &[u64]) -> Vec<(usize, U256)> {
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| (i, U256::from(*amount)))
+            .collect()
+    }
+
+    #[test]
+    fn test_simulation_outcomes() {
+        assert_eq!(
+            simulation_outcomes(Ok(vec![true, false]), 2),
+            Some(vec![true, false])
+        );
+        // a malformed or failed simulation falls back to submitting the
+        // messages on their own
+        assert_eq!(simulation_outcomes(Ok(vec![true]), 2), None);
+        assert_eq!(
+            simulation_outcomes(Err(ChainCommunicationError::TransactionTimeout()), 2),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chunk_batch_without_gas_limit() {
+        let chunks = chunk_batch(gas(&[100_000, 200_000, 300_000]), None);
+        assert_eq!(
+            chunks,
+            vec![BatchChunk {
+                indices: vec![0, 1, 2],
+                messages_gas_limit: U256::from(600_000),
+            }]
+        );
+        assert_eq!(
+            chunks[0].tx_gas_limit(),
+            U256::from(600_000 + BATCH_BASE_GAS_OVERHEAD + 3 * BATCH_PER_CALL_GAS_OVERHEAD)
+        );
+    }
+
+    #[test]
+    fn test_chunk_batch_respects_gas_limit_with_overhead() {
+        // Two messages would fit without the multicall overhead
+        let max = U256::from(400_000 + BATCH_BASE_GAS_OVERHEAD + BATCH_PER_CALL_GAS_OVERHEAD);
+        let chunks = chunk_batch(gas(&[200_000, 200_000, 150_000, 900_000]), Some(max));
+        let indices: Vec<_> = chunks.iter().map(|c| c.indices.clone()).collect();
+        assert_eq!(indices, vec![vec![0], vec![1, 2], vec![3]]);
+        for chunk in &chunks[..2] {
+            assert!(chunk.tx_gas_limit() <= max);
+        }
+    }
+}
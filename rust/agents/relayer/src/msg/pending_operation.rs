@@ -5,10 +5,11 @@ use async_trait::async_trait;
 use enum_dispatch::enum_dispatch;
 use eyre::Report;
 
-use hyperlane_core::HyperlaneDomain;
+use hyperlane_core::{HyperlaneDomain, H256};
 
 #[allow(unused_imports)] // required for enum_dispatch
 use super::pending_message::PendingMessage;
+use super::priority::PriorityInputs;
 
 /// A pending operation that will be run by the submitter and cause a
 /// transaction to be sent.
@@ -54,6 +55,28 @@ pub trait PendingOperation {
     /// This is only used for sorting, the functions are responsible for
     /// returning `NotReady` if it is too early and matters.
     fn _next_attempt_after(&self) -> Option<Instant>;
+
+    /// Whether this operation has already been submitted and only needs to be
+    /// confirmed. Used to put operations restored from a previous run back
+    /// into the right queue.
+    fn is_submitted(&self) -> bool;
+
+    /// A unique identifier for this operation, e.g. the message id.
+    fn id(&self) -> H256;
+
+    /// The number of times this operation has been attempted unsuccessfully.
+    fn num_retries(&self) -> u32;
+
+    /// The reason the last attempt of this operation failed, if any.
+    fn last_failure(&self) -> Option<&str>;
+
+    /// Clear any backoff so the operation is attempted again as soon as
+    /// possible.
+    fn retry_now(&mut self);
+
+    /// What to prioritise this operation by when several are ready to be
+    /// prepared.
+    fn priority_inputs(&self) -> PriorityInputs;
 }
 
 /// A "dynamic" pending operation implementation which knows about the
@@ -137,7 +160,7 @@ macro_rules! make_op_try {
                                             Ok(v) => v,
                                             Err(e) => {
                                                 warn!(error=?e, concat!("Error when ", $ctx));
-                                                return $on_retry();
+                                                return $on_retry(format!(concat!("Error when ", $ctx, ": {:#}"), e));
                                             }
                                         }
                                     };
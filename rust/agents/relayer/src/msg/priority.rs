@@ -0,0 +1,199 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::{Duration, Instant};
+
+use hyperlane_core::U256;
+
+use super::pending_operation::{DynPendingOperation, PendingOperation};
+use crate::settings::SubmissionPriorityConf;
+
+/// What an operation is prioritised by when choosing between operations which
+/// are ready to be prepared.
+#[derive(Debug, Clone, Copy)]
+pub struct PriorityInputs {
+    /// Domain id of the chain the operation originated from
+    pub origin: u32,
+    /// Total gas payment made for the operation on its origin
+    pub gas_payment: U256,
+    /// Time since the operation's message was dispatched
+    pub age: Duration,
+    /// Number of failed attempts
+    pub num_retries: u32,
+}
+
+/// Chooses the next operation to prepare according to a
+/// [SubmissionPriorityConf].
+///
+/// The queue itself stays ordered by next attempt time, so the operations
+/// which are ready to be attempted are always at the front of it. Those are
+/// scored and the best one is returned; with origin fairness enabled, the
+/// origin which has least recently had an operation chosen goes first.
+#[derive(Debug)]
+pub struct OperationSelector {
+    conf: SubmissionPriorityConf,
+    /// Number of selections made so far, used as a logical clock
+    selections: u64,
+    /// When each origin last had an operation chosen
+    last_chosen: HashMap<u32, u64>,
+}
+
+impl OperationSelector {
+    pub fn new(conf: SubmissionPriorityConf) -> Self {
+        Self {
+            conf,
+            selections: 0,
+            last_chosen: HashMap::new(),
+        }
+    }
+
+    /// Take the operation that should be prepared next out of the queue.
+    pub fn pop(
+        &mut self,
+        queue: &mut BinaryHeap<Reverse<Box<DynPendingOperation>>>,
+    ) -> Option<Box<DynPendingOperation>> {
+        if self.conf.is_default() {
+            return queue.pop().map(|Reverse(op)| op);
+        }
+
+        let now = Instant::now();
+        let mut ready = Vec::new();
+        while queue
+            .peek()
+            .map(|Reverse(op)| op._next_attempt_after().map_or(true, |t| t <= now))
+            .unwrap_or(false)
+        {
+            ready.push(queue.pop().unwrap().0);
+        }
+        if ready.is_empty() {
+            // Nothing is ready, the earliest operation is the one to wait on
+            return queue.pop().map(|Reverse(op)| op);
+        }
+
+        let inputs: Vec<_> = ready.iter().map(|op| op.priority_inputs()).collect();
+        let chosen = self.choose(&inputs);
+        let op = ready.swap_remove(chosen);
+        queue.extend(ready.into_iter().map(Reverse));
+        Some(op)
+    }
+
+    /// Index of the candidate to choose. Candidates must be in queue order,
+    /// which breaks ties between equal scores, and there must be at least
+    /// one.
+    fn choose(&mut self, candidates: &[PriorityInputs]) -> usize {
+        let origin = self.conf.origin_fairness.then(|| {
+            candidates
+                .iter()
+                .map(|c| c.origin)
+                .min_by_key(|origin| (self.last_chosen.get(origin).copied(), *origin))
+                .unwrap()
+        });
+
+        let chosen = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| origin.map_or(true, |o| c.origin == o))
+            .map(|(i, c)| (i, self.score(c)))
+            // `max_by` returns the last of equal elements, so compare in
+            // reverse to keep the first
+            .rev()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+            .unwrap();
+
+        self.selections += 1;
+        self.last_chosen
+            .insert(candidates[chosen].origin, self.selections);
+        chosen
+    }
+
+    fn score(&self, inputs: &PriorityInputs) -> f64 {
+        let age_minutes = inputs.age.as_secs_f64() / 60.;
+        self.conf.gas_payment_weight as f64 * log2_plus_one(inputs.gas_payment)
+            + self.conf.age_weight as f64 * age_minutes
+            - self.conf.retry_weight as f64 * inputs.num_retries as f64
+    }
+}
+
+/// `log2(1 + value)`, so that a payment of zero scores zero.
+fn log2_plus_one(value: U256) -> f64 {
+    // Keep the 64 most significant bits, the rest don't change the result
+    // noticeably
+    let shift = value.bits().saturating_sub(64);
+    ((value >> shift).as_u64() as f64 + 1.).log2() + shift as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn inputs(origin: u32, gas_payment: u64, num_retries: u32) -> PriorityInputs {
+        PriorityInputs {
+            origin,
+            gas_payment: gas_payment.into(),
+            age: Duration::ZERO,
+            num_retries,
+        }
+    }
+
+    #[test]
+    fn test_log2_plus_one() {
+        assert_eq!(log2_plus_one(U256::zero()), 0.);
+        assert_eq!(log2_plus_one(1.into()), 1.);
+        let large = log2_plus_one(U256::from(1) << 200);
+        assert!((large - 200.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_prefers_paid_messages() {
+        let mut selector = OperationSelector::new(SubmissionPriorityConf {
+            gas_payment_weight: 1,
+            retry_weight: 2,
+            ..Default::default()
+        });
+        let candidates = [inputs(1, 0, 0), inputs(1, 1_000_000, 3), inputs(1, 10, 0)];
+        assert_eq!(selector.choose(&candidates), 1);
+
+        // Enough failed attempts outweigh the payment
+        let candidates = [inputs(1, 0, 0), inputs(1, 1_000_000, 20)];
+        assert_eq!(selector.choose(&candidates), 0);
+    }
+
+    #[test]
+    fn test_prefers_older_messages() {
+        let mut selector = OperationSelector::new(SubmissionPriorityConf {
+            age_weight: 1,
+            ..Default::default()
+        });
+        let old = PriorityInputs {
+            age: Duration::from_secs(600),
+            ..inputs(1, 0, 0)
+        };
+        let candidates = [inputs(1, 0, 0), old];
+        assert_eq!(selector.choose(&candidates), 1);
+    }
+
+    #[test]
+    fn test_ties_keep_queue_order() {
+        let mut selector = OperationSelector::new(SubmissionPriorityConf {
+            gas_payment_weight: 1,
+            ..Default::default()
+        });
+        let candidates = [inputs(1, 5, 0), inputs(1, 5, 0), inputs(1, 5, 0)];
+        assert_eq!(selector.choose(&candidates), 0);
+    }
+
+    #[test]
+    fn test_origin_fairness() {
+        let mut selector = OperationSelector::new(SubmissionPriorityConf {
+            gas_payment_weight: 1,
+            origin_fairness: true,
+            ..Default::default()
+        });
+        // Origin 1 pays more but origin 2 still gets every other turn
+        let candidates = [inputs(1, 1_000, 0), inputs(1, 1_000, 0), inputs(2, 1, 0)];
+        let origins: Vec<_> = (0..4)
+            .map(|_| candidates[selector.choose(&candidates)].origin)
+            .collect();
+        assert_eq!(origins, vec![1, 2, 1, 2]);
+    }
+}
@@ -1,17 +1,26 @@
 use std::fmt::{Debug, Formatter};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use derive_new::new;
 use eyre::Result;
-use prometheus::IntGauge;
+use prometheus::{IntCounter, IntCounterVec, IntGauge};
 use tokio::{
     sync::{mpsc::UnboundedSender, RwLock},
     task::JoinHandle,
 };
-use tracing::{debug, info_span, instrument, instrument::Instrumented, trace, Instrument};
+use tracing::{
+    debug, field, info, info_span, instrument, instrument::Instrumented, trace, warn, Instrument,
+};
 
 use hyperlane_base::{db::HyperlaneRocksDB, CoreMetrics};
-use hyperlane_core::{HyperlaneDomain, HyperlaneMessage};
+use hyperlane_core::{body::MessageBodyDecoders, HyperlaneDomain, HyperlaneMessage};
 
 use crate::msg::pending_operation::DynPendingOperation;
 use crate::{merkle_tree_builder::MerkleTreeBuilder, settings::matching_list::MatchingList};
@@ -32,8 +41,24 @@ pub struct MessageProcessor {
     send_channels: HashMap<u32, UnboundedSender<Box<DynPendingOperation>>>,
     /// Needed context to send a message for each destination chain
     destination_ctxs: HashMap<u32, Arc<MessageContext>>,
+    /// Used to show the bodies of messages in logs
+    body_decoders: MessageBodyDecoders,
+    /// Whether the relayer runs in dry-run mode, in which nothing is written
+    /// to the db
+    dry_run: bool,
+    /// The nonce of the next message to process, published after every tick
+    /// so the admin API doesn't queue messages the processor will send to
+    /// the submitters itself
+    processor_nonce: Arc<AtomicU32>,
     #[new(default)]
     message_nonce: u32,
+    /// Number of message rollbacks in the db which have been accounted for
+    #[new(default)]
+    seen_rollbacks: u32,
+    /// Nonces of the messages which were restored into the submitter queues
+    /// on startup and must not be sent to them again
+    #[new(default)]
+    restored_nonces: HashSet<u32>,
 }
 
 impl Debug for MessageProcessor {
@@ -66,9 +91,64 @@ impl MessageProcessor {
         // satisfied or the message is disqualified, push the message onto
         // self.tx_msg and then continue the scan at the next highest
         // nonce.
+        self.restore_pending_messages().await?;
         loop {
             self.tick().await?;
+            self.processor_nonce
+                .store(self.message_nonce, Ordering::Relaxed);
+        }
+    }
+
+    /// Send the messages which have persisted delivery state, i.e. which the
+    /// relayer was working on before it restarted, straight to the submitter
+    /// queues instead of waiting for the scan to reach them.
+    async fn restore_pending_messages(&mut self) -> Result<()> {
+        let mut messages = Vec::new();
+        for (id, _) in self.db.retrieve_pending_message_states()? {
+            let Some(message) = self.db.retrieve_message_by_id(&id)? else {
+                debug!(?id, "Message with pending state is not in the db, not restoring it");
+                continue;
+            };
+            if self
+                .db
+                .retrieve_processed_by_nonce(&message.nonce)?
+                .unwrap_or(false)
+            {
+                debug!(
+                    ?id,
+                    "Message with pending state was processed, deleting its state"
+                );
+                if !self.dry_run {
+                    self.db.delete_pending_message_state_by_message_id(&id)?;
+                }
+                continue;
+            }
+            if self.should_skip(&message)? {
+                continue;
+            }
+            messages.push(message);
+        }
+
+        // The metadata builder needs the merkle tree to include the messages
+        if let Some(max_nonce) = messages.iter().map(|m| m.nonce).max() {
+            self.prover_sync
+                .write()
+                .await
+                .update_to_index(max_nonce)
+                .await?;
         }
+        let count = messages.len();
+        for message in messages {
+            let destination = message.destination;
+            self.restored_nonces.insert(message.nonce);
+            let pending_msg = PendingMessage::from_persisted_state(
+                message,
+                self.destination_ctxs[&destination].clone(),
+            );
+            self.send_channels[&destination].send(Box::new(pending_msg.into()))?;
+        }
+        info!(count, "Restored pending messages into the submitter queues");
+        Ok(())
     }
 
     /// Tries to get the next message to process.
@@ -107,38 +187,97 @@ impl MessageProcessor {
         }
     }
 
+    /// If messages which were already scanned were rolled back because of a
+    /// reorg on the origin, rewind so they are processed again once they have
+    /// been re-indexed.
+    fn handle_rollbacks(&mut self) -> Result<()> {
+        let (rollbacks, reorged_nonce) = self
+            .db
+            .retrieve_message_rollbacks_since(self.seen_rollbacks)?;
+        self.seen_rollbacks = rollbacks;
+        if let Some(reorged_nonce) = reorged_nonce.filter(|nonce| *nonce < self.message_nonce) {
+            warn!(
+                reorged_nonce,
+                message_nonce = self.message_nonce,
+                "Messages were reorged out, rewinding"
+            );
+            self.message_nonce = reorged_nonce;
+            self.restored_nonces.retain(|nonce| *nonce < reorged_nonce);
+        }
+        Ok(())
+    }
+
+    /// Whether the message should not be relayed, e.g. because it is not
+    /// whitelisted or has been dead-lettered.
+    fn should_skip(&self, message: &HyperlaneMessage) -> Result<bool> {
+        // Skip if not whitelisted.
+        if !self.whitelist.msg_matches(message, true) {
+            debug!(?message, whitelist=?self.whitelist, "Message not whitelisted, skipping");
+            return Ok(true);
+        }
+
+        // Skip if the message is blacklisted
+        if self.blacklist.msg_matches(message, false) {
+            debug!(?message, blacklist=?self.blacklist, "Message blacklisted, skipping");
+            return Ok(true);
+        }
+
+        // Skip if the message has a version the relayer can't handle, it
+        // would be relayed blindly otherwise
+        if !message.has_supported_version() {
+            warn!(?message, "Message has an unsupported version, skipping");
+            self.metrics.unsupported_version(message.version).inc();
+            return Ok(true);
+        }
+
+        // Skip if the relayer gave up on the message before, it has to be
+        // re-injected through the admin API
+        if self
+            .db
+            .retrieve_dead_letter_by_message_id(&message.id())?
+            .is_some()
+        {
+            debug!(?message, "Message was dead-lettered, skipping");
+            return Ok(true);
+        }
+
+        // Skip if the message is intended for this origin
+        if message.destination == self.domain().id() {
+            debug!(?message, "Message destined for self, skipping");
+            return Ok(true);
+        }
+
+        // Skip if the message is intended for a destination we do not service
+        if !self.send_channels.contains_key(&message.destination) {
+            debug!(?message, "Message destined for unknown domain, skipping");
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     /// One round of processing, extracted from infinite work loop for
     /// testing purposes.
     async fn tick(&mut self) -> Result<()> {
+        self.handle_rollbacks()?;
+
         // Scan until we find next nonce without delivery confirmation.
         if let Some(msg) = self.try_get_unprocessed_message()? {
-            debug!(?msg, "Processor working on message");
+            debug!(
+                ?msg,
+                body = self.body_decoders.decode(&msg).map(field::display),
+                "Processor working on message"
+            );
             let destination = msg.destination;
 
-            // Skip if not whitelisted.
-            if !self.whitelist.msg_matches(&msg, true) {
-                debug!(?msg, whitelist=?self.whitelist, "Message not whitelisted, skipping");
-                self.message_nonce += 1;
-                return Ok(());
-            }
-
-            // Skip if the message is blacklisted
-            if self.blacklist.msg_matches(&msg, false) {
-                debug!(?msg, blacklist=?self.blacklist, "Message blacklisted, skipping");
-                self.message_nonce += 1;
-                return Ok(());
-            }
-
-            // Skip if the message is intended for this origin
-            if destination == self.domain().id() {
-                debug!(?msg, "Message destined for self, skipping");
+            // Skip if the message was already sent to the submitter when
+            // restoring its persisted state
+            if self.restored_nonces.remove(&msg.nonce) {
+                debug!(?msg, "Message was restored on startup, skipping");
                 self.message_nonce += 1;
                 return Ok(());
             }
 
-            // Skip if the message is intended for a destination we do not service
-            if !self.send_channels.contains_key(&destination) {
-                debug!(?msg, "Message destined for unknown domain, skipping");
+            if self.should_skip(&msg)? {
                 self.message_nonce += 1;
                 return Ok(());
             }
@@ -153,7 +292,10 @@ impl MessageProcessor {
             debug!(%msg, "Sending message to submitter");
 
             // Finally, build the submit arg and dispatch it to the submitter.
-            let pending_msg = PendingMessage::new(msg, self.destination_ctxs[&destination].clone());
+            let pending_msg = PendingMessage::from_persisted_state(
+                msg,
+                self.destination_ctxs[&destination].clone(),
+            );
             self.send_channels[&destination].send(Box::new(pending_msg.into()))?;
             self.message_nonce += 1;
         } else {
@@ -165,8 +307,10 @@ impl MessageProcessor {
 
 #[derive(Debug)]
 pub struct MessageProcessorMetrics {
+    origin: String,
     max_last_known_message_nonce_gauge: IntGauge,
     last_known_message_nonce_gauges: HashMap<u32, IntGauge>,
+    unsupported_message_versions: IntCounterVec,
 }
 
 impl MessageProcessorMetrics {
@@ -187,14 +331,21 @@ impl MessageProcessorMetrics {
             );
         }
         Self {
+            origin: origin.name().to_owned(),
             max_last_known_message_nonce_gauge: metrics
                 .last_known_message_nonce()
                 .with_label_values(&["processor_loop", origin.name(), "any"]),
             last_known_message_nonce_gauges: gauges,
+            unsupported_message_versions: metrics.unsupported_message_versions(),
         }
     }
 
     fn get(&self, destination: u32) -> Option<&IntGauge> {
         self.last_known_message_nonce_gauges.get(&destination)
     }
+
+    fn unsupported_version(&self, version: u8) -> IntCounter {
+        self.unsupported_message_versions
+            .with_label_values(&[self.origin.as_str(), version.to_string().as_str()])
+    }
 }
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use eyre::{eyre, Result};
+use prometheus::{CounterVec, IntCounterVec};
+use serde::Serialize;
+
+use hyperlane_base::{
+    db::{HyperlaneRocksDB, ProfitabilityTotals, RouteProfitability},
+    CoreMetrics,
+};
+use hyperlane_core::{HyperlaneDomain, HyperlaneMessage, TxOutcome, U256};
+
+/// Keeps running totals of the gas payments the relayer received and the gas
+/// it spent, per route and per sender, so gas payment enforcement policies
+/// can be tuned from real data.
+///
+/// Spending is recorded for every transaction outcome, including reverted
+/// attempts. The gas payment of a message is counted once it is confirmed to
+/// be delivered by this relayer; payments made after that are not counted.
+///
+/// Totals are persisted in the origin's db and survive restarts, the metrics
+/// only count what happened during the lifetime of this process. Senders are
+/// chosen by anyone dispatching messages, so only the persisted totals are
+/// broken down by sender; the metrics are per route.
+#[derive(Debug)]
+pub struct ProfitabilityTracker {
+    /// Databases by origin domain id
+    dbs: HashMap<u32, HyperlaneRocksDB>,
+    /// Domain names by id, used to label metrics
+    domain_names: HashMap<u32, String>,
+    /// The totals are read, updated and written back so updates must not
+    /// interleave.
+    update_lock: Mutex<()>,
+    /// Labels for all of the metrics:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain the message is destined for.
+    messages_delivered: IntCounterVec,
+    submissions: IntCounterVec,
+    gas_used: IntCounterVec,
+    tokens_received: CounterVec,
+    tokens_spent: CounterVec,
+}
+
+/// Profitability totals as reported by the admin API. Token amounts are in
+/// the smallest unit of the native token, e.g. wei.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfitabilitySummary {
+    origin: u32,
+    destination: u32,
+    /// None for the totals of the whole route
+    sender: Option<String>,
+    messages_delivered: u64,
+    submissions: u64,
+    tokens_received: String,
+    gas_paid_for: String,
+    gas_used: String,
+    tokens_spent: String,
+    /// Tokens received minus tokens spent, may be negative
+    net_tokens: String,
+}
+
+impl ProfitabilitySummary {
+    fn new(origin: u32, route: RouteProfitability) -> Self {
+        let totals = route.totals;
+        let net_tokens = if totals.tokens_received >= totals.tokens_spent {
+            (totals.tokens_received - totals.tokens_spent).to_string()
+        } else {
+            format!("-{}", totals.tokens_spent - totals.tokens_received)
+        };
+        Self {
+            origin,
+            destination: route.destination,
+            sender: route.sender.map(|s| format!("{s:?}")),
+            messages_delivered: totals.messages_delivered,
+            submissions: totals.submissions,
+            tokens_received: totals.tokens_received.to_string(),
+            gas_paid_for: totals.gas_paid_for.to_string(),
+            gas_used: totals.gas_used.to_string(),
+            tokens_spent: totals.tokens_spent.to_string(),
+            net_tokens,
+        }
+    }
+}
+
+impl ProfitabilityTracker {
+    pub fn new<'a>(
+        dbs: HashMap<u32, HyperlaneRocksDB>,
+        domains: impl IntoIterator<Item = &'a HyperlaneDomain>,
+        metrics: &CoreMetrics,
+    ) -> Self {
+        const LABELS: &[&str] = &["origin", "remote"];
+        let int_counter = |name: &str, help: &str| {
+            metrics
+                .new_int_counter(name, help, LABELS)
+                .unwrap_or_else(|_| panic!("failed to register {name} metric"))
+        };
+        let counter = |name: &str, help: &str| {
+            metrics
+                .new_counter(name, help, LABELS)
+                .unwrap_or_else(|_| panic!("failed to register {name} metric"))
+        };
+        Self {
+            dbs,
+            domain_names: domains
+                .into_iter()
+                .map(|d| (d.id(), d.name().to_owned()))
+                .collect(),
+            update_lock: Mutex::new(()),
+            messages_delivered: int_counter(
+                "profitability_messages_delivered",
+                "Number of messages delivered by the relayer",
+            ),
+            submissions: int_counter(
+                "profitability_submissions",
+                "Number of times a message was submitted, including reverted attempts",
+            ),
+            gas_used: int_counter(
+                "profitability_gas_used",
+                "Destination gas used by submitted messages",
+            ),
+            tokens_received: counter(
+                "profitability_tokens_received",
+                "Gas payments of delivered messages in the origin's native token",
+            ),
+            tokens_spent: counter(
+                "profitability_tokens_spent",
+                "Native tokens spent on the destination by submitted messages",
+            ),
+        }
+    }
+
+    /// Record the gas spent by a transaction which attempted to process a
+    /// message. `outcome.gas_price` must be the effective gas price of the
+    /// receipt.
+    pub fn record_submission(&self, message: &HyperlaneMessage, outcome: &TxOutcome) -> Result<()> {
+        let delta = ProfitabilityTotals {
+            submissions: 1,
+            gas_used: outcome.gas_used,
+            tokens_spent: outcome.gas_used.saturating_mul(outcome.gas_price),
+            ..Default::default()
+        };
+        self.record(message, &delta)?;
+
+        let names = self.label_values(message);
+        let labels: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.submissions.with_label_values(&labels).inc();
+        self.gas_used
+            .with_label_values(&labels)
+            .inc_by(outcome.gas_used.low_u64());
+        self.tokens_spent
+            .with_label_values(&labels)
+            .inc_by(as_native_tokens(delta.tokens_spent));
+        Ok(())
+    }
+
+    /// Record the delivery of a message by this relayer along with its gas
+    /// payment.
+    pub fn record_delivery(&self, message: &HyperlaneMessage) -> Result<()> {
+        let payment = self
+            .db(message)?
+            .retrieve_gas_payment_by_message_id(message.id())?;
+        let delta = ProfitabilityTotals {
+            messages_delivered: 1,
+            tokens_received: payment.payment,
+            gas_paid_for: payment.gas_amount,
+            ..Default::default()
+        };
+        self.record(message, &delta)?;
+
+        let names = self.label_values(message);
+        let labels: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.messages_delivered.with_label_values(&labels).inc();
+        self.tokens_received
+            .with_label_values(&labels)
+            .inc_by(as_native_tokens(payment.payment));
+        Ok(())
+    }
+
+    /// The persisted totals of every route and of every sender on them,
+    /// sorted by origin, destination and sender.
+    pub fn summary(&self) -> Result<Vec<ProfitabilitySummary>> {
+        let mut routes = Vec::new();
+        for (&origin, db) in &self.dbs {
+            routes.extend(
+                db.retrieve_profitability_totals()?
+                    .into_iter()
+                    .map(|route| (origin, route)),
+            );
+        }
+        routes.sort_by_key(|(origin, route)| (*origin, route.destination, route.sender));
+        Ok(routes
+            .into_iter()
+            .map(|(origin, route)| ProfitabilitySummary::new(origin, route))
+            .collect())
+    }
+
+    fn record(&self, message: &HyperlaneMessage, delta: &ProfitabilityTotals) -> Result<()> {
+        let db = self.db(message)?;
+        let _guard = self.update_lock.lock().unwrap();
+        db.add_to_profitability_totals(message.destination, message.sender, delta)?;
+        Ok(())
+    }
+
+    fn db(&self, message: &HyperlaneMessage) -> Result<&HyperlaneRocksDB> {
+        self.dbs
+            .get(&message.origin)
+            .ok_or_else(|| eyre!("No db for origin {}", message.origin))
+    }
+
+    fn label_values(&self, message: &HyperlaneMessage) -> [String; 2] {
+        let name = |domain: u32| {
+            self.domain_names
+                .get(&domain)
+                .cloned()
+                .unwrap_or_else(|| domain.to_string())
+        };
+        [name(message.origin), name(message.destination)]
+    }
+}
+
+/// Convert an amount in the smallest unit of a native token with 18 decimals
+/// to whole tokens.
+fn as_native_tokens(amount: U256) -> f64 {
+    amount.to_f64_lossy() / 1e18
+}
+
+#[cfg(test)]
+mod test {
+    use hyperlane_core::H256;
+
+    use super::*;
+
+    #[test]
+    fn test_summary_net_tokens() {
+        let route = |tokens_received: u64, tokens_spent: u64| RouteProfitability {
+            destination: 2,
+            sender: Some(H256::from_low_u64_be(1)),
+            totals: ProfitabilityTotals {
+                tokens_received: tokens_received.into(),
+                tokens_spent: tokens_spent.into(),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            ProfitabilitySummary::new(1, route(500, 200)).net_tokens,
+            "300"
+        );
+        assert_eq!(
+            ProfitabilitySummary::new(1, route(200, 500)).net_tokens,
+            "-300"
+        );
+        assert_eq!(ProfitabilitySummary::new(1, route(0, 0)).net_tokens, "0");
+    }
+}
@@ -1,5 +1,5 @@
 use std::cmp::Reverse;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,11 +17,27 @@ use tokio::time::sleep;
 use tracing::{debug, info_span, instrument, instrument::Instrumented, trace, Instrument};
 
 use hyperlane_base::CoreMetrics;
-use hyperlane_core::HyperlaneDomain;
+use hyperlane_core::{HyperlaneDomain, H256};
 
+use super::pending_message::{submit_batch, PendingMessage};
 use super::pending_operation::*;
+use super::priority::OperationSelector;
+use crate::settings::SubmissionPriorityConf;
 
-type OpQueue = Arc<Mutex<BinaryHeap<Reverse<Box<DynPendingOperation>>>>>;
+pub(crate) type OpQueue = Arc<Mutex<BinaryHeap<Reverse<Box<DynPendingOperation>>>>>;
+
+/// Handles to the queues of operations held by a SerialSubmitter.
+#[derive(Debug, Clone, Default)]
+pub struct SubmitterQueues {
+    /// Operations waiting to be prepared.
+    pub prepare: OpQueue,
+    /// Operations waiting to be confirmed.
+    pub confirm: OpQueue,
+    /// Ids of every operation the submitter holds, including those which are
+    /// not in a queue because they are being prepared, submitted or
+    /// confirmed.
+    pub held: Arc<Mutex<HashSet<H256>>>,
+}
 
 /// SerialSubmitter accepts operations over a channel. It is responsible for
 /// executing the right strategy to deliver those messages to the destination
@@ -78,6 +94,14 @@ pub struct SerialSubmitter {
     rx: mpsc::UnboundedReceiver<Box<DynPendingOperation>>,
     /// Metrics for serial submitter.
     metrics: SerialSubmitterMetrics,
+    /// Max number of prepared operations to submit together. Messages are
+    /// only delivered in a single transaction if the destination mailbox
+    /// supports batching.
+    max_batch_size: usize,
+    /// Queues of operations waiting to be prepared or confirmed.
+    queues: SubmitterQueues,
+    /// How to choose between operations which are ready to be prepared.
+    priority: SubmissionPriorityConf,
 }
 
 impl SerialSubmitter {
@@ -91,26 +115,36 @@ impl SerialSubmitter {
             domain,
             metrics,
             rx: rx_prepare,
+            max_batch_size,
+            queues:
+                SubmitterQueues {
+                    prepare: prepare_queue,
+                    confirm: confirm_queue,
+                    held,
+                },
+            priority,
         } = self;
-        let prepare_queue: OpQueue = Default::default();
-        let confirm_queue: OpQueue = Default::default();
 
         // This is a channel because we want to only have a small number of messages
         // sitting ready to go at a time and this acts as a synchronization tool
         // to slow down the preparation of messages when the submitter gets
-        // behind.
-        let (tx_submit, rx_submit) = mpsc::channel(1);
+        // behind. When batching, enough messages to fill a batch are kept ready.
+        let (tx_submit, rx_submit) = mpsc::channel(max_batch_size);
 
         let tasks = [
             spawn(receive_task(
                 domain.clone(),
                 rx_prepare,
                 prepare_queue.clone(),
+                confirm_queue.clone(),
+                held.clone(),
             )),
             spawn(prepare_task(
                 domain.clone(),
                 prepare_queue.clone(),
                 tx_submit,
+                held.clone(),
+                OperationSelector::new(priority),
                 metrics.clone(),
             )),
             spawn(submit_task(
@@ -118,12 +152,15 @@ impl SerialSubmitter {
                 rx_submit,
                 prepare_queue.clone(),
                 confirm_queue.clone(),
+                held.clone(),
+                max_batch_size,
                 metrics.clone(),
             )),
             spawn(confirm_task(
                 domain.clone(),
                 prepare_queue,
                 confirm_queue,
+                held,
                 metrics,
             )),
         ];
@@ -140,6 +177,8 @@ async fn receive_task(
     domain: HyperlaneDomain,
     mut rx: mpsc::UnboundedReceiver<Box<DynPendingOperation>>,
     prepare_queue: OpQueue,
+    confirm_queue: OpQueue,
+    held: Arc<Mutex<HashSet<H256>>>,
 ) -> Result<()> {
     // Pull any messages sent to this submitter
     while let Some(op) = rx.recv().await {
@@ -147,7 +186,14 @@ async fn receive_task(
         // make sure things are getting wired up correctly; if this works in testing it
         // should also be valid in production.
         debug_assert_eq!(*op.domain(), domain);
-        prepare_queue.lock().await.push(Reverse(op));
+        held.lock().await.insert(op.id());
+        // Operations restored from a previous run may already have been
+        // submitted, in which case they only need to be confirmed.
+        if op.is_submitted() {
+            confirm_queue.lock().await.push(Reverse(op));
+        } else {
+            prepare_queue.lock().await.push(Reverse(op));
+        }
     }
     bail!("Submitter receive channel was closed")
 }
@@ -157,6 +203,8 @@ async fn prepare_task(
     domain: HyperlaneDomain,
     prepare_queue: OpQueue,
     tx_submit: mpsc::Sender<Box<DynPendingOperation>>,
+    held: Arc<Mutex<HashSet<H256>>>,
+    mut selector: OperationSelector,
     metrics: SerialSubmitterMetrics,
 ) -> Result<()> {
     loop {
@@ -164,9 +212,9 @@ async fn prepare_task(
         let next = {
             let mut queue = prepare_queue.lock().await;
             metrics.prepare_queue_length.set(queue.len() as i64);
-            queue.pop()
+            selector.pop(&mut queue)
         };
-        let Some(Reverse(mut op)) = next else {
+        let Some(mut op) = next else {
             // queue is empty so give some time before checking again to prevent burning CPU
             sleep(Duration::from_millis(200)).await;
             continue;
@@ -192,6 +240,7 @@ async fn prepare_task(
             }
             PendingOperationResult::Drop => {
                 metrics.ops_dropped.inc();
+                held.lock().await.remove(&op.id());
             }
             PendingOperationResult::CriticalFailure(e) => {
                 return Err(e);
@@ -206,29 +255,54 @@ async fn submit_task(
     mut rx_submit: mpsc::Receiver<Box<DynPendingOperation>>,
     prepare_queue: OpQueue,
     confirm_queue: OpQueue,
+    held: Arc<Mutex<HashSet<H256>>>,
+    max_batch_size: usize,
     metrics: SerialSubmitterMetrics,
 ) -> Result<()> {
-    while let Some(mut op) = rx_submit.recv().await {
-        trace!(?op, "Submitting operation");
-        debug_assert_eq!(*op.domain(), domain);
+    while let Some(op) = rx_submit.recv().await {
+        let mut batch = vec![op];
+        // Pick up any other operations which are already prepared so they can
+        // be delivered together.
+        while batch.len() < max_batch_size {
+            let Ok(op) = rx_submit.try_recv() else { break };
+            batch.push(op);
+        }
+        trace!(?batch, "Submitting operations");
+        debug_assert!(batch.iter().all(|op| *op.domain() == domain));
 
-        match op.submit().await {
-            PendingOperationResult::Success => {
-                debug!(?op, "Operation submitted");
-                metrics.ops_submitted.inc();
-                confirm_queue.lock().await.push(Reverse(op));
-            }
-            PendingOperationResult::NotReady => {
-                panic!("Pending operation was prepared and therefore must be ready")
-            }
-            PendingOperationResult::Reprepare => {
-                metrics.ops_failed.inc();
-                prepare_queue.lock().await.push(Reverse(op));
-            }
-            PendingOperationResult::Drop => {
-                metrics.ops_dropped.inc();
+        let results = if let [op] = &mut batch[..] {
+            vec![op.submit().await]
+        } else {
+            let mut messages: Vec<&mut PendingMessage> = batch
+                .iter_mut()
+                .map(|op| {
+                    let DynPendingOperation::PendingMessage(pm) = op.as_mut();
+                    pm
+                })
+                .collect();
+            submit_batch(&mut messages).await
+        };
+
+        for (op, result) in batch.into_iter().zip(results) {
+            match result {
+                PendingOperationResult::Success => {
+                    debug!(?op, "Operation submitted");
+                    metrics.ops_submitted.inc();
+                    confirm_queue.lock().await.push(Reverse(op));
+                }
+                PendingOperationResult::NotReady => {
+                    panic!("Pending operation was prepared and therefore must be ready")
+                }
+                PendingOperationResult::Reprepare => {
+                    metrics.ops_failed.inc();
+                    prepare_queue.lock().await.push(Reverse(op));
+                }
+                PendingOperationResult::Drop => {
+                    metrics.ops_dropped.inc();
+                    held.lock().await.remove(&op.id());
+                }
+                PendingOperationResult::CriticalFailure(e) => return Err(e),
             }
-            PendingOperationResult::CriticalFailure(e) => return Err(e),
         }
     }
     bail!("Internal submitter channel was closed");
@@ -239,6 +313,7 @@ async fn confirm_task(
     domain: HyperlaneDomain,
     prepare_queue: OpQueue,
     confirm_queue: OpQueue,
+    held: Arc<Mutex<HashSet<H256>>>,
     metrics: SerialSubmitterMetrics,
 ) -> Result<()> {
     loop {
@@ -259,6 +334,7 @@ async fn confirm_task(
             PendingOperationResult::Success => {
                 debug!(?op, "Operation confirmed");
                 metrics.ops_confirmed.inc();
+                held.lock().await.remove(&op.id());
             }
             PendingOperationResult::NotReady => {
                 // none of the operations are ready yet, so wait for a little bit
@@ -271,6 +347,7 @@ async fn confirm_task(
             }
             PendingOperationResult::Drop => {
                 metrics.ops_dropped.inc();
+                held.lock().await.remove(&op.id());
             }
             PendingOperationResult::CriticalFailure(e) => return Err(e),
         }
@@ -3,6 +3,7 @@
 //! Struct responsible for syncing Prover
 
 use hyperlane_core::accumulator::{
+    incremental::IncrementalMerkle,
     merkle::{merkle_root_from_branch, MerkleTree, MerkleTreeError, Proof},
     TREE_DEPTH,
 };
@@ -14,6 +15,9 @@ use tracing::{error, instrument};
 #[derive(Debug)]
 pub struct Prover {
     count: usize,
+    /// Number of leaves at the start of the tree which were pruned, they
+    /// can't be proven.
+    pruned: usize,
     tree: MerkleTree,
 }
 
@@ -31,6 +35,16 @@ pub enum ProverError {
         /// The number of leaves
         count: usize,
     },
+    /// Requested proof for a leaf which was pruned from the tree
+    #[error(
+        "Requested proof for a pruned leaf. Requested: {index}. Tree pruned the first {pruned}"
+    )]
+    PrunedLeaf {
+        /// The index requested
+        index: usize,
+        /// The number of pruned leaves
+        pruned: usize,
+    },
     /// Bubbled up from underlying
     #[error(transparent)]
     MerkleTreeError(#[from] MerkleTreeError),
@@ -50,12 +64,24 @@ impl Default for Prover {
         let full = MerkleTree::create(&[], TREE_DEPTH);
         Self {
             count: 0,
+            pruned: 0,
             tree: full,
         }
     }
 }
 
 impl Prover {
+    /// Create a prover which continues from an incremental merkle tree. The
+    /// leaves which were ingested into it are pruned.
+    pub fn from_incremental(incremental: &IncrementalMerkle) -> Self {
+        let count = incremental.count();
+        Self {
+            count,
+            pruned: count,
+            tree: MerkleTree::from_frontier(incremental.branch(), count, TREE_DEPTH),
+        }
+    }
+
     /// Push a leaf to the tree. Appends it to the first unoccupied slot
     ///
     /// This will fail if the underlying tree is full.
@@ -75,6 +101,12 @@ impl Prover {
         self.count
     }
 
+    /// Return the number of leaves at the start of the tree which can't be
+    /// proven
+    pub fn pruned(&self) -> usize {
+        self.pruned
+    }
+
     /// Create a proof of a leaf in this tree.
     #[instrument(err, skip(self), fields(prover_msg_count=self.count()))]
     pub fn prove_against_previous(
@@ -92,6 +124,12 @@ impl Prover {
                 count,
             });
         }
+        if leaf_index < self.pruned {
+            return Err(ProverError::PrunedLeaf {
+                index: leaf_index,
+                pruned: self.pruned,
+            });
+        }
         Ok(self.tree.prove_against_previous(leaf_index, root_index))
     }
 
@@ -116,6 +154,7 @@ where
         let slice = t.as_ref();
         Self {
             count: slice.len(),
+            pruned: 0,
             tree: MerkleTree::create(slice, TREE_DEPTH),
         }
     }
@@ -177,4 +216,37 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn it_proves_leaves_after_the_incremental_tree() {
+        let leaves: Vec<H256> = (0..20u64).map(H256::from_low_u64_be).collect();
+        let full: Prover = leaves.iter().copied().collect();
+        let mut incremental = IncrementalMerkle::default();
+        leaves[..13]
+            .iter()
+            .for_each(|leaf| incremental.ingest(*leaf));
+
+        let mut resumed = Prover::from_incremental(&incremental);
+        resumed.extend(leaves[13..].iter().copied());
+        assert_eq!(resumed.count(), full.count());
+        assert_eq!(resumed.root(), full.root());
+
+        for leaf_index in 13..20 {
+            for root_index in leaf_index..20 {
+                assert_eq!(
+                    resumed
+                        .prove_against_previous(leaf_index, root_index)
+                        .unwrap(),
+                    full.prove_against_previous(leaf_index, root_index).unwrap()
+                );
+            }
+        }
+        assert!(matches!(
+            resumed.prove_against_previous(12, 19),
+            Err(ProverError::PrunedLeaf {
+                index: 12,
+                pruned: 13
+            })
+        ));
+    }
 }
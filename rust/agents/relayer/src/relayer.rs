@@ -1,7 +1,7 @@
 use std::fmt::{Debug, Formatter};
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{atomic::AtomicU32, Arc},
 };
 
 use async_trait::async_trait;
@@ -15,7 +15,7 @@ use tokio::{
     },
     task::JoinHandle,
 };
-use tracing::{info, info_span, instrument::Instrumented, Instrument};
+use tracing::{info, info_span, instrument::Instrumented, warn, Instrument};
 
 use hyperlane_base::{
     db::{HyperlaneRocksDB, DB},
@@ -25,22 +25,29 @@ use hyperlane_core::{HyperlaneDomain, InterchainGasPayment, U256};
 
 use crate::msg::pending_message::MessageSubmissionMetrics;
 use crate::{
+    admin::{run_admin_server, AdminState},
     merkle_tree_builder::MerkleTreeBuilder,
     msg::{
+        dry_run::DryRunReporter,
         gas_payment::GasPaymentEnforcer,
-        metadata::BaseMetadataBuilder,
+        metadata::{BaseMetadataBuilder, IsmCache},
+        one_shot::{relay_message, OriginChain},
         pending_message::MessageContext,
         pending_operation::DynPendingOperation,
         processor::{MessageProcessor, MessageProcessorMetrics},
-        serial_submitter::{SerialSubmitter, SerialSubmitterMetrics},
+        profitability::ProfitabilityTracker,
+        serial_submitter::{SerialSubmitter, SerialSubmitterMetrics, SubmitterQueues},
+    },
+    settings::{
+        matching_list::MatchingList, AdminConf, RelayMessageConf, RelayerSettings,
+        SubmissionPriorityConf,
     },
-    settings::{matching_list::MatchingList, RelayerSettings},
 };
 
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
-struct ContextKey {
-    origin: u32,
-    destination: u32,
+pub(crate) struct ContextKey {
+    pub origin: u32,
+    pub destination: u32,
 }
 
 /// A relayer agent
@@ -61,20 +68,32 @@ pub struct Relayer {
     transaction_gas_limit: Option<U256>,
     skip_transaction_gas_limit_for: HashSet<u32>,
     allow_local_checkpoint_syncers: bool,
+    max_batch_size: u32,
+    admin: Option<AdminConf>,
+    ism_cache: Arc<IsmCache>,
+    dry_run: Option<Arc<DryRunReporter>>,
+    profitability: Arc<ProfitabilityTracker>,
+    relay_message: Option<RelayMessageConf>,
+    submission_priority: SubmissionPriorityConf,
 }
 
 impl Debug for Relayer {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Relayer {{ origin_chains: {:?}, destination_chains: {:?}, whitelist: {:?}, blacklist: {:?}, transaction_gas_limit: {:?}, skip_transaction_gas_limit_for: {:?}, allow_local_checkpoint_syncers: {:?} }}",
+            "Relayer {{ origin_chains: {:?}, destination_chains: {:?}, whitelist: {:?}, blacklist: {:?}, transaction_gas_limit: {:?}, skip_transaction_gas_limit_for: {:?}, allow_local_checkpoint_syncers: {:?}, max_batch_size: {}, admin: {:?}, dry_run: {}, relay_message: {:?}, submission_priority: {:?} }}",
             self.origin_chains,
             self.destination_chains,
             self.whitelist,
             self.blacklist,
             self.transaction_gas_limit,
             self.skip_transaction_gas_limit_for,
-            self.allow_local_checkpoint_syncers
+            self.allow_local_checkpoint_syncers,
+            self.max_batch_size,
+            self.admin,
+            self.dry_run.is_some(),
+            self.relay_message,
+            self.submission_priority
         )
     }
 }
@@ -110,6 +129,9 @@ impl BaseAgent for Relayer {
         let validator_announces = settings
             .build_validator_announces(settings.origin_chains.iter(), &metrics)
             .await?;
+        let origin_providers = settings
+            .build_providers(settings.origin_chains.iter(), &metrics)
+            .await?;
 
         let contract_sync_metrics = Arc::new(ContractSyncMetrics::new(&metrics));
 
@@ -153,12 +175,12 @@ impl BaseAgent for Relayer {
             .iter()
             .map(|origin| {
                 let db = dbs.get(origin).unwrap().clone();
-                (
+                Ok((
                     origin.clone(),
-                    Arc::new(RwLock::new(MerkleTreeBuilder::new(db))),
-                )
+                    Arc::new(RwLock::new(MerkleTreeBuilder::new(db)?)),
+                ))
             })
-            .collect::<HashMap<_, _>>();
+            .collect::<Result<HashMap<_, _>>>()?;
 
         info!(gas_enforcement_policies=?settings.gas_payment_enforcement, "Gas enforcement configuration");
 
@@ -168,15 +190,36 @@ impl BaseAgent for Relayer {
             .origin_chains
             .iter()
             .map(|domain| {
-                (
+                Ok((
                     domain.clone(),
                     Arc::new(GasPaymentEnforcer::new(
                         settings.gas_payment_enforcement.clone(),
                         dbs.get(domain).unwrap().clone(),
-                    )),
-                )
+                    )?),
+                ))
             })
-            .collect();
+            .collect::<Result<_>>()?;
+
+        let ism_cache = Arc::new(IsmCache::new(settings.ism_cache_ttl, &metrics));
+
+        let dry_run = settings.dry_run_report.map(|path| {
+            warn!(
+                ?path,
+                "Running in dry-run mode, messages will not be submitted"
+            );
+            Arc::new(DryRunReporter::new(path, &metrics))
+        });
+
+        let profitability = Arc::new(ProfitabilityTracker::new(
+            dbs.iter()
+                .map(|(origin, db)| (origin.id(), db.clone()))
+                .collect(),
+            settings
+                .origin_chains
+                .iter()
+                .chain(&settings.destination_chains),
+            &metrics,
+        ));
 
         let mut msg_ctxs = HashMap::new();
         for destination in &settings.destination_chains {
@@ -196,6 +239,7 @@ impl BaseAgent for Relayer {
                     validator_announces[origin].clone(),
                     settings.allow_local_checkpoint_syncers,
                     core.metrics.clone(),
+                    ism_cache.clone(),
                     5,
                 );
 
@@ -207,9 +251,15 @@ impl BaseAgent for Relayer {
                     Arc::new(MessageContext {
                         destination_mailbox: mailboxes[destination].clone(),
                         origin_db: dbs.get(origin).unwrap().clone(),
+                        origin_provider: origin_providers[origin].clone(),
                         metadata_builder,
                         origin_gas_payment_enforcer: gas_payment_enforcers[origin].clone(),
                         transaction_gas_limit,
+                        destination_finality_blocks: destination_chain_setup.finality_blocks,
+                        max_retries: settings.max_message_retries,
+                        retry_policies: settings.retry_policies.clone(),
+                        dry_run: dry_run.clone(),
+                        profitability: profitability.clone(),
                         metrics: MessageSubmissionMetrics::new(&metrics, origin, destination),
                     }),
                 );
@@ -230,21 +280,37 @@ impl BaseAgent for Relayer {
             transaction_gas_limit,
             skip_transaction_gas_limit_for,
             allow_local_checkpoint_syncers: settings.allow_local_checkpoint_syncers,
+            max_batch_size: settings.max_batch_size,
+            admin: settings.admin,
+            ism_cache,
+            dry_run,
+            profitability,
+            relay_message: settings.relay_message,
+            submission_priority: settings.submission_priority,
         })
     }
 
     #[allow(clippy::async_yields_async)]
     async fn run(self) -> Instrumented<JoinHandle<Result<()>>> {
+        if let Some(conf) = &self.relay_message {
+            return self.run_relay_message(conf.clone());
+        }
+
         let mut tasks = vec![];
 
         // send channels by destination chain
         let mut send_channels = HashMap::with_capacity(self.destination_chains.len());
+        // submitter queues by destination chain
+        let mut submitter_queues = HashMap::with_capacity(self.destination_chains.len());
         for destination in &self.destination_chains {
             let (send_channel, receive_channel) =
                 mpsc::unbounded_channel::<Box<DynPendingOperation>>();
             send_channels.insert(destination.id(), send_channel);
 
-            tasks.push(self.run_destination_submitter(destination, receive_channel));
+            let queues = SubmitterQueues::default();
+            submitter_queues.insert(destination.id(), (destination.clone(), queues.clone()));
+
+            tasks.push(self.run_destination_submitter(destination, receive_channel, queues));
         }
 
         for origin in &self.origin_chains {
@@ -253,8 +319,37 @@ impl BaseAgent for Relayer {
         }
 
         // each message process attempts to send messages from a chain
+        let mut processor_nonces = HashMap::with_capacity(self.origin_chains.len());
         for origin in &self.origin_chains {
-            tasks.push(self.run_message_processor(origin, send_channels.clone()));
+            let processor_nonce = Arc::new(AtomicU32::new(0));
+            processor_nonces.insert(origin.id(), processor_nonce.clone());
+            tasks.push(self.run_message_processor(origin, send_channels.clone(), processor_nonce));
+        }
+
+        if let Some(dry_run) = &self.dry_run {
+            tasks.push(dry_run.clone().spawn());
+        }
+
+        if let Some(admin) = &self.admin {
+            let state = AdminState {
+                queues: submitter_queues,
+                send_channels,
+                msg_ctxs: self.msg_ctxs.clone(),
+                dbs: self
+                    .dbs
+                    .iter()
+                    .map(|(origin, db)| (origin.id(), db.clone()))
+                    .collect(),
+                prover_syncs: self
+                    .prover_syncs
+                    .iter()
+                    .map(|(origin, prover_sync)| (origin.id(), prover_sync.clone()))
+                    .collect(),
+                processor_nonces,
+                ism_cache: self.ism_cache.clone(),
+                profitability: self.profitability.clone(),
+            };
+            tasks.push(run_admin_server(admin.clone(), state));
         }
 
         run_all(tasks)
@@ -295,10 +390,37 @@ impl Relayer {
             .instrument(info_span!("ContractSync"))
     }
 
+    /// Deliver a single message without running any of the syncing or
+    /// processing loops.
+    fn run_relay_message(&self, conf: RelayMessageConf) -> Instrumented<JoinHandle<Result<()>>> {
+        let origin = conf.origin.clone();
+        let db = self.dbs[&origin].clone();
+        let prover_sync = self.prover_syncs[&origin].clone();
+        let destination_ctxs = self
+            .msg_ctxs
+            .iter()
+            .filter(|(key, _)| key.origin == origin.id())
+            .map(|(key, ctx)| (key.destination, ctx.clone()))
+            .collect();
+        let chain_setup = self.core.settings.chain_setup(&origin).cloned();
+        let metrics = self.core.metrics.clone();
+        tokio::spawn(async move {
+            let chain_setup = chain_setup?;
+            let origin_chain = OriginChain {
+                mailbox: chain_setup.build_mailbox(&metrics).await?,
+                indexer: chain_setup.build_message_indexer(&metrics).await?,
+                index: chain_setup.index.clone(),
+            };
+            relay_message(conf, origin_chain, db, prover_sync, destination_ctxs).await
+        })
+        .instrument(info_span!("RelayMessage", origin=%origin))
+    }
+
     fn run_message_processor(
         &self,
         origin: &HyperlaneDomain,
         send_channels: HashMap<u32, UnboundedSender<Box<DynPendingOperation>>>,
+        processor_nonce: Arc<AtomicU32>,
     ) -> Instrumented<JoinHandle<Result<()>>> {
         let metrics = MessageProcessorMetrics::new(
             &self.core.metrics,
@@ -328,6 +450,9 @@ impl Relayer {
             self.prover_syncs[origin].clone(),
             send_channels,
             destination_ctxs,
+            self.core.settings.body_decoders.clone(),
+            self.dry_run.is_some(),
+            processor_nonce,
         );
 
         let span = info_span!("MessageProcessor", origin=%message_processor.domain());
@@ -341,16 +466,20 @@ impl Relayer {
     }
 
     #[allow(clippy::too_many_arguments)]
-    #[tracing::instrument(skip(self, receiver))]
+    #[tracing::instrument(skip(self, receiver, queues))]
     fn run_destination_submitter(
         &self,
         destination: &HyperlaneDomain,
         receiver: UnboundedReceiver<Box<DynPendingOperation>>,
+        queues: SubmitterQueues,
     ) -> Instrumented<JoinHandle<Result<()>>> {
         let serial_submitter = SerialSubmitter::new(
             destination.clone(),
             receiver,
             SerialSubmitterMetrics::new(&self.core.metrics, destination),
+            self.max_batch_size as usize,
+            queues,
+            self.submission_priority,
         );
         let span = info_span!("SerialSubmitter", destination=%destination);
         let submit_fut = serial_submitter.spawn();
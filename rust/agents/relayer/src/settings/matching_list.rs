@@ -4,6 +4,7 @@ use std::marker::PhantomData;
 
 use eyre::{Report, Result};
 
+use regex::Regex;
 use serde::de::{Error, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 
@@ -18,6 +19,11 @@ use hyperlane_core::{HyperlaneMessage, H160, H256};
 /// - wildcard "*"
 /// - single value in decimal or hex (must start with `0x`) format
 /// - list of values in decimal or hex format
+///
+/// Rules may additionally filter on the message body, see [MatchItem]. Rules
+/// with `exclude` set remove messages from the list: a message matches the
+/// list if it matches no exclusion rule and matches any other rule, or if
+/// there are only exclusion rules.
 #[derive(Debug, Deserialize, Default, Clone, PartialEq)]
 #[serde(transparent)]
 pub struct MatchingList(pub Option<Vec<MatchItem>>);
@@ -45,11 +51,14 @@ impl MatchingList {
     /// Check if a message matches any of the rules.
     /// - `default`: What to return if the the matching list is empty.
     fn matches(&self, info: MatchInfo, default: bool) -> bool {
-        if let Some(rules) = &self.0 {
-            matches_any_rule(rules.iter(), info)
-        } else {
-            default
+        let Some(rules) = &self.0 else {
+            return default;
+        };
+        let (exclusions, inclusions): (Vec<_>, Vec<_>) = rules.iter().partition(|r| r.exclude);
+        if matches_any_rule(exclusions.into_iter(), info) {
+            return false;
         }
+        inclusions.is_empty() || matches_any_rule(inclusions.into_iter(), info)
     }
 }
 
@@ -203,6 +212,39 @@ impl<'de> Visitor<'de> for FilterVisitor<H256> {
     }
 }
 
+impl<'de> Visitor<'de> for FilterVisitor<[u8; 4]> {
+    type Value = Filter<[u8; 4]>;
+
+    fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Expecting either a wildcard \"*\", 4-byte hex selector string, or list of 4-byte hex selector strings"
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(if v == "*" {
+            Self::Value::Wildcard
+        } else {
+            Self::Value::Enumerated(vec![parse_selector(v)?])
+        })
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(i) = seq.next_element::<&str>()? {
+            values.push(parse_selector(i)?)
+        }
+        Ok(Self::Value::Enumerated(values))
+    }
+}
+
 impl<'de> Deserialize<'de> for Filter<u32> {
     fn deserialize<D>(d: D) -> Result<Self, D::Error>
     where
@@ -221,7 +263,51 @@ impl<'de> Deserialize<'de> for Filter<H256> {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+impl<'de> Deserialize<'de> for Filter<[u8; 4]> {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        d.deserialize_any(FilterVisitor::<[u8; 4]>(Default::default()))
+    }
+}
+
+/// Bytes given as a hex string in the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl<'de> Deserialize<'de> for HexBytes {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <&str>::deserialize(d)?;
+        parse_hex(s).map(Self)
+    }
+}
+
+/// A regex matched against the lowercase hex encoding of a message body,
+/// without a `0x` prefix.
+#[derive(Debug, Clone)]
+pub struct BodyRegex(pub Regex);
+
+impl PartialEq for BodyRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl<'de> Deserialize<'de> for BodyRegex {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <&str>::deserialize(d)?;
+        Regex::new(s).map(Self).map_err(to_serde_err)
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
 #[serde(tag = "type")]
 pub struct MatchItem {
     #[serde(default, rename = "originDomain")]
@@ -235,6 +321,26 @@ pub struct MatchItem {
 
     #[serde(default, rename = "recipientAddress")]
     pub recipient_address: Filter<H256>,
+
+    /// 4-byte function selectors the message body may start with.
+    #[serde(default, rename = "bodySelector")]
+    pub body_selector: Filter<[u8; 4]>,
+
+    /// Bytes the message body must start with.
+    #[serde(default, rename = "bodyPrefix")]
+    pub body_prefix: Option<HexBytes>,
+
+    /// Regex the hex encoded message body must match.
+    #[serde(default, rename = "bodyRegex")]
+    pub body_regex: Option<BodyRegex>,
+
+    /// Maximum length of the message body in bytes.
+    #[serde(default, rename = "maxBodyLength")]
+    pub max_body_length: Option<usize>,
+
+    /// If true, messages matching this rule are excluded from the list.
+    #[serde(default)]
+    pub exclude: bool,
 }
 
 impl MatchItem {
@@ -252,6 +358,7 @@ impl MatchItem {
             sender_address: Filter::<H256>::from_csv(item[1])?,
             destination_domain: Filter::<u32>::from_csv(item[2])?,
             recipient_address: Filter::<H256>::from_csv(item[3])?,
+            ..Default::default()
         })
     }
 }
@@ -260,12 +367,32 @@ impl Display for MatchItem {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{{originDomain: {}, senderAddress: {}, destinationDomain: {}, recipientAddress: {}}}",
+            "{{originDomain: {}, senderAddress: {}, destinationDomain: {}, recipientAddress: {}",
             self.origin_domain,
             self.sender_address,
             self.destination_domain,
             self.recipient_address
-        )
+        )?;
+        if let Filter::Enumerated(selectors) = &self.body_selector {
+            let selectors: Vec<_> = selectors
+                .iter()
+                .map(|s| format!("0x{}", hex::encode(s)))
+                .collect();
+            write!(f, ", bodySelector: [{}]", selectors.join(","))?;
+        }
+        if let Some(prefix) = &self.body_prefix {
+            write!(f, ", bodyPrefix: 0x{}", hex::encode(&prefix.0))?;
+        }
+        if let Some(regex) = &self.body_regex {
+            write!(f, ", bodyRegex: {}", regex.0)?;
+        }
+        if let Some(max_len) = self.max_body_length {
+            write!(f, ", maxBodyLength: {max_len}")?;
+        }
+        if self.exclude {
+            write!(f, ", exclude: true")?;
+        }
+        write!(f, "}}")
     }
 }
 
@@ -275,6 +402,7 @@ struct MatchInfo<'a> {
     src_addr: &'a H256,
     dst_domain: u32,
     dst_addr: &'a H256,
+    body: &'a [u8],
 }
 
 impl<'a> From<&'a HyperlaneMessage> for MatchInfo<'a> {
@@ -284,6 +412,7 @@ impl<'a> From<&'a HyperlaneMessage> for MatchInfo<'a> {
             src_addr: &msg.sender,
             dst_domain: msg.destination,
             dst_addr: &msg.recipient,
+            body: &msg.body,
         }
     }
 }
@@ -294,9 +423,32 @@ fn matches_any_rule<'a>(mut rules: impl Iterator<Item = &'a MatchItem>, info: Ma
             && rule.sender_address.matches(info.src_addr)
             && rule.destination_domain.matches(&info.dst_domain)
             && rule.recipient_address.matches(info.dst_addr)
+            && body_matches(rule, info.body)
     })
 }
 
+fn body_matches(rule: &MatchItem, body: &[u8]) -> bool {
+    let selector_matches = match &rule.body_selector {
+        Filter::Wildcard => true,
+        Filter::Enumerated(_) => body
+            .get(..4)
+            .map(|s| rule.body_selector.matches(&s.try_into().unwrap()))
+            .unwrap_or(false),
+    };
+    selector_matches
+        && rule
+            .body_prefix
+            .as_ref()
+            .map_or(true, |prefix| body.starts_with(&prefix.0))
+        && rule
+            .max_body_length
+            .map_or(true, |max_len| body.len() <= max_len)
+        && rule
+            .body_regex
+            .as_ref()
+            .map_or(true, |regex| regex.0.is_match(&hex::encode(body)))
+}
+
 impl Display for MatchingList {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(wl) = &self.0 {
@@ -324,6 +476,16 @@ fn parse_addr<E: Error>(addr_str: &str) -> Result<H256, E> {
     .map_err(to_serde_err)
 }
 
+fn parse_hex<E: Error>(hex_str: &str) -> Result<Vec<u8>, E> {
+    hex::decode(hex_str.trim_start_matches("0x")).map_err(to_serde_err)
+}
+
+fn parse_selector<E: Error>(selector_str: &str) -> Result<[u8; 4], E> {
+    parse_hex(selector_str)?
+        .try_into()
+        .map_err(|_| E::custom(format!("Selector must be 4 bytes: '{selector_str}'")))
+}
+
 fn csv_to_u32_vec(csv: &str) -> Result<Vec<u32>> {
     let csv = csv.trim();
 
@@ -360,7 +522,7 @@ pub fn csv_to_h160_vec(csv: &str) -> Result<Vec<H160>> {
 
 #[cfg(test)]
 mod test {
-    use hyperlane_core::{H160, H256};
+    use hyperlane_core::{HyperlaneMessage, H160, H256};
 
     use crate::settings::matching_list::MatchInfo;
 
@@ -388,7 +550,8 @@ mod test {
                 src_domain: 0,
                 src_addr: &H256::default(),
                 dst_domain: 0,
-                dst_addr: &H256::default()
+                dst_addr: &H256::default(),
+                body: &[],
             },
             false
         ));
@@ -401,7 +564,8 @@ mod test {
                     .unwrap()
                     .into(),
                 dst_domain: 5456,
-                dst_addr: &H256::default()
+                dst_addr: &H256::default(),
+                body: &[],
             },
             false
         ))
@@ -441,7 +605,8 @@ mod test {
                 dst_addr: &"9d4454B023096f34B160D6B654540c56A1F81688"
                     .parse::<H160>()
                     .unwrap()
-                    .into()
+                    .into(),
+                body: &[],
             },
             false
         ));
@@ -454,7 +619,8 @@ mod test {
                     .unwrap()
                     .into(),
                 dst_domain: 5456,
-                dst_addr: &H256::default()
+                dst_addr: &H256::default(),
+                body: &[],
             },
             false
         ));
@@ -480,10 +646,85 @@ mod test {
             src_addr: &H256::default(),
             dst_domain: 0,
             dst_addr: &H256::default(),
+            body: &[],
         };
         // whitelist use
         assert!(MatchingList(None).matches(info, true));
         // blacklist use
         assert!(!MatchingList(None).matches(info, false));
     }
+
+    fn message_with_body(body: &str) -> HyperlaneMessage {
+        HyperlaneMessage {
+            body: hex::decode(body).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn config_with_body_rules() {
+        let list: MatchingList = serde_json::from_str(
+            r#"[{"bodySelector": ["0xa9059cbb", "095ea7b3"], "maxBodyLength": 8}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            list.0.as_ref().unwrap()[0].body_selector,
+            Enumerated(vec![[0xa9, 0x05, 0x9c, 0xbb], [0x09, 0x5e, 0xa7, 0xb3]])
+        );
+        assert!(list.msg_matches(&message_with_body("a9059cbb0000"), false));
+        assert!(list.msg_matches(&message_with_body("095ea7b3"), false));
+        assert!(!list.msg_matches(&message_with_body("deadbeef"), false));
+        assert!(!list.msg_matches(&message_with_body("a905"), false));
+        assert!(!list.msg_matches(&message_with_body("a9059cbb0000000000"), false));
+        assert!(list
+            .to_string()
+            .contains("bodySelector: [0xa9059cbb,0x095ea7b3]"));
+
+        let list: MatchingList =
+            serde_json::from_str(r#"[{"bodyPrefix": "0x0102"}, {"bodyRegex": "^ff.*ee$"}]"#)
+                .unwrap();
+        assert!(list.msg_matches(&message_with_body("010203"), false));
+        assert!(list.msg_matches(&message_with_body("ff00ee"), false));
+        assert!(!list.msg_matches(&message_with_body("0203"), false));
+        assert!(!list.msg_matches(&message_with_body("ff00"), false));
+    }
+
+    #[test]
+    fn invalid_body_selector() {
+        assert!(serde_json::from_str::<MatchingList>(r#"[{"bodySelector": "0xa9059c"}]"#).is_err());
+    }
+
+    #[test]
+    fn exclusion_rules() {
+        let sender = "0x9d4454B023096f34B160D6B654540c56A1F81688";
+        let list: MatchingList = serde_json::from_str(&format!(
+            r#"[{{"destinationDomain": 1}}, {{"senderAddress": "{sender}", "exclude": true}}]"#
+        ))
+        .unwrap();
+        let message = HyperlaneMessage {
+            destination: 1,
+            ..Default::default()
+        };
+        let excluded = HyperlaneMessage {
+            sender: sender.parse::<H160>().unwrap().into(),
+            ..message.clone()
+        };
+        assert!(list.msg_matches(&message, false));
+        assert!(!list.msg_matches(&excluded, false));
+        assert!(!list.msg_matches(
+            &HyperlaneMessage {
+                destination: 2,
+                ..message.clone()
+            },
+            false
+        ));
+
+        // A list of only exclusions matches everything else
+        let list: MatchingList = serde_json::from_str(&format!(
+            r#"[{{"senderAddress": "{sender}", "exclude": true}}]"#
+        ))
+        .unwrap();
+        assert!(list.msg_matches(&message, false));
+        assert!(!list.msg_matches(&excluded, false));
+    }
 }
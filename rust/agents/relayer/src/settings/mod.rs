@@ -1,7 +1,10 @@
 //! Configuration
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use eyre::{eyre, Context};
 use serde::Deserialize;
@@ -9,11 +12,13 @@ use tracing::warn;
 
 use hyperlane_base::{decl_settings, Settings};
 use hyperlane_core::config::*;
-use hyperlane_core::{HyperlaneDomain, U256};
+use hyperlane_core::{HyperlaneDomain, H256, U256};
 
 pub use crate::settings::matching_list::MatchingList;
+pub use crate::settings::retry_policy::{RetryPolicy, RetryPolicyConf};
 
 pub mod matching_list;
+pub mod retry_policy;
 
 /// Config for a GasPaymentEnforcementPolicy
 #[derive(Debug, Clone, Default)]
@@ -29,6 +34,106 @@ pub enum GasPaymentEnforcementPolicy {
         gas_fraction_numerator: u64,
         gas_fraction_denominator: u64,
     },
+    /// The payment on the origin chain covers the estimated cost of delivery
+    /// on the destination chain plus a margin, with both converted to USD.
+    UsdCost {
+        margin_percent: u32,
+        price_source: TokenPriceSourceConf,
+    },
+}
+
+/// The USD price of a chain's native token.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct TokenPrice {
+    /// USD price of one whole token.
+    pub usd: f64,
+    /// Number of decimals of the token, used to convert amounts in the
+    /// token's smallest unit (e.g. wei) into whole tokens.
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+}
+
+fn default_decimals() -> u8 {
+    18
+}
+
+impl TokenPrice {
+    /// USD value of an amount given in the token's smallest unit.
+    pub fn usd_value(&self, amount: U256) -> f64 {
+        // U256 is stored as little endian u64 limbs
+        let amount = amount
+            .0
+            .iter()
+            .rev()
+            .fold(0f64, |acc, &limb| acc * 2f64.powi(64) + limb as f64);
+        amount / 10f64.powi(self.decimals as i32) * self.usd
+    }
+}
+
+/// Config for a source of native token prices
+#[derive(Debug, Clone)]
+pub enum TokenPriceSourceConf {
+    /// Fixed prices by domain id
+    Static { prices: HashMap<u32, TokenPrice> },
+    /// Prices by domain id fetched from an HTTP endpoint and cached
+    Http { url: String, cache_ttl: Duration },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum RawTokenPriceSourceConf {
+    Static {
+        /// Prices by domain id
+        prices: Option<HashMap<String, TokenPrice>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Http {
+        url: Option<String>,
+        /// How long fetched prices are used for. Defaults to 60 seconds.
+        cache_seconds: Option<StrOrInt>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+impl FromRawConf<'_, RawTokenPriceSourceConf> for TokenPriceSourceConf {
+    fn from_config_filtered(
+        raw: RawTokenPriceSourceConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        use RawTokenPriceSourceConf::*;
+        match raw {
+            Static { prices } => {
+                let prices = prices
+                    .ok_or_else(|| eyre!("Missing `prices` for static token price source"))
+                    .into_config_result(|| cwp + "prices")?
+                    .into_iter()
+                    .map(|(domain, price)| {
+                        let cwp = cwp + "prices";
+                        domain
+                            .parse()
+                            .map(|domain| (domain, price))
+                            .into_config_result(|| cwp + domain)
+                    })
+                    .collect::<ConfigResult<_>>()?;
+                Ok(Self::Static { prices })
+            }
+            Http { url, cache_seconds } => Ok(Self::Http {
+                url: url
+                    .ok_or_else(|| eyre!("Missing `url` for http token price source"))
+                    .into_config_result(|| cwp + "url")?,
+                cache_ttl: Duration::from_secs(
+                    cache_seconds
+                        .map(|r| r.try_into())
+                        .transpose()
+                        .into_config_result(|| cwp + "cacheSeconds")?
+                        .unwrap_or(60),
+                ),
+            }),
+            Unknown => Err(eyre!("Unknown token price source")).into_config_result(|| cwp.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,6 +150,13 @@ enum RawGasPaymentEnforcementPolicy {
         #[serde(default = "default_gasfraction")]
         gasfraction: String,
     },
+    #[serde(rename_all = "camelCase")]
+    UsdCost {
+        /// Optional percentage of the estimated cost which must be paid on
+        /// top of it. Defaults to 0.
+        margin_percent: Option<StrOrInt>,
+        price_source: Option<RawTokenPriceSourceConf>,
+    },
     #[serde(other)]
     Unknown,
 }
@@ -85,6 +197,22 @@ impl FromRawConf<'_, RawGasPaymentEnforcementPolicy> for GasPaymentEnforcementPo
                         .into_config_result(|| cwp + "gasfraction")?,
                 })
             }
+            UsdCost {
+                margin_percent,
+                price_source,
+            } => Ok(Self::UsdCost {
+                margin_percent: margin_percent
+                    .map(|r| r.try_into())
+                    .transpose()
+                    .into_config_result(|| cwp + "marginPercent")?
+                    .unwrap_or(0),
+                price_source: price_source
+                    .ok_or_else(|| {
+                        eyre!("Missing `priceSource` for UsdCost gas payment enforcement policy")
+                    })
+                    .into_config_result(|| cwp + "priceSource")?
+                    .parse_config(&(cwp + "priceSource"))?,
+            }),
             Unknown => Err(eyre!("Unknown gas payment enforcement policy"))
                 .into_config_result(|| cwp.clone()),
         }
@@ -132,6 +260,95 @@ impl FromRawConf<'_, RawGasPaymentEnforcementConf> for GasPaymentEnforcementConf
     }
 }
 
+/// A single message to deliver in one-shot mode.
+#[derive(Debug, Clone)]
+pub struct RelayMessageConf {
+    /// The chain the message was dispatched on
+    pub origin: HyperlaneDomain,
+    /// Which message to deliver
+    pub message: MessageSelector,
+    /// Gas limit for the delivery transaction. Estimated if not set.
+    pub gas_limit: Option<U256>,
+}
+
+/// Identifies a message dispatched on a known origin.
+#[derive(Debug, Clone, Copy)]
+pub enum MessageSelector {
+    /// The message with this nonce
+    Nonce(u32),
+    /// The message with this id
+    Id(H256),
+}
+
+/// Raw config for one-shot mode, e.g. given as
+/// `--relayMessage.origin ethereum --relayMessage.nonce 12`.
+#[derive(Debug, Deserialize)]
+struct RawRelayMessageConf {
+    /// Name of the origin chain
+    origin: Option<String>,
+    /// Nonce of the message, mutually exclusive with `id`
+    nonce: Option<StrOrInt>,
+    /// Id of the message as a hex string, mutually exclusive with `nonce`
+    id: Option<String>,
+    /// Gas limit for the delivery transaction
+    gaslimit: Option<StrOrInt>,
+}
+
+/// How the submitter for a destination chooses between operations which are
+/// ready to be prepared. The default keeps the order of next attempt time and
+/// nonce.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubmissionPriorityConf {
+    /// Score added for each doubling of the gas payment made for a message
+    pub gas_payment_weight: u64,
+    /// Score added for each minute since a message was dispatched
+    pub age_weight: u64,
+    /// Score subtracted for each failed attempt
+    pub retry_weight: u64,
+    /// Take turns between origins so that one origin with many messages can't
+    /// starve the others
+    pub origin_fairness: bool,
+}
+
+impl SubmissionPriorityConf {
+    /// Whether this only keeps the default order.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Raw config for submission priority, e.g. given as
+/// `--submissionPriority.gasPaymentWeight 10`.
+#[derive(Debug, Deserialize)]
+struct RawSubmissionPriorityConf {
+    gaspaymentweight: Option<StrOrInt>,
+    ageweight: Option<StrOrInt>,
+    retryweight: Option<StrOrInt>,
+    #[serde(default)]
+    originfairness: bool,
+}
+
+/// Config for the admin API
+#[derive(Clone)]
+pub struct AdminConf {
+    /// Address to serve the admin API on
+    pub host: IpAddr,
+    /// Port to serve the admin API on
+    pub port: u16,
+    /// Token which requests must present as a bearer token
+    pub token: String,
+}
+
+impl Debug for AdminConf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminConf")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
 decl_settings!(Relayer,
     Parsed {
         /// Database path
@@ -154,6 +371,30 @@ decl_settings!(Relayer,
         /// If true, allows local storage based checkpoint syncers.
         /// Not intended for production use.
         allow_local_checkpoint_syncers: bool,
+        /// The max number of messages to deliver in a single transaction to a
+        /// destination with a multicall contract configured. A value of 1
+        /// disables batching.
+        max_batch_size: u32,
+        /// Config for the admin API. The API is disabled if not set.
+        admin: Option<AdminConf>,
+        /// How long ISM lookups such as module types and validator sets are
+        /// cached for.
+        ism_cache_ttl: Duration,
+        /// If set, the relayer runs in dry-run mode: messages are prepared but
+        /// never submitted, and what would have happened to each of them is
+        /// written to a JSON report at this path.
+        dry_run_report: Option<PathBuf>,
+        /// If set, the relayer delivers only this message and then exits
+        /// instead of running continuously.
+        relay_message: Option<RelayMessageConf>,
+        /// How operations ready to be prepared are prioritised.
+        submission_priority: SubmissionPriorityConf,
+        /// Number of failed attempts after which a message is dead-lettered
+        /// instead of retried. Messages are retried forever if not set.
+        max_message_retries: Option<u32>,
+        /// How messages are retried, the first policy a message matches is
+        /// used. Messages which match none use the default policy.
+        retry_policies: Vec<RetryPolicyConf>,
     },
     Raw {
         /// Database path (path on the fs)
@@ -183,6 +424,42 @@ decl_settings!(Relayer,
         /// Not intended for production use. Defaults to false.
         #[serde(default)]
         allowlocalcheckpointsyncers: bool,
+        /// This is optional. The max number of messages to deliver in a single
+        /// transaction to a destination with a multicall contract configured.
+        /// Defaults to 1, which disables batching.
+        maxbatchsize: Option<StrOrInt>,
+        /// This is optional. Port to serve the admin API on. The admin API is
+        /// disabled unless both a port and a token are configured.
+        adminport: Option<StrOrInt>,
+        /// This is optional. Address to serve the admin API on. Defaults to
+        /// 127.0.0.1 so the API is only reachable from the same host.
+        adminhost: Option<String>,
+        /// This is optional. Shared token the admin API requires as a bearer
+        /// token.
+        admintoken: Option<String>,
+        /// This is optional. Number of seconds ISM lookups are cached for.
+        /// Defaults to 120.
+        ismcachettl: Option<StrOrInt>,
+        /// If true, prepares messages without submitting them. Not intended
+        /// for use with a database shared with a relayer that submits.
+        /// Defaults to false.
+        #[serde(default)]
+        dryrun: bool,
+        /// This is optional. Path to write the dry-run report to. Defaults to
+        /// `dry_run_report.json`.
+        dryrunreport: Option<String>,
+        /// This is optional. A single message to deliver, after which the
+        /// relayer exits. See `RawRelayMessageConf`.
+        relaymessage: Option<RawRelayMessageConf>,
+        /// This is optional. Weights for prioritising messages by gas
+        /// payment, age and failed attempts. See `RawSubmissionPriorityConf`.
+        submissionpriority: Option<RawSubmissionPriorityConf>,
+        /// This is optional. Number of failed attempts after which a message
+        /// is dead-lettered. Messages are retried forever if not set.
+        maxmessageretries: Option<StrOrInt>,
+        /// This is optional. The retry policies as JSON. Expects an ordered
+        /// array of `RetryPolicyConf`.
+        retrypolicies: Option<String>,
     }
 );
 
@@ -231,6 +508,99 @@ impl FromRawConf<'_, RawRelayerSettings> for RelayerSettings {
                 .take_err(&mut err, || cwp + "transactiongaslimit")
         });
 
+        let max_batch_size: u32 = raw
+            .maxbatchsize
+            .and_then(|r| r.try_into().take_err(&mut err, || cwp + "maxbatchsize"))
+            .unwrap_or(1);
+        if max_batch_size == 0 {
+            err.push(
+                cwp + "maxbatchsize",
+                eyre!("The max batch size must be at least 1"),
+            );
+        }
+
+        let admin_port: Option<u16> = raw
+            .adminport
+            .and_then(|r| r.try_into().take_err(&mut err, || cwp + "adminport"));
+        let admin_host = raw
+            .adminhost
+            .and_then(|r| r.parse().take_err(&mut err, || cwp + "adminhost"))
+            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let admin = match (admin_port, raw.admintoken) {
+            (Some(port), Some(token)) if !token.is_empty() => Some(AdminConf {
+                host: admin_host,
+                port,
+                token,
+            }),
+            (None, None) => None,
+            (Some(_), _) => {
+                err.push(
+                    cwp + "admintoken",
+                    eyre!("A non-empty `admintoken` is required to enable the admin API"),
+                );
+                None
+            }
+            (None, Some(_)) => {
+                err.push(
+                    cwp + "adminport",
+                    eyre!("An `adminport` is required to enable the admin API"),
+                );
+                None
+            }
+        };
+
+        let ism_cache_ttl = raw
+            .ismcachettl
+            .and_then(|r| r.try_into().take_err(&mut err, || cwp + "ismcachettl"))
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(120));
+
+        let dry_run_report = raw.dryrun.then(|| {
+            raw.dryrunreport
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("dry_run_report.json"))
+        });
+
+        let submission_priority = raw
+            .submissionpriority
+            .map(|r| {
+                let cwp = cwp + "submissionpriority";
+                let mut weight = |v: Option<StrOrInt>, key: &str| -> u64 {
+                    v.and_then(|v| v.try_into().take_err(&mut err, || &cwp + key))
+                        .unwrap_or(0)
+                };
+                SubmissionPriorityConf {
+                    gas_payment_weight: weight(r.gaspaymentweight, "gaspaymentweight"),
+                    age_weight: weight(r.ageweight, "ageweight"),
+                    retry_weight: weight(r.retryweight, "retryweight"),
+                    origin_fairness: r.originfairness,
+                }
+            })
+            .unwrap_or_default();
+
+        let max_message_retries = raw.maxmessageretries.and_then(|r| {
+            r.try_into()
+                .take_err(&mut err, || cwp + "maxmessageretries")
+        });
+
+        let retry_policies = raw
+            .retrypolicies
+            .and_then(|j| {
+                serde_json::from_str::<Vec<retry_policy::RawRetryPolicyConf>>(&j)
+                    .take_err(&mut err, || cwp + "retrypolicies")
+            })
+            .map(|rv| {
+                let cwp = cwp + "retrypolicies";
+                rv.into_iter()
+                    .enumerate()
+                    .filter_map(|(i, r)| {
+                        r.parse_config(&cwp.join(i.to_string()))
+                            .take_config_err(&mut err)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let skip_transaction_gas_limit_for = raw
             .skiptransactiongaslimitfor
             .and_then(|r| {
@@ -372,6 +742,53 @@ impl FromRawConf<'_, RawRelayerSettings> for RelayerSettings {
             }
         }
 
+        let relay_message = raw.relaymessage.and_then(|r| {
+            let cwp = cwp + "relaymessage";
+            let origin = r
+                .origin
+                .ok_or_else(|| eyre!("An `origin` is required to relay a message"))
+                .take_err(&mut err, || &cwp + "origin")
+                .and_then(|name| {
+                    origin_chains
+                        .iter()
+                        .find(|d| d.name() == name)
+                        .cloned()
+                        .ok_or_else(|| eyre!("`{name}` is not one of the origin chains"))
+                        .take_err(&mut err, || &cwp + "origin")
+                });
+            let message = match (r.nonce, r.id) {
+                (Some(nonce), None) => nonce
+                    .try_into()
+                    .take_err(&mut err, || &cwp + "nonce")
+                    .map(MessageSelector::Nonce),
+                (None, Some(id)) => id
+                    .parse()
+                    .take_err(&mut err, || &cwp + "id")
+                    .map(MessageSelector::Id),
+                _ => {
+                    err.push(
+                        &cwp + "nonce",
+                        eyre!("Exactly one of `nonce` or `id` is required to relay a message"),
+                    );
+                    None
+                }
+            };
+            let gas_limit = r
+                .gaslimit
+                .and_then(|r| r.try_into().take_err(&mut err, || &cwp + "gaslimit"));
+            Some(RelayMessageConf {
+                origin: origin?,
+                message: message?,
+                gas_limit,
+            })
+        });
+        if relay_message.is_some() && dry_run_report.is_some() {
+            err.push(
+                cwp + "relaymessage",
+                eyre!("`relaymessage` can't be combined with `dryrun`"),
+            );
+        }
+
         err.into_result()?;
         Ok(Self {
             base: base.unwrap(),
@@ -384,6 +801,14 @@ impl FromRawConf<'_, RawRelayerSettings> for RelayerSettings {
             transaction_gas_limit,
             skip_transaction_gas_limit_for,
             allow_local_checkpoint_syncers: raw.allowlocalcheckpointsyncers,
+            max_batch_size,
+            admin,
+            ism_cache_ttl,
+            dry_run_report,
+            relay_message,
+            submission_priority,
+            max_message_retries,
+            retry_policies,
         })
     }
 }
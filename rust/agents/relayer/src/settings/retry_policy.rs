@@ -0,0 +1,312 @@
+use std::time::Duration;
+
+use eyre::eyre;
+use serde::Deserialize;
+
+use hyperlane_core::config::*;
+
+use crate::settings::matching_list::MatchingList;
+
+/// How long to wait after submitting a message before checking that it was
+/// delivered, unless a retry policy says otherwise.
+pub const DEFAULT_CONFIRM_DELAY: Duration = if cfg!(any(test, feature = "test-utils")) {
+    // Wait 5 seconds after submitting the message before confirming in test mode
+    Duration::from_secs(5)
+} else {
+    // Wait 10 min after submitting the message before confirming in normal/production mode
+    Duration::from_secs(60 * 10)
+};
+
+/// How long to wait before re-attempting a message after a failed attempt.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RetrySchedule {
+    /// 10s for the first 11 attempts, then linear 90s steps, then exponential
+    /// growth plus 30 minutes.
+    #[default]
+    Default,
+    /// `initial + step * (n - 1)` after the nth failed attempt
+    Linear { initial: Duration, step: Duration },
+    /// `initial * factor ^ (n - 1)` after the nth failed attempt
+    Exponential { initial: Duration, factor: u32 },
+}
+
+impl RetrySchedule {
+    fn delay(&self, num_retries: u32) -> Duration {
+        match *self {
+            Self::Default => Duration::from_secs(match num_retries {
+                // wait 10s for the first few attempts; this prevents thrashing
+                i if i < 12 => 10,
+                // wait 90s to 19.5min with a linear increase
+                i if i < 24 => (i as u64 - 11) * 90,
+                // exponential increase + 30 min; -21 makes it so that at i = 32 it will be
+                // ~60min timeout (64min to be more precise).
+                i => 2u64.saturating_pow(i - 21).saturating_add(60 * 30),
+            }),
+            Self::Linear { initial, step } => {
+                initial.saturating_add(step.saturating_mul(num_retries - 1))
+            }
+            Self::Exponential { initial, factor } => {
+                initial.saturating_mul(factor.saturating_pow(num_retries - 1))
+            }
+        }
+    }
+}
+
+/// How a message is retried after failed attempts and confirmed after being
+/// submitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Delay between attempts
+    pub schedule: RetrySchedule,
+    /// Upper bound on the delay between attempts
+    pub max_delay: Option<Duration>,
+    /// Messages dispatched longer ago than this are dead-lettered instead of
+    /// retried
+    pub max_age: Option<Duration>,
+    /// How long to wait after submitting a message before checking that it
+    /// was delivered
+    pub confirm_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            schedule: RetrySchedule::default(),
+            max_delay: None,
+            max_age: None,
+            confirm_delay: DEFAULT_CONFIRM_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Get duration we should wait before re-attempting to deliver a message
+    /// given the number of retries.
+    pub fn backoff(&self, num_retries: u32) -> Option<Duration> {
+        if num_retries < 1 {
+            return None;
+        }
+        let delay = self.schedule.delay(num_retries);
+        Some(self.max_delay.map_or(delay, |max| delay.min(max)))
+    }
+}
+
+/// Config for a retry policy
+#[derive(Debug, Clone, Default)]
+pub struct RetryPolicyConf {
+    /// The retry policy
+    pub policy: RetryPolicy,
+    /// An optional matching list, any message that matches will use this
+    /// policy. By default all messages will match.
+    pub matching_list: MatchingList,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum RawRetrySchedule {
+    Default,
+    #[serde(rename_all = "camelCase")]
+    Linear {
+        /// Defaults to 10 seconds.
+        initial_seconds: Option<StrOrInt>,
+        step_seconds: Option<StrOrInt>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Exponential {
+        /// Defaults to 10 seconds.
+        initial_seconds: Option<StrOrInt>,
+        /// Defaults to 2.
+        factor: Option<StrOrInt>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+impl FromRawConf<'_, RawRetrySchedule> for RetrySchedule {
+    fn from_config_filtered(
+        raw: RawRetrySchedule,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        use RawRetrySchedule::*;
+        let seconds = |v: Option<StrOrInt>, key: &str| -> ConfigResult<Option<Duration>> {
+            v.map(|r| r.try_into())
+                .transpose()
+                .into_config_result(|| cwp + key)
+                .map(|s: Option<u64>| s.map(Duration::from_secs))
+        };
+        match raw {
+            Default => Ok(Self::Default),
+            Linear {
+                initial_seconds,
+                step_seconds,
+            } => Ok(Self::Linear {
+                initial: seconds(initial_seconds, "initialSeconds")?
+                    .unwrap_or(Duration::from_secs(10)),
+                step: seconds(step_seconds, "stepSeconds")?
+                    .ok_or_else(|| eyre!("Missing `stepSeconds` for linear retry schedule"))
+                    .into_config_result(|| cwp + "stepSeconds")?,
+            }),
+            Exponential {
+                initial_seconds,
+                factor,
+            } => {
+                let factor: u32 = factor
+                    .map(|r| r.try_into())
+                    .transpose()
+                    .into_config_result(|| cwp + "factor")?
+                    .unwrap_or(2);
+                if factor == 0 {
+                    return Err(eyre!(
+                        "The factor of an exponential retry schedule must be at least 1"
+                    ))
+                    .into_config_result(|| cwp + "factor");
+                }
+                Ok(Self::Exponential {
+                    initial: seconds(initial_seconds, "initialSeconds")?
+                        .unwrap_or(Duration::from_secs(10)),
+                    factor,
+                })
+            }
+            Unknown => Err(eyre!("Unknown retry schedule")).into_config_result(|| cwp.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RawRetryPolicyConf {
+    #[serde(flatten)]
+    schedule: Option<RawRetrySchedule>,
+    max_delay_seconds: Option<StrOrInt>,
+    max_age_seconds: Option<StrOrInt>,
+    confirm_delay_seconds: Option<StrOrInt>,
+    #[serde(default)]
+    matching_list: Option<MatchingList>,
+}
+
+impl FromRawConf<'_, RawRetryPolicyConf> for RetryPolicyConf {
+    fn from_config_filtered(
+        raw: RawRetryPolicyConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let schedule = raw
+            .schedule
+            .and_then(|r| r.parse_config(cwp).take_config_err(&mut err))
+            .unwrap_or_default();
+
+        let mut seconds = |v: Option<StrOrInt>, key: &str| -> Option<Duration> {
+            v.and_then(|r| r.try_into().take_err(&mut err, || cwp + key))
+                .map(Duration::from_secs)
+        };
+        let max_delay = seconds(raw.max_delay_seconds, "maxDelaySeconds");
+        let max_age = seconds(raw.max_age_seconds, "maxAgeSeconds");
+        let confirm_delay = seconds(raw.confirm_delay_seconds, "confirmDelaySeconds")
+            .unwrap_or(DEFAULT_CONFIRM_DELAY);
+
+        err.into_result()?;
+        Ok(Self {
+            policy: RetryPolicy {
+                schedule,
+                max_delay,
+                max_age,
+                confirm_delay,
+            },
+            matching_list: raw.matching_list.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(policy: &RetryPolicy, num_retries: u32) -> Option<u64> {
+        policy.backoff(num_retries).map(|d| d.as_secs())
+    }
+
+    #[test]
+    fn test_default_schedule() {
+        let policy = RetryPolicy::default();
+        assert_eq!(secs(&policy, 0), None);
+        assert_eq!(secs(&policy, 1), Some(10));
+        assert_eq!(secs(&policy, 11), Some(10));
+        assert_eq!(secs(&policy, 12), Some(90));
+        assert_eq!(secs(&policy, 23), Some(12 * 90));
+        assert_eq!(secs(&policy, 32), Some(2048 + 1800));
+        assert_eq!(secs(&policy, 200), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_linear_and_exponential_schedules() {
+        let linear = RetryPolicy {
+            schedule: RetrySchedule::Linear {
+                initial: Duration::from_secs(5),
+                step: Duration::from_secs(30),
+            },
+            ..Default::default()
+        };
+        assert_eq!(secs(&linear, 1), Some(5));
+        assert_eq!(secs(&linear, 3), Some(65));
+
+        let exponential = RetryPolicy {
+            schedule: RetrySchedule::Exponential {
+                initial: Duration::from_secs(10),
+                factor: 3,
+            },
+            ..Default::default()
+        };
+        assert_eq!(secs(&exponential, 1), Some(10));
+        assert_eq!(secs(&exponential, 4), Some(270));
+    }
+
+    #[test]
+    fn test_max_delay_caps_schedule() {
+        let capped = RetryPolicy {
+            schedule: RetrySchedule::Exponential {
+                initial: Duration::from_secs(10),
+                factor: 2,
+            },
+            max_delay: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(secs(&capped, 3), Some(40));
+        assert_eq!(secs(&capped, 4), Some(60));
+        assert_eq!(secs(&capped, 100), Some(60));
+    }
+
+    #[test]
+    fn test_parse_retry_policy() {
+        let raw: RawRetryPolicyConf = serde_json::from_str(
+            r#"{
+                "type": "linear",
+                "stepSeconds": 30,
+                "maxAgeSeconds": "86400",
+                "confirmDelaySeconds": 60,
+                "matchingList": [{"destinationDomain": 1}]
+            }"#,
+        )
+        .unwrap();
+        let conf: RetryPolicyConf = raw.parse_config(&ConfigPath::default()).unwrap();
+        assert_eq!(
+            conf.policy,
+            RetryPolicy {
+                schedule: RetrySchedule::Linear {
+                    initial: Duration::from_secs(10),
+                    step: Duration::from_secs(30),
+                },
+                max_delay: None,
+                max_age: Some(Duration::from_secs(86400)),
+                confirm_delay: Duration::from_secs(60),
+            }
+        );
+        assert!(conf.matching_list.0.is_some());
+
+        let raw: RawRetryPolicyConf = serde_json::from_str(r#"{"type": "fibonacci"}"#).unwrap();
+        assert!(raw
+            .parse_config::<RetryPolicyConf>(&ConfigPath::default())
+            .is_err());
+    }
+}
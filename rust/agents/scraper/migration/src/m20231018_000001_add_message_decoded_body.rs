@@ -0,0 +1,229 @@
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+
+use crate::m20230309_000001_create_table_domain::Domain;
+use crate::m20230309_000002_create_table_block::Block;
+use crate::m20230309_000003_create_table_transaction::Transaction;
+use crate::m20230309_000004_create_table_delivered_message::DeliveredMessage;
+use crate::m20230309_000004_create_table_gas_payment::TotalGasPayment;
+use crate::m20230309_000005_create_table_message::Message;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(ColumnDef::new(MessageDecodedBody::DecodedBody).text())
+                    .to_owned(),
+            )
+            .await?;
+        recreate_message_view(manager, true).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_message_view(manager).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(MessageDecodedBody::DecodedBody)
+                    .to_owned(),
+            )
+            .await?;
+        recreate_message_view(manager, false).await
+    }
+}
+
+async fn drop_message_view(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .get_connection()
+        .execute_unprepared(&format!(
+            r#"DROP VIEW IF EXISTS "{}_view""#,
+            Message::Table.to_string()
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Replace the message view created in `m20230309_000005_create_table_message`
+/// with one that does or does not expose the decoded body column.
+async fn recreate_message_view(
+    manager: &SchemaManager<'_>,
+    with_decoded_body: bool,
+) -> Result<(), DbErr> {
+    drop_message_view(manager).await?;
+    manager
+        .get_connection()
+        .execute_unprepared(&message_view_sql(with_decoded_body))
+        .await?;
+    Ok(())
+}
+
+fn message_view_sql(with_decoded_body: bool) -> String {
+    let decoded_body = if with_decoded_body {
+        format!(
+            r#",
+            "msg"."{}" AS "decoded_body""#,
+            MessageDecodedBody::DecodedBody.to_string()
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        r#"
+        CREATE VIEW "{msg_table}_view" AS
+        SELECT
+            "msg"."{msg_id}" AS "id",
+            "msg"."{msg_mid}" AS "msg_id",
+            "msg"."{msg_nonce}" AS "nonce",
+
+            "dmsg"."{dmsg_id}" IS NOT NULL AS "is_delivered",
+
+            COALESCE("tgp"."{tgp_num_payments}", '0') AS "num_payments",
+            COALESCE("tgp"."{tgp_payment}", '0') AS "total_payment",
+            COALESCE("tgp"."{tgp_gas_amount}", '0') AS "total_gas_amount",
+
+            "msg"."{msg_origin}" AS "origin_domain_id",
+            "origin_domain"."{domain_chain_id}" AS "origin_chain_id",
+            "origin_domain"."{domain_name}" AS "origin_domain",
+
+            "msg"."{msg_dest}" AS "destination_domain_id",
+            "dest_domain"."{domain_chain_id}" AS "destination_chain_id",
+            "dest_domain"."{domain_name}" AS "destination_domain",
+
+            "msg"."{msg_time_created}" AS "send_scraped_at",
+            "origin_block"."{block_timestamp}" AS "send_occurred_at",
+            "dmsg"."{dmsg_time_created}" AS "delivery_scraped_at",
+            "dest_block"."{block_timestamp}" AS "delivery_occurred_at",
+            "dest_block"."{block_timestamp}" - "origin_block"."{block_timestamp}" AS "delivery_latency",
+            "msg"."{msg_time_created}" - "origin_block"."{block_timestamp}" AS "send_scape_latency",
+            "dmsg"."{dmsg_time_created}" - "dest_block"."{block_timestamp}" AS "delivery_scape_latency",
+
+            "msg"."{msg_sender}" AS "sender",
+            "msg"."{msg_recipient}" AS "recipient",
+            "msg"."{msg_origin_mb}" AS "origin_mailbox",
+            "dmsg"."{dmsg_dest_mb}" AS "destination_mailbox",
+
+            "msg"."{msg_oti}" AS "origin_tx_id",
+            "origin_tx"."{tx_hash}" AS "origin_tx_hash",
+            "origin_tx"."{tx_gas_limit}" AS "origin_tx_gas_limit",
+            "origin_tx"."{tx_mpfpg}" AS "origin_tx_max_priority_fee_per_gas",
+            "origin_tx"."{tx_mfpg}" AS "origin_tx_max_fee_per_gas",
+            "origin_tx"."{tx_gas_price}" AS "origin_tx_gas_price",
+            "origin_tx"."{tx_egp}" AS "origin_tx_effective_gas_price",
+            "origin_tx"."{tx_nonce}" AS "origin_tx_nonce",
+            "origin_tx"."{tx_sender}" AS "origin_tx_sender",
+            "origin_tx"."{tx_receipient}" AS "origin_tx_recipient",
+            "origin_tx"."{tx_gas_used}" AS "origin_tx_gas_used",
+            "origin_tx"."{tx_cgu}" AS "origin_tx_cumulative_gas_used",
+
+            "origin_tx"."{tx_block_id}" AS "origin_block_id",
+            "origin_block"."{block_height}" AS "origin_block_height",
+            "origin_block"."{block_hash}" AS "origin_block_hash",
+
+            "dmsg"."{dmsg_dti}" AS "destination_tx_id",
+            "dest_tx"."{tx_hash}" AS "destination_tx_hash",
+            "dest_tx"."{tx_gas_limit}" AS "destination_tx_gas_limit",
+            "dest_tx"."{tx_mpfpg}" AS "destination_tx_max_priority_fee_per_gas",
+            "dest_tx"."{tx_mfpg}" AS "destination_tx_max_fee_per_gas",
+            "dest_tx"."{tx_gas_price}" AS "destination_tx_gas_price",
+            "dest_tx"."{tx_egp}" AS "destination_tx_effective_gas_price",
+            "dest_tx"."{tx_nonce}" AS "destination_tx_nonce",
+            "dest_tx"."{tx_sender}" AS "destination_tx_sender",
+            "dest_tx"."{tx_receipient}" AS "destination_tx_recipient",
+            "dest_tx"."{tx_gas_used}" AS "destination_tx_gas_used",
+            "dest_tx"."{tx_cgu}" AS "destination_tx_cumulative_gas_used",
+
+            "dest_tx"."{tx_block_id}" AS "destination_block_id",
+            "dest_block"."{block_height}" AS "destination_block_height",
+            "dest_block"."{block_hash}" AS "destination_block_hash",
+
+            "msg"."{msg_body}" AS "message_body"{decoded_body}
+        FROM "{msg_table}" AS "msg"
+            LEFT JOIN "{domain_table}"
+                AS "origin_domain"
+                ON "origin_domain"."{domain_id}" = "msg"."{msg_origin}"
+            LEFT JOIN "{domain_table}"
+                AS "dest_domain"
+                ON "dest_domain"."{domain_id}" = "msg"."{msg_dest}"
+            LEFT JOIN "{tx_table}"
+                AS "origin_tx"
+                ON "origin_tx"."{tx_id}" = "msg"."{msg_oti}"
+            LEFT JOIN "{block_table}"
+                AS "origin_block"
+                ON "origin_block"."{block_id}" = "origin_tx"."{tx_block_id}"
+            LEFT JOIN "{tgp_table}"
+                AS "tgp"
+                ON "tgp"."{tgp_mid}" = "msg"."{msg_mid}"
+            LEFT JOIN "{dmsg_table}"
+                AS "dmsg"
+                ON "dmsg"."{dmsg_mid}" = "msg"."{msg_mid}"
+            LEFT JOIN "{tx_table}"
+                AS "dest_tx"
+                ON "dest_tx"."{tx_id}" = "dmsg"."{dmsg_dti}"
+            LEFT JOIN "{block_table}"
+                AS "dest_block"
+                ON "dest_block"."{block_id}" = "dest_tx"."{tx_block_id}"
+        "#,
+        decoded_body = decoded_body,
+        msg_table = Message::Table.to_string(),
+        msg_id = Message::Id.to_string(),
+        msg_time_created = Message::TimeCreated.to_string(),
+        msg_mid = Message::MsgId.to_string(),
+        msg_origin = Message::Origin.to_string(),
+        msg_dest = Message::Destination.to_string(),
+        msg_nonce = Message::Nonce.to_string(),
+        msg_sender = Message::Sender.to_string(),
+        msg_recipient = Message::Recipient.to_string(),
+        msg_body = Message::MsgBody.to_string(),
+        msg_origin_mb = Message::OriginMailbox.to_string(),
+        msg_oti = Message::OriginTxId.to_string(),
+        domain_table = Domain::Table.to_string(),
+        domain_id = Domain::Id.to_string(),
+        domain_name = Domain::Name.to_string(),
+        domain_chain_id = Domain::ChainId.to_string(),
+        tx_table = Transaction::Table.to_string(),
+        tx_id = Transaction::Id.to_string(),
+        tx_hash = Transaction::Hash.to_string(),
+        tx_block_id = Transaction::BlockId.to_string(),
+        tx_gas_limit = Transaction::GasLimit.to_string(),
+        tx_mpfpg = Transaction::MaxPriorityFeePerGas.to_string(),
+        tx_mfpg = Transaction::MaxFeePerGas.to_string(),
+        tx_gas_price = Transaction::GasPrice.to_string(),
+        tx_egp = Transaction::EffectiveGasPrice.to_string(),
+        tx_nonce = Transaction::Nonce.to_string(),
+        tx_sender = Transaction::Sender.to_string(),
+        tx_receipient = Transaction::Recipient.to_string(),
+        tx_gas_used = Transaction::GasUsed.to_string(),
+        tx_cgu = Transaction::CumulativeGasUsed.to_string(),
+        block_table = Block::Table.to_string(),
+        block_id = Block::Id.to_string(),
+        block_hash = Block::Hash.to_string(),
+        block_height = Block::Height.to_string(),
+        block_timestamp = Block::Timestamp.to_string(),
+        tgp_table = TotalGasPayment::Table.to_string(),
+        tgp_mid = TotalGasPayment::MsgId.to_string(),
+        tgp_num_payments = TotalGasPayment::NumPayments.to_string(),
+        tgp_payment = TotalGasPayment::TotalPayment.to_string(),
+        tgp_gas_amount = TotalGasPayment::TotalGasAmount.to_string(),
+        dmsg_table = DeliveredMessage::Table.to_string(),
+        dmsg_id = DeliveredMessage::Id.to_string(),
+        dmsg_mid = DeliveredMessage::MsgId.to_string(),
+        dmsg_dest_mb = DeliveredMessage::DestinationMailbox.to_string(),
+        dmsg_dti = DeliveredMessage::DestinationTxId.to_string(),
+        dmsg_time_created = DeliveredMessage::TimeCreated.to_string(),
+    )
+}
+
+/// Learn more at https://docs.rs/sea-query#iden
+#[derive(Iden)]
+pub enum MessageDecodedBody {
+    /// Human readable rendering of the message body produced by the body
+    /// decoders, if one of them recognized it.
+    DecodedBody,
+}
@@ -129,6 +129,7 @@ impl BaseAgent for Scraper {
                     .await?
                     .into(),
                 &chain_setup.index.clone(),
+                settings.body_decoders.clone(),
             )
             .await?;
             scrapers.insert(
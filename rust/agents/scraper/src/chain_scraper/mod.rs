@@ -11,9 +11,9 @@ use tracing::trace;
 
 use hyperlane_base::chains::IndexSettings;
 use hyperlane_core::{
-    BlockInfo, Delivery, HyperlaneDomain, HyperlaneLogStore, HyperlaneMessage,
-    HyperlaneMessageStore, HyperlaneProvider, HyperlaneWatermarkedLogStore, InterchainGasPayment,
-    LogMeta, H256,
+    body::MessageBodyDecoders, BlockInfo, Delivery, HyperlaneDomain, HyperlaneLogStore,
+    HyperlaneMessage, HyperlaneMessageStore, HyperlaneProvider, HyperlaneWatermarkedLogStore,
+    InterchainGasPayment, LogMeta, H256,
 };
 
 use crate::db::StorablePayment;
@@ -37,6 +37,7 @@ pub struct HyperlaneSqlDb {
     db: ScraperDb,
     provider: Arc<dyn HyperlaneProvider>,
     cursor: Arc<BlockCursor>,
+    body_decoders: MessageBodyDecoders,
 }
 
 #[allow(unused)]
@@ -47,6 +48,7 @@ impl HyperlaneSqlDb {
         domain: HyperlaneDomain,
         provider: Arc<dyn HyperlaneProvider>,
         index_settings: &IndexSettings,
+        body_decoders: MessageBodyDecoders,
     ) -> Result<Self> {
         let cursor = Arc::new(
             db.block_cursor(domain.id(), index_settings.from as u64)
@@ -58,6 +60,7 @@ impl HyperlaneSqlDb {
             provider,
             mailbox_address,
             cursor,
+            body_decoders,
         })
     }
 
@@ -260,8 +263,13 @@ impl HyperlaneLogStore<HyperlaneMessage> for HyperlaneSqlDb {
             .collect();
         let storable = messages.iter().map(|m| {
             let txn = txns.get(&m.1.transaction_hash).unwrap();
+            let decoded_body = self
+                .body_decoders
+                .decode(&m.0)
+                .map(|body| serde_json::to_string(&body).expect("!serialize decoded body"));
             StorableMessage {
                 msg: m.0.clone(),
+                decoded_body,
                 meta: &m.1,
                 txn_id: txn.id,
             }
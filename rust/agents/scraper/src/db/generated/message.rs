@@ -22,6 +22,7 @@ pub struct Model {
     pub sender: Vec<u8>,
     pub recipient: Vec<u8>,
     pub msg_body: Option<Vec<u8>>,
+    pub decoded_body: Option<String>,
     pub origin_mailbox: Vec<u8>,
     pub origin_tx_id: i64,
 }
@@ -37,6 +38,7 @@ pub enum Column {
     Sender,
     Recipient,
     MsgBody,
+    DecodedBody,
     OriginMailbox,
     OriginTxId,
 }
@@ -74,6 +76,7 @@ impl ColumnTrait for Column {
             Self::MsgBody => ColumnType::Binary(sea_orm::sea_query::BlobSize::Blob(None))
                 .def()
                 .null(),
+            Self::DecodedBody => ColumnType::Text.def().null(),
             Self::OriginMailbox => {
                 ColumnType::Binary(sea_orm::sea_query::BlobSize::Blob(None)).def()
             }
@@ -22,6 +22,8 @@ pub struct StorableDelivery<'a> {
 
 pub struct StorableMessage<'a> {
     pub msg: HyperlaneMessage,
+    /// The fields of the message body as JSON, if it could be decoded
+    pub decoded_body: Option<String>,
     pub meta: &'a LogMeta,
     /// The database id of the transaction the message was sent in
     pub txn_id: i64,
@@ -215,6 +217,7 @@ impl ScraperDb {
                 } else {
                     Some(storable.msg.body)
                 }),
+                decoded_body: Set(storable.decoded_body),
                 origin_mailbox: Unchanged(origin_mailbox.clone()),
                 origin_tx_id: Set(storable.txn_id),
             })
@@ -236,6 +239,7 @@ impl ScraperDb {
                     message::Column::Sender,
                     message::Column::Recipient,
                     message::Column::MsgBody,
+                    message::Column::DecodedBody,
                     message::Column::OriginTxId,
                 ])
                 .to_owned(),
@@ -4,10 +4,10 @@ use std::time::{Duration, Instant};
 use std::vec;
 
 use eyre::Result;
-use prometheus::IntGauge;
+use prometheus::{IntCounter, IntCounterVec, IntGauge};
 use tokio::time::sleep;
 use tracing::instrument;
-use tracing::{debug, info};
+use tracing::{debug, error, info, warn};
 
 use hyperlane_base::{db::HyperlaneRocksDB, CheckpointSyncer, CoreMetrics};
 use hyperlane_core::{
@@ -66,6 +66,8 @@ impl ValidatorSubmitter {
         let mut checkpoint_queue = vec![];
 
         let mut reached_target = false;
+        let mut seen_rollbacks = self.message_db.retrieve_message_rollback_count()?;
+        let mut unsupported_nonce = None;
 
         while !reached_target {
             let correctness_checkpoint = if let Some(c) = target_checkpoint {
@@ -79,11 +81,43 @@ impl ValidatorSubmitter {
                 latest_checkpoint
             };
 
+            // if messages which were already ingested were reorged out,
+            // rewind the tree to before the first of them and re-ingest
+            let (rollbacks, reorged_nonce) = self
+                .message_db
+                .retrieve_message_rollbacks_since(seen_rollbacks)?;
+            seen_rollbacks = rollbacks;
+            if let Some(nonce) = reorged_nonce.filter(|nonce| (*nonce as usize) < tree.count()) {
+                warn!(
+                    nonce,
+                    count = tree.count(),
+                    "Message was reorged out after being ingested into the merkle tree, rewinding"
+                );
+                tree = self.rewind_tree(nonce).await?;
+                checkpoint_queue.retain(|queued| (queued.index as usize) < tree.count());
+                unsupported_nonce = unsupported_nonce.filter(|unsupported| *unsupported < nonce);
+            }
+
             // ingest available messages from DB
             while let Some(message) = self
                 .message_db
                 .retrieve_message_by_nonce(tree.count() as u32)?
             {
+                // the leaf can't be skipped without diverging from the
+                // mailbox's tree, so stop signing until the validator is
+                // upgraded rather than attesting to a message it can't read
+                if !message.has_supported_version() {
+                    if unsupported_nonce != Some(message.nonce) {
+                        error!(
+                            ?message,
+                            "Message has an unsupported version, not signing checkpoints past it"
+                        );
+                        self.metrics.unsupported_version(message.version).inc();
+                        unsupported_nonce = Some(message.nonce);
+                    }
+                    break;
+                }
+
                 debug!(index = message.nonce, "Ingesting leaf to tree");
                 let message_id = message.id();
                 tree.ingest(message_id);
@@ -130,6 +164,21 @@ impl ValidatorSubmitter {
         }
     }
 
+    /// Rebuild the tree from the messages before `nonce`. If the DB doesn't
+    /// hold all of them, e.g. because the tree was fetched from the mailbox
+    /// rather than built from the DB, the mailbox's tree is fetched again.
+    async fn rewind_tree(&self, nonce: u32) -> Result<IncrementalMerkle> {
+        let mut tree = IncrementalMerkle::default();
+        for index in 0..nonce {
+            let Some(message) = self.message_db.retrieve_message_by_nonce(index)? else {
+                debug!(index, "Message is not in the DB, fetching the tree from the mailbox");
+                return Ok(self.mailbox.tree(self.reorg_period).await?);
+            };
+            tree.ingest(message.id());
+        }
+        Ok(tree)
+    }
+
     pub(crate) async fn legacy_checkpoint_submitter(self) -> Result<()> {
         // current_index will be None if the validator cannot find
         // a previously signed checkpoint
@@ -210,16 +259,19 @@ impl ValidatorSubmitter {
 
 #[derive(Clone)]
 pub(crate) struct ValidatorSubmitterMetrics {
+    origin: String,
     latest_checkpoint_observed: IntGauge,
     latest_checkpoint_processed: IntGauge,
     legacy_latest_checkpoint_observed: IntGauge,
     legacy_latest_checkpoint_processed: IntGauge,
+    unsupported_message_versions: IntCounterVec,
 }
 
 impl ValidatorSubmitterMetrics {
     pub fn new(metrics: &CoreMetrics, mailbox_chain: &HyperlaneDomain) -> Self {
         let chain_name = mailbox_chain.name();
         Self {
+            origin: chain_name.to_owned(),
             legacy_latest_checkpoint_observed: metrics
                 .latest_checkpoint()
                 .with_label_values(&["legacy_validator_observed", chain_name]),
@@ -232,6 +284,12 @@ impl ValidatorSubmitterMetrics {
             latest_checkpoint_processed: metrics
                 .latest_checkpoint()
                 .with_label_values(&["validator_processed", chain_name]),
+            unsupported_message_versions: metrics.unsupported_message_versions(),
         }
     }
+
+    fn unsupported_version(&self, version: u8) -> IntCounter {
+        self.unsupported_message_versions
+            .with_label_values(&[self.origin.as_str(), version.to_string().as_str()])
+    }
 }
@@ -1,7 +1,10 @@
+use std::time::Duration;
+
 use serde::Deserialize;
 use url::Url;
 
 use hyperlane_core::config::*;
+use hyperlane_core::U256;
 
 /// Ethereum connection configuration
 #[derive(Debug, Clone)]
@@ -120,3 +123,82 @@ impl FromRawConf<'_, RawConnectionConf> for ConnectionConf {
         }
     }
 }
+
+/// Configuration for replacing transactions which have not been included in
+/// a block in time with ones paying higher fees.
+#[derive(Debug, Clone)]
+pub struct TransactionReplacementConf {
+    /// How long to wait for a transaction to be included before replacing it.
+    pub interval: Duration,
+    /// Percentage by which the fees are increased with each replacement.
+    pub fee_bump_percent: u32,
+    /// Max number of times a transaction is replaced before giving up on it.
+    pub max_replacements: u32,
+    /// Max fee per gas in wei that replacements will not exceed. There is no
+    /// ceiling if not set.
+    pub max_fee_per_gas: Option<U256>,
+}
+
+impl Default for TransactionReplacementConf {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            fee_bump_percent: 20,
+            max_replacements: 4,
+            max_fee_per_gas: None,
+        }
+    }
+}
+
+/// Raw configuration for replacing transactions
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransactionReplacementConf {
+    /// Seconds to wait for a transaction to be included before replacing it
+    interval: Option<StrOrInt>,
+    /// Percentage by which the fees are increased with each replacement
+    fee_bump_percent: Option<StrOrInt>,
+    /// Max number of times a transaction is replaced
+    max_replacements: Option<StrOrInt>,
+    /// Max fee per gas in wei that replacements will not exceed
+    max_fee_per_gas: Option<StrOrInt>,
+}
+
+impl FromRawConf<'_, RawTransactionReplacementConf> for TransactionReplacementConf {
+    fn from_config_filtered(
+        raw: RawTransactionReplacementConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let default = Self::default();
+
+        let interval = raw
+            .interval
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "interval"))
+            .map(Duration::from_secs)
+            .unwrap_or(default.interval);
+
+        let fee_bump_percent = raw
+            .fee_bump_percent
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "feeBumpPercent"))
+            .unwrap_or(default.fee_bump_percent);
+
+        let max_replacements = raw
+            .max_replacements
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "maxReplacements"))
+            .unwrap_or(default.max_replacements);
+
+        let max_fee_per_gas = raw
+            .max_fee_per_gas
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "maxFeePerGas"));
+
+        err.into_result()?;
+        Ok(Self {
+            interval,
+            fee_bump_percent,
+            max_replacements,
+            max_fee_per_gas,
+        })
+    }
+}
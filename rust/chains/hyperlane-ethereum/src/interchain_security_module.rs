@@ -5,12 +5,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use ethers::contract::ContractError;
 use ethers::providers::Middleware;
 use tracing::{instrument, warn};
 
 use hyperlane_core::{
     ChainResult, ContractLocator, HyperlaneAbi, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
-    HyperlaneProvider, InterchainSecurityModule, ModuleType, H256,
+    HyperlaneMessage, HyperlaneProvider, InterchainSecurityModule, IsmVerification, ModuleType,
+    RawHyperlaneMessage, H256,
 };
 use num_traits::cast::FromPrimitive;
 
@@ -106,6 +108,41 @@ where
             Ok(ModuleType::Unused)
         }
     }
+
+    #[instrument(skip(self, metadata))]
+    async fn dry_run_verify(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+    ) -> ChainResult<IsmVerification> {
+        let call = self.contract.verify(
+            metadata.to_vec().into(),
+            RawHyperlaneMessage::from(message).to_vec().into(),
+        );
+        match call.call().await {
+            Ok(true) => Ok(IsmVerification::Verified),
+            Ok(false) => Ok(IsmVerification::Rejected(
+                "verify returned false".to_owned(),
+            )),
+            Err(err) => match revert_reason(&err) {
+                Some(reason) => Ok(IsmVerification::Rejected(reason)),
+                None => Err(err.into()),
+            },
+        }
+    }
+}
+
+/// Describe why a call reverted, or None if it failed for another reason.
+fn revert_reason<M: Middleware>(err: &ContractError<M>) -> Option<String> {
+    let data = err.as_revert()?;
+    let reason = if let Some(reason) = err.decode_revert::<String>() {
+        format!("reverted: {reason}")
+    } else if data.is_empty() {
+        "reverted without a reason".to_owned()
+    } else {
+        format!("reverted with data {data}")
+    };
+    Some(reason)
 }
 
 pub struct EthereumInterchainSecurityModuleAbi;
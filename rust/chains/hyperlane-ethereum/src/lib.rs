@@ -10,9 +10,9 @@ use ethers::prelude::{abi, Lazy, Middleware};
 
 #[cfg(not(doctest))]
 pub use self::{
-    config::*, interchain_gas::*, interchain_security_module::*, mailbox::*, multisig_ism::*,
-    provider::*, routing_ism::*, rpc_clients::*, signers::*, singleton_signer::*, trait_builder::*,
-    validator_announce::*,
+    aggregation_ism::*, config::*, interchain_gas::*, interchain_security_module::*, mailbox::*,
+    multisig_ism::*, provider::*, routing_ism::*, rpc_clients::*, signer_pool::*, signers::*,
+    singleton_signer::*, trait_builder::*, validator_announce::*,
 };
 
 #[cfg(not(doctest))]
@@ -45,6 +45,10 @@ mod multisig_ism;
 #[cfg(not(doctest))]
 mod routing_ism;
 
+/// AggregationIsm abi
+#[cfg(not(doctest))]
+mod aggregation_ism;
+
 /// ValidatorAnnounce abi
 #[cfg(not(doctest))]
 mod validator_announce;
@@ -58,6 +62,9 @@ mod rpc_clients;
 
 mod signers;
 
+#[cfg(not(doctest))]
+mod signer_pool;
+
 #[cfg(not(doctest))]
 mod singleton_signer;
 
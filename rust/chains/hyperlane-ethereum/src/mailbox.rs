@@ -8,23 +8,26 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use ethers::abi::AbiEncode;
 use ethers::prelude::Middleware;
+use ethers::types::Bytes;
 use ethers_contract::builders::ContractCall;
 use hyperlane_core::accumulator::incremental::IncrementalMerkle;
 use hyperlane_core::accumulator::TREE_DEPTH;
-use tracing::instrument;
+use prometheus::IntCounter;
+use tracing::{instrument, warn};
 
 use hyperlane_core::{
-    utils::fmt_bytes, ChainCommunicationError, ChainResult, Checkpoint, ContractLocator,
+    utils::fmt_bytes, BatchItem, ChainCommunicationError, ChainResult, Checkpoint, ContractLocator,
     HyperlaneAbi, HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneMessage,
-    HyperlaneProtocolError, HyperlaneProvider, Indexer, LogMeta, Mailbox, MessageIndexer,
-    RawHyperlaneMessage, TxCostEstimate, TxOutcome, H160, H256, U256,
+    HyperlaneProtocolError, HyperlaneProvider, Indexer, L2FeeModel, LogMeta, Mailbox,
+    MessageIndexer, RawHyperlaneMessage, TxCostEstimate, TxOutcome, H160, H256, U256,
 };
 
 use crate::contracts::arbitrum_node_interface::ArbitrumNodeInterface;
 use crate::contracts::i_mailbox::{IMailbox as EthereumMailboxInternal, ProcessCall, IMAILBOX_ABI};
+use crate::contracts::i_multicall::IMulticall;
 use crate::trait_builder::BuildableWithProvider;
-use crate::tx::{fill_tx_gas_params, report_tx};
-use crate::EthereumProvider;
+use crate::tx::{fill_tx_gas_params, TransactionManager};
+use crate::{EthereumProvider, TransactionReplacementConf};
 
 /// derived from `forge inspect Mailbox storage --pretty`
 const MERKLE_TREE_CONTRACT_SLOT: u32 = 152;
@@ -40,6 +43,8 @@ where
 
 pub struct MessageIndexerBuilder {
     pub finality_blocks: u32,
+    /// Counts dispatch logs skipped because their message is malformed
+    pub malformed_messages: IntCounter,
 }
 
 #[async_trait]
@@ -51,11 +56,10 @@ impl BuildableWithProvider for MessageIndexerBuilder {
         provider: M,
         locator: &ContractLocator,
     ) -> Self::Output {
-        Box::new(EthereumMailboxIndexer::new(
-            Arc::new(provider),
-            locator,
-            self.finality_blocks,
-        ))
+        Box::new(
+            EthereumMailboxIndexer::new(Arc::new(provider), locator, self.finality_blocks)
+                .with_malformed_message_counter(self.malformed_messages.clone()),
+        )
     }
 }
 
@@ -89,6 +93,7 @@ where
     contract: Arc<EthereumMailboxInternal<M>>,
     provider: Arc<M>,
     finality_blocks: u32,
+    malformed_messages: Option<IntCounter>,
 }
 
 impl<M> EthereumMailboxIndexer<M>
@@ -105,6 +110,16 @@ where
             contract,
             provider,
             finality_blocks,
+            malformed_messages: None,
+        }
+    }
+
+    /// Count the dispatch logs which are skipped because their message is
+    /// malformed.
+    pub fn with_malformed_message_counter(self, counter: IntCounter) -> Self {
+        Self {
+            malformed_messages: Some(counter),
+            ..self
         }
     }
 
@@ -135,16 +150,33 @@ where
         from: u32,
         to: u32,
     ) -> ChainResult<Vec<(HyperlaneMessage, LogMeta)>> {
-        let mut events: Vec<(HyperlaneMessage, LogMeta)> = self
+        let logs = self
             .contract
             .dispatch_filter()
             .from_block(from)
             .to_block(to)
             .query_with_meta()
-            .await?
-            .into_iter()
-            .map(|(event, meta)| (HyperlaneMessage::from(event.message.to_vec()), meta.into()))
-            .collect();
+            .await?;
+
+        let mut events = Vec::with_capacity(logs.len());
+        for (event, meta) in logs {
+            let meta = LogMeta::from(meta);
+            match HyperlaneMessage::try_from(event.message.to_vec()) {
+                Ok(message) => events.push((message, meta)),
+                // Failing the range on a log we can never decode would stall
+                // indexing forever, so skip it instead.
+                Err(error) => {
+                    warn!(
+                        ?error,
+                        ?meta,
+                        "Skipping dispatch log with a malformed message"
+                    );
+                    if let Some(counter) = &self.malformed_messages {
+                        counter.inc();
+                    }
+                }
+            }
+        }
 
         events.sort_by(|a, b| a.0.nonce.cmp(&b.0.nonce));
         Ok(events)
@@ -189,7 +221,12 @@ where
             .collect())
     }
 }
-pub struct MailboxBuilder {}
+pub struct MailboxBuilder {
+    /// Address of a multicall contract used to process batches of messages
+    pub multicall: Option<H256>,
+    /// How to replace transactions which are not included in time
+    pub transaction_replacement: TransactionReplacementConf,
+}
 
 #[async_trait]
 impl BuildableWithProvider for MailboxBuilder {
@@ -200,7 +237,12 @@ impl BuildableWithProvider for MailboxBuilder {
         provider: M,
         locator: &ContractLocator,
     ) -> Self::Output {
-        Box::new(EthereumMailbox::new(Arc::new(provider), locator))
+        let mailbox = EthereumMailbox::new(Arc::new(provider), locator)
+            .with_transaction_replacement(self.transaction_replacement.clone());
+        Box::new(match self.multicall {
+            Some(multicall) => mailbox.with_multicall(multicall),
+            None => mailbox,
+        })
     }
 }
 
@@ -214,6 +256,8 @@ where
     domain: HyperlaneDomain,
     provider: Arc<M>,
     arbitrum_node_interface: Option<Arc<ArbitrumNodeInterface<M>>>,
+    multicall: Option<Arc<IMulticall<M>>>,
+    tx_manager: TransactionManager<M>,
 }
 
 impl<M> EthereumMailbox<M>
@@ -227,12 +271,13 @@ where
         // The gas amount that eth_estimateGas returns considers both L1 and L2 gas costs.
         // We use the NodeInterface, found at address(0xC8), to isolate the L2 gas costs.
         // See https://developer.arbitrum.io/arbos/gas#nodeinterfacesol or https://github.com/OffchainLabs/nitro/blob/master/contracts/src/node-interface/NodeInterface.sol#L25
-        let arbitrum_node_interface = locator.domain.is_arbitrum_nitro().then(|| {
-            Arc::new(ArbitrumNodeInterface::new(
-                H160::from_low_u64_be(0xC8),
-                provider.clone(),
-            ))
-        });
+        let arbitrum_node_interface =
+            (locator.domain.chain_traits().l2_fee_model == L2FeeModel::ArbitrumNitro).then(|| {
+                Arc::new(ArbitrumNodeInterface::new(
+                    H160::from_low_u64_be(0xC8),
+                    provider.clone(),
+                ))
+            });
 
         Self {
             contract: Arc::new(EthereumMailboxInternal::new(
@@ -240,11 +285,31 @@ where
                 provider.clone(),
             )),
             domain: locator.domain.clone(),
+            tx_manager: TransactionManager::new(
+                provider.clone(),
+                Default::default(),
+                &locator.domain,
+            ),
             provider,
             arbitrum_node_interface,
+            multicall: None,
         }
     }
 
+    /// Replace transactions which are not included in time according to the
+    /// given configuration.
+    pub fn with_transaction_replacement(mut self, conf: TransactionReplacementConf) -> Self {
+        self.tx_manager = TransactionManager::new(self.provider.clone(), conf, &self.domain);
+        self
+    }
+
+    /// Use a multicall contract at the given address to process batches of
+    /// messages in a single transaction.
+    pub fn with_multicall(mut self, address: H256) -> Self {
+        self.multicall = Some(Arc::new(IMulticall::new(address, self.provider.clone())));
+        self
+    }
+
     /// Returns a ContractCall that processes the provided message.
     /// If the provided tx_gas_limit is None, gas estimation occurs.
     async fn process_contract_call(
@@ -257,7 +322,32 @@ where
             metadata.to_vec().into(),
             RawHyperlaneMessage::from(message).to_vec().into(),
         );
-        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), message.destination).await
+        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), &self.domain).await
+    }
+
+    /// Returns a ContractCall that processes the provided batch of messages
+    /// through the multicall contract. If `allow_failure` is false the whole
+    /// call reverts when any of the messages fails to be processed.
+    fn process_batch_contract_call(
+        &self,
+        batch: &[BatchItem],
+        allow_failure: bool,
+    ) -> ChainResult<ContractCall<M, Vec<(bool, Bytes)>>> {
+        let multicall = self
+            .multicall
+            .as_ref()
+            .ok_or(HyperlaneProtocolError::BatchingNotSupported)?;
+        let calls = batch
+            .iter()
+            .map(|item| {
+                (
+                    self.contract.address(),
+                    allow_failure,
+                    self.process_calldata(&item.message, &item.metadata).into(),
+                )
+            })
+            .collect();
+        Ok(multicall.aggregate_3(calls))
     }
 }
 
@@ -424,7 +514,7 @@ where
         let contract_call = self
             .process_contract_call(message, metadata, tx_gas_limit)
             .await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt = self.tx_manager.send(contract_call).await?;
         Ok(receipt.into())
     }
 
@@ -483,6 +573,36 @@ where
 
         AbiEncode::encode(process_call)
     }
+
+    fn supports_batching(&self) -> bool {
+        self.multicall.is_some()
+    }
+
+    #[instrument(skip(self, batch), fields(batch_size=batch.len()))]
+    async fn simulate_process_batch(&self, batch: &[BatchItem]) -> ChainResult<Vec<bool>> {
+        let results = self
+            .process_batch_contract_call(batch, true)?
+            .call()
+            .await?;
+        Ok(results.into_iter().map(|(success, _)| success).collect())
+    }
+
+    #[instrument(skip(self, batch), fields(batch_size=batch.len()))]
+    async fn process_batch(
+        &self,
+        batch: &[BatchItem],
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome> {
+        let contract_call = fill_tx_gas_params(
+            self.process_batch_contract_call(batch, false)?,
+            tx_gas_limit,
+            self.provider.clone(),
+            &self.domain,
+        )
+        .await?;
+        let receipt = self.tx_manager.send(contract_call).await?;
+        Ok(receipt.into())
+    }
 }
 
 pub struct EthereumMailboxAbi;
@@ -500,29 +620,91 @@ mod test {
     use std::{str::FromStr, sync::Arc};
 
     use ethers::{
+        abi::{encode, Token},
+        contract::EthEvent,
         providers::{MockProvider, Provider},
-        types::{Block, Transaction},
+        types::{Block, Log, Transaction, U64},
     };
     use hyperlane_core::{
-        ContractLocator, HyperlaneDomain, HyperlaneMessage, KnownHyperlaneDomain, Mailbox,
-        TxCostEstimate, H160, H256, U256,
+        ContractLocator, DomainRegistry, Encode, HyperlaneDomain, HyperlaneMessage, Indexer,
+        Mailbox, TxCostEstimate, H160, H256, U256,
     };
+    use prometheus::IntCounter;
 
-    use crate::EthereumMailbox;
+    use crate::contracts::i_mailbox::DispatchFilter;
+    use crate::{EthereumMailbox, EthereumMailboxIndexer};
 
     /// An amount of gas to add to the estimated gas
     const GAS_ESTIMATE_BUFFER: u32 = 50000;
 
+    fn dispatch_log(message: Vec<u8>, log_index: u64) -> Log {
+        Log {
+            topics: vec![
+                DispatchFilter::signature(),
+                H256::zero(),
+                H256::zero(),
+                H256::zero(),
+            ],
+            data: encode(&[Token::Bytes(message)]).into(),
+            block_hash: Some(H256::zero()),
+            block_number: Some(U64::one()),
+            transaction_hash: Some(H256::zero()),
+            transaction_index: Some(U64::zero()),
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_skips_malformed_messages() {
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Arc::new(Provider::new(mock_provider.clone()));
+        let domain = HyperlaneDomain::new_test_domain("test");
+        let malformed_messages = IntCounter::new("malformed", "malformed").unwrap();
+        let indexer = EthereumMailboxIndexer::new(
+            provider,
+            &ContractLocator {
+                domain: &domain,
+                address: H256::default(),
+            },
+            0,
+        )
+        .with_malformed_message_counter(malformed_messages.clone());
+
+        let message = HyperlaneMessage {
+            nonce: 7,
+            ..Default::default()
+        };
+        // RPC 1: eth_getLogs by fetch_logs, with a message too short to decode
+        // ahead of a valid one
+        mock_provider
+            .push(vec![
+                dispatch_log(vec![3; 10], 0),
+                dispatch_log(message.to_vec(), 1),
+            ])
+            .unwrap();
+
+        let logs = Indexer::<HyperlaneMessage>::fetch_logs(&indexer, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].0.id(), message.id());
+        assert_eq!(logs[0].1.log_index, 1.into());
+        assert_eq!(malformed_messages.get(), 1);
+    }
+
     #[tokio::test]
     async fn test_process_estimate_costs_sets_l2_gas_limit_for_arbitrum() {
         let mock_provider = Arc::new(MockProvider::new());
         let provider = Arc::new(Provider::new(mock_provider.clone()));
 
+        // An Arbitrum Nitro chain
+        let domain =
+            HyperlaneDomain::Known(DomainRegistry::global().by_name("arbitrumgoerli").unwrap());
         let mailbox = EthereumMailbox::new(
             provider.clone(),
             &ContractLocator {
-                // An Arbitrum Nitro chain
-                domain: &HyperlaneDomain::Known(KnownHyperlaneDomain::ArbitrumGoerli),
+                domain: &domain,
                 // Address doesn't matter because we're using a MockProvider
                 address: H256::default(),
             },
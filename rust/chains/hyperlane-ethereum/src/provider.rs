@@ -62,6 +62,33 @@ where
         })
     }
 
+    #[instrument(err, skip(self))]
+    async fn get_block_by_number(&self, number: u64) -> ChainResult<BlockInfo> {
+        let block = self
+            .provider
+            .get_block(number)
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .ok_or(HyperlaneProviderError::CouldNotFindBlockByNumber(number))?;
+        Ok(BlockInfo {
+            hash: block
+                .hash
+                .ok_or(HyperlaneProviderError::CouldNotFindBlockByNumber(number))?,
+            timestamp: block.timestamp.as_u64(),
+            number,
+        })
+    }
+
+    #[instrument(err, skip(self))]
+    async fn get_block_number(&self) -> ChainResult<u64> {
+        let number = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(number.as_u64())
+    }
+
     #[instrument(err, skip(self))]
     async fn get_txn_by_hash(&self, hash: &H256) -> ChainResult<TxnInfo> {
         let txn = get_with_retry_on_none(hash, |h| self.provider.get_transaction(*h)).await?;
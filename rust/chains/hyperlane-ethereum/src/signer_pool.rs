@@ -0,0 +1,626 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ethers::middleware::signer::SignerMiddlewareError;
+use ethers::prelude::{
+    Address, BlockId, BlockNumber, Middleware, NonceManagerMiddleware, PendingTransaction,
+    Signature, SignerMiddleware,
+};
+use ethers::providers::MiddlewareError;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers_signers::Signer;
+use prometheus::IntGaugeVec;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use hyperlane_core::config::*;
+use hyperlane_core::U256;
+
+use crate::Signers;
+
+/// How long the balance and nonces of the signers are cached for.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Configuration for choosing between the signers of a chain when there are
+/// several.
+#[derive(Debug, Clone)]
+pub struct SignerPoolConf {
+    /// Signers with a native token balance below this, in wei, are not used
+    /// for new transactions.
+    pub min_balance: U256,
+    /// Signers whose oldest pending transaction has not been included for
+    /// this long are not used for new transactions.
+    pub stuck_after: Duration,
+}
+
+impl Default for SignerPoolConf {
+    fn default() -> Self {
+        Self {
+            min_balance: U256::zero(),
+            stuck_after: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Raw configuration for choosing between signers
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawSignerPoolConf {
+    /// Min native token balance in wei of a signer to use it
+    min_balance: Option<StrOrInt>,
+    /// Seconds after which a signer's pending transaction is considered stuck
+    stuck_after: Option<StrOrInt>,
+}
+
+impl FromRawConf<'_, RawSignerPoolConf> for SignerPoolConf {
+    fn from_config_filtered(
+        raw: RawSignerPoolConf,
+        cwp: &ConfigPath,
+        _filter: (),
+    ) -> ConfigResult<Self> {
+        let mut err = ConfigParsingError::default();
+        let default = Self::default();
+
+        let min_balance = raw
+            .min_balance
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "minBalance"))
+            .unwrap_or(default.min_balance);
+
+        let stuck_after = raw
+            .stuck_after
+            .and_then(|v| v.try_into().take_err(&mut err, || cwp + "stuckAfter"))
+            .map(Duration::from_secs)
+            .unwrap_or(default.stuck_after);
+
+        err.into_result()?;
+        Ok(Self {
+            min_balance,
+            stuck_after,
+        })
+    }
+}
+
+/// Metrics for the signers in a pool.
+#[derive(Debug, Clone)]
+pub struct SignerPoolMetrics {
+    /// Nonce of the latest included transaction of each signer.
+    ///
+    /// Labels:
+    /// - `chain`: Chain the signer sends transactions on.
+    /// - `wallet_address`: Address of the signer.
+    pub nonce: IntGaugeVec,
+    /// Transactions sent by each signer which have not been included yet.
+    ///
+    /// Labels:
+    /// - `chain`: Chain the signer sends transactions on.
+    /// - `wallet_address`: Address of the signer.
+    pub in_flight: IntGaugeVec,
+    /// Whether each signer is used for new transactions, i.e. has enough
+    /// balance and no stuck transactions.
+    ///
+    /// Labels:
+    /// - `chain`: Chain the signer sends transactions on.
+    /// - `wallet_address`: Address of the signer.
+    pub available: IntGaugeVec,
+}
+
+/// The signers a provider sends transactions from.
+#[derive(Debug, Clone)]
+pub struct SignerPool {
+    /// The signers, of which there must be at least one
+    pub signers: Vec<Signers>,
+    /// How to choose between the signers
+    pub conf: SignerPoolConf,
+    /// Metrics for the signers, only used if there are several
+    pub metrics: Option<SignerPoolMetrics>,
+}
+
+/// Errors of a [SignerPoolMiddleware].
+#[derive(Debug, thiserror::Error)]
+pub enum SignerPoolError<M: Middleware> {
+    /// Error from the inner middleware
+    #[error(transparent)]
+    MiddlewareError(M::Error),
+    /// Error from the signing middleware of one of the signers
+    #[error(transparent)]
+    SignerMiddlewareError(SignerMiddlewareError<NonceManagerMiddleware<M>, Signers>),
+    /// No signer has enough balance and no stuck transactions
+    #[error("None of the signers in the pool are available")]
+    NoAvailableSigner,
+    /// A transaction is from an address which is not in the pool
+    #[error("{0:?} is not one of the signers in the pool")]
+    UnknownSigner(Address),
+}
+
+impl<M: Middleware> MiddlewareError for SignerPoolError<M> {
+    type Inner = M::Error;
+
+    fn from_err(e: Self::Inner) -> Self {
+        Self::MiddlewareError(e)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            Self::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// What is known about a signer from the chain.
+#[derive(Debug, Clone)]
+struct SignerState {
+    address: Address,
+    balance: U256,
+    /// Nonce of the next transaction to be included.
+    latest_nonce: U256,
+    /// Transactions sent which have not been included yet, as counted by
+    /// the node when last refreshed plus those counted locally since.
+    in_flight: u64,
+    /// When the latest nonce last increased or there was nothing in flight.
+    last_progress: Instant,
+    refreshed_at: Option<Instant>,
+}
+
+/// Balance and nonces of a signer as fetched from the chain.
+#[derive(Debug, Clone, Copy)]
+struct FetchedSignerState {
+    balance: U256,
+    latest_nonce: U256,
+    pending_nonce: U256,
+}
+
+impl SignerState {
+    fn is_available(&self, conf: &SignerPoolConf, now: Instant) -> bool {
+        let is_stuck =
+            self.in_flight > 0 && now.duration_since(self.last_progress) > conf.stuck_after;
+        self.balance >= conf.min_balance && !is_stuck
+    }
+
+    /// Update the state with what was fetched from the chain. The node's
+    /// count of pending transactions replaces the local one, so transactions
+    /// which were counted when a signer was selected for them but never
+    /// broadcast, e.g. because sending them failed, are not counted forever.
+    fn update(&mut self, fetched: FetchedSignerState, now: Instant) {
+        let in_flight = fetched
+            .pending_nonce
+            .saturating_sub(fetched.latest_nonce)
+            .as_u64();
+        if fetched.latest_nonce > self.latest_nonce || in_flight == 0 {
+            self.last_progress = now;
+        }
+        self.balance = fetched.balance;
+        self.latest_nonce = fetched.latest_nonce;
+        self.in_flight = in_flight;
+        self.refreshed_at = Some(now);
+    }
+}
+
+/// Choose the available signer with the fewest transactions in flight,
+/// preferring those with a higher balance.
+fn choose_signer(states: &[SignerState], conf: &SignerPoolConf, now: Instant) -> Option<usize> {
+    states
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.is_available(conf, now))
+        .min_by(|(_, a), (_, b)| {
+            a.in_flight
+                .cmp(&b.in_flight)
+                .then_with(|| b.balance.cmp(&a.balance))
+        })
+        .map(|(i, _)| i)
+}
+
+/// A middleware which spreads the transactions it sends across several
+/// signers, each with its own nonce manager. Signers with too low a balance
+/// or with stuck transactions are skipped.
+///
+/// Transactions are assigned a signer when they are filled or sent without a
+/// `from` address, so there is no default sender. Transactions with a `from`
+/// address, e.g. replacements, are signed by that signer.
+#[derive(Debug)]
+pub struct SignerPoolMiddleware<M: Middleware> {
+    inner: M,
+    chain: String,
+    signers: HashMap<Address, SignerMiddleware<NonceManagerMiddleware<M>, Signers>>,
+    states: Mutex<Vec<SignerState>>,
+    conf: SignerPoolConf,
+    metrics: Option<SignerPoolMetrics>,
+}
+
+impl<M> SignerPoolMiddleware<M>
+where
+    M: Middleware + Clone + 'static,
+{
+    /// Create a middleware sending transactions from the signers in the
+    /// pool on the given chain.
+    pub async fn new(inner: M, pool: SignerPool, chain: &str) -> Result<Self, M::Error> {
+        let chain_id = inner.get_chainid().await?.as_u64();
+        let now = Instant::now();
+        let mut signers = HashMap::new();
+        let mut states = Vec::new();
+        for signer in pool.signers {
+            let signer = signer.with_chain_id(chain_id);
+            let address = signer.address();
+            let provider = NonceManagerMiddleware::new(inner.clone(), address);
+            signers.insert(address, SignerMiddleware::new(provider, signer));
+            states.push(SignerState {
+                address,
+                balance: U256::zero(),
+                latest_nonce: U256::zero(),
+                in_flight: 0,
+                last_progress: now,
+                refreshed_at: None,
+            });
+        }
+        Ok(Self {
+            inner,
+            chain: chain.to_owned(),
+            signers,
+            states: Mutex::new(states),
+            conf: pool.conf,
+            metrics: pool.metrics,
+        })
+    }
+
+    fn signer(
+        &self,
+        address: Address,
+    ) -> Result<&SignerMiddleware<NonceManagerMiddleware<M>, Signers>, SignerPoolError<M>> {
+        self.signers
+            .get(&address)
+            .ok_or(SignerPoolError::UnknownSigner(address))
+    }
+
+    /// Choose the signer for a new transaction and count the transaction as
+    /// in flight for it until the signer is next refreshed.
+    async fn select(&self) -> Result<Address, SignerPoolError<M>> {
+        self.refresh_stale().await;
+        let mut states = self.states.lock().await;
+        let now = Instant::now();
+        let Some(i) = choose_signer(&states, &self.conf, now) else {
+            warn!(chain = self.chain, ?states, "No signer is available");
+            return Err(SignerPoolError::NoAvailableSigner);
+        };
+        let state = &mut states[i];
+        state.in_flight += 1;
+        self.update_metrics(state, now);
+        debug!(signer=?state.address, in_flight=state.in_flight, "Selected signer");
+        Ok(state.address)
+    }
+
+    /// Stop counting a transaction as in flight for a signer, e.g. because
+    /// it could not be sent.
+    async fn release(&self, address: Address) {
+        let mut states = self.states.lock().await;
+        if let Some(state) = states.iter_mut().find(|s| s.address == address) {
+            state.in_flight = state.in_flight.saturating_sub(1);
+            self.update_metrics(state, Instant::now());
+        }
+    }
+
+    /// Refresh the signers whose state is older than the refresh interval.
+    /// The states are not locked while querying the chain so other
+    /// selections are not blocked by it.
+    async fn refresh_stale(&self) {
+        let now = Instant::now();
+        let stale: Vec<(Address, Option<Instant>)> = {
+            let mut states = self.states.lock().await;
+            states
+                .iter_mut()
+                .filter(|s| {
+                    s.refreshed_at
+                        .map_or(true, |at| now.duration_since(at) > REFRESH_INTERVAL)
+                })
+                // Mark the signer as refreshed so concurrent selections don't
+                // refresh it as well
+                .map(|s| (s.address, s.refreshed_at.replace(now)))
+                .collect()
+        };
+        for (address, refreshed_at) in stale {
+            let fetched = self.fetch(address).await;
+            let mut states = self.states.lock().await;
+            let Some(state) = states.iter_mut().find(|s| s.address == address) else {
+                continue;
+            };
+            match fetched {
+                Ok(fetched) => {
+                    let now = Instant::now();
+                    state.update(fetched, now);
+                    self.update_metrics(state, now);
+                }
+                Err(e) => {
+                    warn!(signer=?address, error=?e, "Failed to refresh signer state");
+                    state.refreshed_at = refreshed_at;
+                }
+            }
+        }
+    }
+
+    async fn fetch(&self, address: Address) -> Result<FetchedSignerState, M::Error> {
+        let balance = self.inner.get_balance(address, None).await?;
+        let latest_nonce = self
+            .inner
+            .get_transaction_count(address, Some(BlockNumber::Latest.into()))
+            .await?;
+        let pending_nonce = self
+            .inner
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await?;
+        Ok(FetchedSignerState {
+            balance,
+            latest_nonce,
+            pending_nonce,
+        })
+    }
+
+    fn update_metrics(&self, state: &SignerState, now: Instant) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let address = format!("{:?}", state.address);
+        let labels = [self.chain.as_str(), address.as_str()];
+        metrics
+            .nonce
+            .with_label_values(&labels)
+            .set(state.latest_nonce.as_u64() as i64);
+        metrics
+            .in_flight
+            .with_label_values(&labels)
+            .set(state.in_flight as i64);
+        metrics
+            .available
+            .with_label_values(&labels)
+            .set(state.is_available(&self.conf, now) as i64);
+    }
+}
+
+#[async_trait]
+impl<M> Middleware for SignerPoolMiddleware<M>
+where
+    M: Middleware + Clone + 'static,
+{
+    type Error = SignerPoolError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    fn default_sender(&self) -> Option<Address> {
+        None
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let (from, selected) = match tx.from() {
+            Some(from) => (*from, false),
+            None => {
+                let from = self.select().await?;
+                tx.set_from(from);
+                (from, true)
+            }
+        };
+        let result = self.signer(from)?.fill_transaction(tx, block).await;
+        if result.is_err() && selected {
+            self.release(from).await;
+        }
+        result.map_err(SignerPoolError::SignerMiddlewareError)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        let (from, selected) = match tx.from() {
+            Some(from) => (*from, false),
+            None => {
+                let from = self.select().await?;
+                tx.set_from(from);
+                (from, true)
+            }
+        };
+        let result = self.signer(from)?.send_transaction(tx, block).await;
+        if result.is_err() && selected {
+            self.release(from).await;
+        }
+        result.map_err(SignerPoolError::SignerMiddlewareError)
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+        from: Address,
+    ) -> Result<Signature, Self::Error> {
+        self.signer(from)?
+            .sign_transaction(tx, from)
+            .await
+            .map_err(SignerPoolError::SignerMiddlewareError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use ethers::prelude::{LocalWallet, TransactionRequest};
+    use ethers::providers::{MockProvider, Provider};
+
+    use super::*;
+
+    fn state(balance: u64, in_flight: u64, last_progress: Instant) -> SignerState {
+        SignerState {
+            address: Address::random(),
+            balance: balance.into(),
+            latest_nonce: U256::zero(),
+            in_flight,
+            last_progress,
+            refreshed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_choose_signer_prefers_fewest_in_flight() {
+        let now = Instant::now();
+        let conf = SignerPoolConf::default();
+        let states = [state(100, 2, now), state(10, 1, now), state(50, 1, now)];
+        assert_eq!(choose_signer(&states, &conf, now), Some(2));
+    }
+
+    #[test]
+    fn test_choose_signer_skips_low_balance_and_stuck() {
+        let now = Instant::now();
+        let conf = SignerPoolConf {
+            min_balance: 20.into(),
+            stuck_after: Duration::from_secs(60),
+        };
+        let long_ago = now - Duration::from_secs(120);
+        let states = [
+            state(10, 0, now),
+            state(100, 1, long_ago),
+            state(30, 3, now),
+        ];
+        assert_eq!(choose_signer(&states, &conf, now), Some(2));
+
+        // A signer with nothing in flight is never stuck
+        let states = [state(100, 0, long_ago)];
+        assert_eq!(choose_signer(&states, &conf, now), Some(0));
+
+        let states = [state(10, 0, now), state(100, 1, long_ago)];
+        assert_eq!(choose_signer(&states, &conf, now), None);
+    }
+
+    fn signer(key: char) -> Signers {
+        key.to_string()
+            .repeat(64)
+            .parse::<LocalWallet>()
+            .unwrap()
+            .into()
+    }
+
+    /// Queue the responses to refreshing a signer. The MockProvider processes
+    /// responses in LIFO order, so signers refreshed later must be pushed
+    /// first.
+    fn push_fetched(mock: &MockProvider, balance: u64, latest_nonce: u64, pending_nonce: u64) {
+        mock.push(U256::from(pending_nonce)).unwrap();
+        mock.push(U256::from(latest_nonce)).unwrap();
+        mock.push(U256::from(balance)).unwrap();
+    }
+
+    async fn in_flight<M: Middleware>(middleware: &SignerPoolMiddleware<M>) -> Vec<u64> {
+        let states = middleware.states.lock().await;
+        states.iter().map(|s| s.in_flight).collect()
+    }
+
+    async fn expire_states<M: Middleware>(middleware: &SignerPoolMiddleware<M>) {
+        for state in middleware.states.lock().await.iter_mut() {
+            state.refreshed_at = None;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_spreads_and_counts_transactions() {
+        let mock = Arc::new(MockProvider::new());
+        let provider = Provider::new(mock.clone());
+        let signers = vec![signer('1'), signer('2')];
+        let addresses: Vec<Address> = signers.iter().map(|s| s.address()).collect();
+        let pool = SignerPool {
+            signers,
+            conf: SignerPoolConf::default(),
+            metrics: None,
+        };
+        mock.push(U256::from(1)).unwrap();
+        let middleware = SignerPoolMiddleware::new(provider, pool, "test")
+            .await
+            .unwrap();
+
+        // Both signers are refreshed by the first selection, after which
+        // the transactions alternate between them
+        push_fetched(&mock, 100, 5, 5);
+        push_fetched(&mock, 100, 5, 5);
+        assert_eq!(middleware.select().await.unwrap(), addresses[0]);
+        assert_eq!(middleware.select().await.unwrap(), addresses[1]);
+        assert_eq!(middleware.select().await.unwrap(), addresses[0]);
+        assert_eq!(in_flight(&middleware).await, vec![2, 1]);
+
+        // On refresh the node's count of pending transactions replaces the
+        // local one
+        expire_states(&middleware).await;
+        push_fetched(&mock, 100, 5, 5);
+        push_fetched(&mock, 100, 6, 7);
+        assert_eq!(middleware.select().await.unwrap(), addresses[1]);
+        assert_eq!(in_flight(&middleware).await, vec![1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_forgets_transactions_which_failed_to_send() {
+        let mock = Arc::new(MockProvider::new());
+        let provider = Provider::new(mock.clone());
+        let pool = SignerPool {
+            signers: vec![signer('1')],
+            conf: SignerPoolConf {
+                stuck_after: Duration::ZERO,
+                ..Default::default()
+            },
+            metrics: None,
+        };
+        mock.push(U256::from(1)).unwrap();
+        let middleware = SignerPoolMiddleware::new(provider, pool, "test")
+            .await
+            .unwrap();
+
+        // The transaction manager selects the signer when filling the
+        // transaction and sends it from that signer, so the pool can't tell
+        // the broadcast failed, which it does as there are no responses left
+        push_fetched(&mock, 100, 5, 5);
+        let from = middleware.select().await.unwrap();
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(from)
+            .to(Address::zero())
+            .into();
+        assert!(middleware.send_transaction(tx, None).await.is_err());
+        assert_eq!(in_flight(&middleware).await, vec![1]);
+
+        // Counting the transaction would make the signer look stuck forever,
+        // but the node doesn't know about it so it is forgotten on refresh
+        expire_states(&middleware).await;
+        push_fetched(&mock, 100, 5, 5);
+        assert_eq!(middleware.select().await.unwrap(), from);
+        assert_eq!(in_flight(&middleware).await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_skips_signers_without_balance() {
+        let mock = Arc::new(MockProvider::new());
+        let provider = Provider::new(mock.clone());
+        let signers = vec![signer('1'), signer('2')];
+        let addresses: Vec<Address> = signers.iter().map(|s| s.address()).collect();
+        let pool = SignerPool {
+            signers,
+            conf: SignerPoolConf {
+                min_balance: 50.into(),
+                ..Default::default()
+            },
+            metrics: None,
+        };
+        mock.push(U256::from(1)).unwrap();
+        let middleware = SignerPoolMiddleware::new(provider, pool, "test")
+            .await
+            .unwrap();
+
+        push_fetched(&mock, 100, 0, 0);
+        push_fetched(&mock, 10, 0, 0);
+        assert_eq!(middleware.select().await.unwrap(), addresses[1]);
+        assert_eq!(middleware.select().await.unwrap(), addresses[1]);
+        assert_eq!(in_flight(&middleware).await, vec![0, 2]);
+    }
+}
@@ -19,7 +19,10 @@ use ethers_prometheus::middleware::{
 };
 use hyperlane_core::{ChainCommunicationError, ChainResult, ContractLocator};
 
-use crate::{signers::Signers, ConnectionConf, FallbackProvider, RetryingProvider};
+use crate::{
+    signers::Signers, ConnectionConf, FallbackProvider, RetryingProvider, SignerPool,
+    SignerPoolMiddleware,
+};
 
 // This should be whatever the prometheus scrape interval is
 const METRICS_SCRAPE_INTERVAL: Duration = Duration::from_secs(60);
@@ -55,7 +58,7 @@ pub trait BuildableWithProvider {
         &self,
         conn: &ConnectionConf,
         locator: &ContractLocator,
-        signer: Option<Signers>,
+        signers: Option<SignerPool>,
         rpc_metrics: Option<JsonRpcClientMetrics>,
         middleware_metrics: Option<(MiddlewareMetrics, PrometheusMiddlewareConf)>,
     ) -> ChainResult<Self::Output> {
@@ -89,7 +92,7 @@ pub trait BuildableWithProvider {
                     builder = builder.add_provider(weighted_provider);
                 }
                 let quorum_provider = builder.build();
-                self.wrap_with_metrics(quorum_provider, locator, signer, middleware_metrics)
+                self.wrap_with_metrics(quorum_provider, locator, signers, middleware_metrics)
                     .await?
             }
             ConnectionConf::HttpFallback { urls } => {
@@ -109,7 +112,7 @@ pub trait BuildableWithProvider {
                     builder = builder.add_provider(metrics_provider);
                 }
                 let fallback_provider = builder.build();
-                self.wrap_with_metrics(fallback_provider, locator, signer, middleware_metrics)
+                self.wrap_with_metrics(fallback_provider, locator, signers, middleware_metrics)
                     .await?
             }
             ConnectionConf::Http { url } => {
@@ -125,14 +128,14 @@ pub trait BuildableWithProvider {
                     &middleware_metrics,
                 );
                 let retrying_http_provider = RetryingProvider::new(metrics_provider, None, None);
-                self.wrap_with_metrics(retrying_http_provider, locator, signer, middleware_metrics)
+                self.wrap_with_metrics(retrying_http_provider, locator, signers, middleware_metrics)
                     .await?
             }
             ConnectionConf::Ws { url } => {
                 let ws = Ws::connect(url)
                     .await
                     .map_err(EthereumProviderConnectionError::from)?;
-                self.wrap_with_metrics(ws, locator, signer, middleware_metrics)
+                self.wrap_with_metrics(ws, locator, signers, middleware_metrics)
                     .await?
             }
         })
@@ -180,7 +183,7 @@ pub trait BuildableWithProvider {
         &self,
         client: P,
         locator: &ContractLocator,
-        signer: Option<Signers>,
+        signers: Option<SignerPool>,
         metrics: Option<(MiddlewareMetrics, PrometheusMiddlewareConf)>,
     ) -> ChainResult<Self::Output>
     where
@@ -190,32 +193,41 @@ pub trait BuildableWithProvider {
         Ok(if let Some(metrics) = metrics {
             let provider = Arc::new(PrometheusMiddleware::new(provider, metrics.0, metrics.1));
             tokio::spawn(provider.start_updating_on_interval(METRICS_SCRAPE_INTERVAL));
-            self.wrap_with_signer(provider, locator, signer).await?
+            self.wrap_with_signer(provider, locator, signers).await?
         } else {
-            self.wrap_with_signer(provider, locator, signer).await?
+            self.wrap_with_signer(provider, locator, signers).await?
         })
     }
 
     /// Wrap the provider creation with a signing provider if signers were
-    /// provided; this is the third step.
+    /// provided; this is the third step. Transactions are spread across the
+    /// signers if there are several.
     async fn wrap_with_signer<M>(
         &self,
         provider: M,
         locator: &ContractLocator,
-        signer: Option<Signers>,
+        signers: Option<SignerPool>,
     ) -> ChainResult<Self::Output>
     where
         M: Middleware + 'static,
     {
-        Ok(if let Some(signer) = signer {
-            let signing_provider = build_signing_provider(provider, signer)
-                .await
-                .map_err(ChainCommunicationError::from_other)?;
-            self.build_with_provider(signing_provider, locator)
-        } else {
-            self.build_with_provider(provider, locator)
-        }
-        .await)
+        Ok(match signers.filter(|pool| !pool.signers.is_empty()) {
+            Some(pool) if pool.signers.len() > 1 => {
+                let signing_provider =
+                    SignerPoolMiddleware::new(Arc::new(provider), pool, locator.domain.name())
+                        .await
+                        .map_err(ChainCommunicationError::from_other)?;
+                self.build_with_provider(signing_provider, locator).await
+            }
+            Some(mut pool) => {
+                let signer = pool.signers.remove(0);
+                let signing_provider = build_signing_provider(provider, signer)
+                    .await
+                    .map_err(ChainCommunicationError::from_other)?;
+                self.build_with_provider(signing_provider, locator).await
+            }
+            None => self.build_with_provider(provider, locator).await,
+        })
     }
 
     /// Construct a new instance of the associated trait using a provider.
@@ -1,67 +1,263 @@
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use ethers::abi::Detokenize;
-use ethers::prelude::{NameOrAddress, TransactionReceipt};
+use ethers::prelude::{Lazy, NameOrAddress, TransactionReceipt};
+use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::types::Eip1559TransactionRequest;
 use ethers_contract::builders::ContractCall;
-use tracing::{error, info};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
 
 use hyperlane_core::utils::fmt_bytes;
-use hyperlane_core::{ChainCommunicationError, ChainResult, KnownHyperlaneDomain, H256, U256};
+use hyperlane_core::{ChainCommunicationError, ChainResult, HyperlaneDomain, H160, H256, U256};
 
-use crate::Middleware;
+use crate::{Middleware, TransactionReplacementConf};
 
 /// An amount of gas to add to the estimated gas
 const GAS_ESTIMATE_BUFFER: u32 = 50000;
 
-/// Dispatches a transaction, logs the tx id, and returns the result
-pub(crate) async fn report_tx<M, D>(tx: ContractCall<M, D>) -> ChainResult<TransactionReceipt>
+/// How often to check whether a dispatched transaction has been included.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of consecutive polls a transaction may be unknown to the node
+/// before it is considered dropped from the mempool.
+const MAX_POLLS_NOT_FOUND: u32 = 3;
+
+/// Hashes of the transactions broadcast for each in-flight nonce of a sender,
+/// oldest first.
+type SenderTransactions = BTreeMap<U256, Vec<H256>>;
+
+/// Transactions in flight by domain id and sender. Shared by the transaction
+/// managers of all contracts, since contracts on the same chain send from the
+/// same signers.
+static IN_FLIGHT: Lazy<Mutex<HashMap<(u32, H160), SenderTransactions>>> =
+    Lazy::new(Default::default);
+
+/// Dispatches transactions and waits for them to be included. Transactions
+/// which are not included in time are replaced by ones with the same nonce
+/// paying higher fees, so that a single underpriced transaction does not
+/// block the signer's nonce.
+#[derive(Debug)]
+pub(crate) struct TransactionManager<M> {
+    provider: Arc<M>,
+    conf: TransactionReplacementConf,
+    /// Domain id of the chain, which scopes the in-flight transactions
+    domain: u32,
+    poll_interval: Duration,
+}
+
+impl<M> TransactionManager<M>
 where
     M: Middleware + 'static,
-    D: Detokenize,
 {
-    let data = tx
-        .tx
-        .data()
-        .map(|b| fmt_bytes(b))
-        .unwrap_or_else(|| "None".into());
+    pub fn new(
+        provider: Arc<M>,
+        conf: TransactionReplacementConf,
+        domain: &HyperlaneDomain,
+    ) -> Self {
+        Self {
+            provider,
+            conf,
+            domain: domain.id(),
+            poll_interval: RECEIPT_POLL_INTERVAL,
+        }
+    }
+
+    /// Dispatches a transaction, logs the tx id, and returns the receipt of
+    /// whichever of the original or its replacements was included.
+    pub async fn send<D>(&self, tx: ContractCall<M, D>) -> ChainResult<TransactionReceipt>
+    where
+        D: Detokenize,
+    {
+        let data = tx
+            .tx
+            .data()
+            .map(|b| fmt_bytes(b))
+            .unwrap_or_else(|| "None".into());
+
+        let to = tx
+            .tx
+            .to()
+            .cloned()
+            .unwrap_or_else(|| NameOrAddress::Address(Default::default()));
+
+        // Fill the transaction first so the nonce is known and can be reused by
+        // replacements.
+        let mut request = tx.tx.clone();
+        self.provider
+            .fill_transaction(&mut request, tx.block)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        let from = request.from().copied().unwrap_or_default();
+        let nonce = request.nonce().copied().unwrap_or_default();
+
+        info!(?to, %data, ?nonce, "Dispatching transaction");
+        let tx_hash = self.broadcast(&request).await?;
+        info!(?to, %data, ?tx_hash, ?nonce, "Dispatched tx");
+
+        self.track(from, nonce, tx_hash);
+        let receipt = self.wait_for_receipt(request, from, nonce).await;
+        self.untrack(from, nonce);
+        receipt
+    }
 
-    let to = tx
-        .tx
-        .to()
-        .cloned()
-        .unwrap_or_else(|| NameOrAddress::Address(Default::default()));
+    async fn wait_for_receipt(
+        &self,
+        mut request: TypedTransaction,
+        from: H160,
+        nonce: U256,
+    ) -> ChainResult<TransactionReceipt> {
+        let mut replacements = 0;
+        let mut polls_not_found = 0;
+        let mut replace_at = Instant::now() + self.conf.interval;
+        loop {
+            sleep(self.poll_interval).await;
 
-    info!(?to, %data, "Dispatching transaction");
-    // We can set the gas higher here!
-    let dispatch_fut = tx.send();
-    let dispatched = dispatch_fut.await?;
+            let tx_hashes = self.tx_hashes(from, nonce);
+            for tx_hash in tx_hashes.iter().rev() {
+                match self.provider.get_transaction_receipt(*tx_hash).await {
+                    Ok(Some(receipt)) => {
+                        info!(?tx_hash, replacements, "confirmed transaction");
+                        return Ok(receipt);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(?tx_hash, error=?e, "Error when fetching transaction receipt"),
+                }
+            }
 
-    let tx_hash: H256 = *dispatched;
+            let latest = *tx_hashes.last().expect("at least one transaction was sent");
+            match self.provider.get_transaction(latest).await {
+                Ok(Some(_)) => polls_not_found = 0,
+                Ok(None) => {
+                    polls_not_found += 1;
+                    if polls_not_found >= MAX_POLLS_NOT_FOUND {
+                        return Err(ChainCommunicationError::TransactionDropped(latest));
+                    }
+                }
+                Err(e) => warn!(tx_hash=?latest, error=?e, "Error when fetching transaction"),
+            }
 
-    info!(?to, %data, ?tx_hash, "Dispatched tx");
+            if Instant::now() < replace_at {
+                continue;
+            }
+            if replacements >= self.conf.max_replacements {
+                error!(?tx_hashes, "waiting for receipt timed out");
+                return Err(ChainCommunicationError::TransactionTimeout());
+            }
+            replacements += 1;
+            replace_at = Instant::now() + self.conf.interval;
 
-    match tokio::time::timeout(Duration::from_secs(300), dispatched).await {
-        // all good
-        Ok(Ok(Some(receipt))) => {
-            info!(?tx_hash, "confirmed transaction");
+            if !self.bump_fees(&mut request).await? {
+                warn!(
+                    tx_hash=?latest,
+                    max_fee_per_gas=?self.conf.max_fee_per_gas,
+                    "Transaction fees are at the ceiling, waiting without replacing it"
+                );
+                continue;
+            }
+            match self.broadcast(&request).await {
+                Ok(tx_hash) => {
+                    info!(?tx_hash, replaced=?latest, ?nonce, replacements, "Dispatched replacement tx");
+                    polls_not_found = 0;
+                    self.track(from, nonce, tx_hash);
+                }
+                // The original may have been included in the meantime, in which
+                // case the replacement is rejected and the receipt shows up on
+                // the next poll.
+                Err(e) => warn!(tx_hash=?latest, error=?e, "Failed to dispatch replacement tx"),
+            }
+        }
+    }
 
-            Ok(receipt)
+    /// Increases the fees of a transaction so that it can replace the one in
+    /// the mempool. Returns false if the fees are already at the ceiling.
+    async fn bump_fees(&self, request: &mut TypedTransaction) -> ChainResult<bool> {
+        let bump = |fee: U256| fee * (100 + self.conf.fee_bump_percent) / 100;
+        if let TypedTransaction::Eip1559(inner) = request {
+            let (max_fee, max_priority_fee) = self
+                .provider
+                .estimate_eip1559_fees(None)
+                .await
+                .map_err(ChainCommunicationError::from_other)?;
+            let Some(max_fee) = self.apply_ceiling(
+                inner.max_fee_per_gas.unwrap_or_default(),
+                bump(inner.max_fee_per_gas.unwrap_or_default()).max(max_fee),
+            ) else {
+                return Ok(false);
+            };
+            let max_priority_fee = bump(inner.max_priority_fee_per_gas.unwrap_or_default())
+                .max(max_priority_fee)
+                .min(max_fee);
+            inner.max_fee_per_gas = Some(max_fee);
+            inner.max_priority_fee_per_gas = Some(max_priority_fee);
+        } else {
+            let gas_price = self
+                .provider
+                .get_gas_price()
+                .await
+                .map_err(ChainCommunicationError::from_other)?;
+            let previous = request.gas_price().unwrap_or_default();
+            let Some(gas_price) = self.apply_ceiling(previous, bump(previous).max(gas_price)) else {
+                return Ok(false);
+            };
+            request.set_gas_price(gas_price);
         }
-        // ethers-rs will return None if it can no longer poll for the tx in the mempool
-        Ok(Ok(None)) => Err(ChainCommunicationError::TransactionDropped(tx_hash)),
-        // Received error, pass it through
-        Ok(Err(x)) => {
-            error!(?tx_hash, error = ?x, "encountered error when waiting for receipt");
-            Err(x.into())
+        Ok(true)
+    }
+
+    /// Caps a bumped fee at the configured ceiling. Returns None if the
+    /// previous fee had already reached it.
+    fn apply_ceiling(&self, previous: U256, bumped: U256) -> Option<U256> {
+        match self.conf.max_fee_per_gas {
+            Some(ceiling) if previous >= ceiling => None,
+            Some(ceiling) => Some(bumped.min(ceiling)),
+            None => Some(bumped),
         }
-        // Timed out
-        Err(x) => {
-            error!(?tx_hash, error = ?x, "waiting for receipt timed out");
-            Err(ChainCommunicationError::TransactionTimeout())
+    }
+
+    async fn broadcast(&self, request: &TypedTransaction) -> ChainResult<H256> {
+        let pending = self
+            .provider
+            .send_transaction(request.clone(), None)
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(*pending)
+    }
+
+    fn track(&self, from: H160, nonce: U256, tx_hash: H256) {
+        IN_FLIGHT
+            .lock()
+            .expect("in flight transactions lock poisoned")
+            .entry((self.domain, from))
+            .or_default()
+            .entry(nonce)
+            .or_default()
+            .push(tx_hash);
+    }
+
+    fn untrack(&self, from: H160, nonce: U256) {
+        let mut in_flight = IN_FLIGHT
+            .lock()
+            .expect("in flight transactions lock poisoned");
+        if let Some(sender) = in_flight.get_mut(&(self.domain, from)) {
+            sender.remove(&nonce);
+            if sender.is_empty() {
+                in_flight.remove(&(self.domain, from));
+            }
         }
     }
+
+    fn tx_hashes(&self, from: H160, nonce: U256) -> Vec<H256> {
+        IN_FLIGHT
+            .lock()
+            .expect("in flight transactions lock poisoned")
+            .get(&(self.domain, from))
+            .and_then(|sender| sender.get(&nonce))
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 /// Populates the gas limit and price for a transaction
@@ -69,7 +265,7 @@ pub(crate) async fn fill_tx_gas_params<M, D>(
     tx: ContractCall<M, D>,
     tx_gas_limit: Option<U256>,
     provider: Arc<M>,
-    domain: u32,
+    domain: &HyperlaneDomain,
 ) -> ChainResult<ContractCall<M, D>>
 where
     M: Middleware + 'static,
@@ -82,19 +278,18 @@ where
             .await?
             .saturating_add(U256::from(GAS_ESTIMATE_BUFFER))
     };
+    let traits = domain.chain_traits();
+    if !traits.eip1559 {
+        return Ok(tx.gas(gas_limit));
+    }
     let Ok((max_fee, max_priority_fee)) = provider.estimate_eip1559_fees(None).await else {
         // Is not EIP 1559 chain
         return Ok(tx.gas(gas_limit))
     };
-    let max_priority_fee = if matches!(
-        KnownHyperlaneDomain::try_from(domain),
-        Ok(KnownHyperlaneDomain::Polygon)
-    ) {
-        // Polygon needs a max priority fee >= 30 gwei
-        let min_polygon_fee = U256::from(30_000_000_000u64);
-        max_priority_fee.max(min_polygon_fee)
-    } else {
-        max_priority_fee
+    // Some chains, e.g. Polygon, reject priority fees below a minimum
+    let max_priority_fee = match traits.min_priority_fee() {
+        Some(min_priority_fee) => max_priority_fee.max(min_priority_fee),
+        None => max_priority_fee,
     };
     // Is EIP 1559 chain
     let mut request = Eip1559TransactionRequest::new();
@@ -113,6 +308,163 @@ where
     request = request.max_fee_per_gas(max_fee);
     request = request.max_priority_fee_per_gas(max_priority_fee);
     let mut eip_1559_tx = tx;
-    eip_1559_tx.tx = ethers::types::transaction::eip2718::TypedTransaction::Eip1559(request);
+    eip_1559_tx.tx = TypedTransaction::Eip1559(request);
     Ok(eip_1559_tx.gas(gas_limit))
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::{Transaction, TransactionReceipt, TransactionRequest};
+    use hyperlane_core::{HyperlaneDomain, H160, H256, U256};
+
+    use super::TransactionManager;
+    use crate::TransactionReplacementConf;
+
+    fn manager(
+        conf: TransactionReplacementConf,
+    ) -> (
+        Arc<MockProvider>,
+        TransactionManager<Provider<Arc<MockProvider>>>,
+    ) {
+        let mock_provider = Arc::new(MockProvider::new());
+        let provider = Arc::new(Provider::new(mock_provider.clone()));
+        let domain = HyperlaneDomain::new_test_domain("test");
+        (
+            mock_provider,
+            TransactionManager::new(provider, conf, &domain),
+        )
+    }
+
+    /// A manager which replaces transactions as soon as they are found to not
+    /// be included, without waiting between polls.
+    fn impatient_manager() -> (
+        Arc<MockProvider>,
+        TransactionManager<Provider<Arc<MockProvider>>>,
+    ) {
+        let (mock_provider, mut manager) = manager(TransactionReplacementConf {
+            interval: Duration::ZERO,
+            ..Default::default()
+        });
+        manager.poll_interval = Duration::ZERO;
+        (mock_provider, manager)
+    }
+
+    fn legacy_request(from: H160, nonce: U256) -> TypedTransaction {
+        TypedTransaction::Legacy(
+            TransactionRequest::new()
+                .from(from)
+                .to(H160::repeat_byte(2))
+                .nonce(nonce)
+                .gas(21_000)
+                .gas_price(100),
+        )
+    }
+
+    fn receipt(tx_hash: H256) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: tx_hash,
+            ..Default::default()
+        }
+    }
+
+    /// Queue the responses to the first poll of a transaction which is not
+    /// included yet, after which it is replaced. The MockProvider processes
+    /// responses in LIFO order, so this must be called after pushing the
+    /// responses to later requests.
+    fn push_stuck_and_replaced(mock_provider: &MockProvider, replacement: H256) {
+        // eth_sendTransaction of the replacement
+        mock_provider.push(replacement).unwrap();
+        // eth_gasPrice when bumping the fees
+        mock_provider.push(U256::from(100)).unwrap();
+        // eth_getTransaction finds the original in the mempool
+        mock_provider.push(Transaction::default()).unwrap();
+        // eth_getTransactionReceipt of the original
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bump_fees_increases_gas_price() {
+        let (mock_provider, manager) = manager(TransactionReplacementConf::default());
+
+        // eth_gasPrice returns a price below the bumped one
+        mock_provider.push(U256::from(110)).unwrap();
+
+        let mut request = TypedTransaction::Legacy(TransactionRequest::new().gas_price(100));
+        assert!(manager.bump_fees(&mut request).await.unwrap());
+        assert_eq!(request.gas_price(), Some(U256::from(120)));
+    }
+
+    #[tokio::test]
+    async fn test_bump_fees_respects_ceiling() {
+        let (mock_provider, manager) = manager(TransactionReplacementConf {
+            max_fee_per_gas: Some(U256::from(110)),
+            ..Default::default()
+        });
+
+        // The bumped price is capped at the ceiling
+        mock_provider.push(U256::from(100)).unwrap();
+        let mut request = TypedTransaction::Legacy(TransactionRequest::new().gas_price(100));
+        assert!(manager.bump_fees(&mut request).await.unwrap());
+        assert_eq!(request.gas_price(), Some(U256::from(110)));
+
+        // Once the ceiling is reached the transaction is not replaced anymore
+        mock_provider.push(U256::from(100)).unwrap();
+        assert!(!manager.bump_fees(&mut request).await.unwrap());
+        assert_eq!(request.gas_price(), Some(U256::from(110)));
+    }
+
+    #[tokio::test]
+    async fn test_replaces_stuck_transaction() {
+        let (mock_provider, manager) = impatient_manager();
+        let (from, nonce) = (H160::random(), U256::from(3));
+        let (original, replacement) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        manager.track(from, nonce, original);
+
+        // The replacement is included on the second poll
+        mock_provider.push(Some(receipt(replacement))).unwrap();
+        push_stuck_and_replaced(&mock_provider, replacement);
+
+        let included = manager
+            .wait_for_receipt(legacy_request(from, nonce), from, nonce)
+            .await
+            .unwrap();
+        assert_eq!(included.transaction_hash, replacement);
+        assert_eq!(manager.tx_hashes(from, nonce), vec![original, replacement]);
+
+        // Other contracts on the chain see the transactions of the sender
+        let (_, other) = manager(TransactionReplacementConf::default());
+        assert_eq!(other.tx_hashes(from, nonce), vec![original, replacement]);
+        manager.untrack(from, nonce);
+        assert!(other.tx_hashes(from, nonce).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_returns_receipt_of_original_if_it_is_included() {
+        let (mock_provider, manager) = impatient_manager();
+        let (from, nonce) = (H160::random(), U256::from(3));
+        let (original, replacement) = (H256::repeat_byte(1), H256::repeat_byte(2));
+        manager.track(from, nonce, original);
+
+        // The original is included after the replacement was sent, so the
+        // replacement never will be
+        mock_provider.push(Some(receipt(original))).unwrap();
+        mock_provider
+            .push(Option::<TransactionReceipt>::None)
+            .unwrap();
+        push_stuck_and_replaced(&mock_provider, replacement);
+
+        let included = manager
+            .wait_for_receipt(legacy_request(from, nonce), from, nonce)
+            .await
+            .unwrap();
+        assert_eq!(included.transaction_hash, original);
+        manager.untrack(from, nonce);
+    }
+}
@@ -18,8 +18,8 @@ use crate::contracts::i_validator_announce::{
     IValidatorAnnounce as EthereumValidatorAnnounceInternal, IVALIDATORANNOUNCE_ABI,
 };
 use crate::trait_builder::BuildableWithProvider;
-use crate::tx::{fill_tx_gas_params, report_tx};
-use crate::EthereumProvider;
+use crate::tx::{fill_tx_gas_params, TransactionManager};
+use crate::{EthereumProvider, TransactionReplacementConf};
 
 impl<M> std::fmt::Display for EthereumValidatorAnnounceInternal<M>
 where
@@ -30,7 +30,10 @@ where
     }
 }
 
-pub struct ValidatorAnnounceBuilder {}
+pub struct ValidatorAnnounceBuilder {
+    /// How to replace transactions which are not included in time
+    pub transaction_replacement: TransactionReplacementConf,
+}
 
 #[async_trait]
 impl BuildableWithProvider for ValidatorAnnounceBuilder {
@@ -41,7 +44,10 @@ impl BuildableWithProvider for ValidatorAnnounceBuilder {
         provider: M,
         locator: &ContractLocator,
     ) -> Self::Output {
-        Box::new(EthereumValidatorAnnounce::new(Arc::new(provider), locator))
+        Box::new(
+            EthereumValidatorAnnounce::new(Arc::new(provider), locator)
+                .with_transaction_replacement(self.transaction_replacement.clone()),
+        )
     }
 }
 
@@ -54,6 +60,7 @@ where
     contract: Arc<EthereumValidatorAnnounceInternal<M>>,
     domain: HyperlaneDomain,
     provider: Arc<M>,
+    tx_manager: TransactionManager<M>,
 }
 
 impl<M> EthereumValidatorAnnounce<M>
@@ -69,10 +76,22 @@ where
                 provider.clone(),
             )),
             domain: locator.domain.clone(),
+            tx_manager: TransactionManager::new(
+                provider.clone(),
+                Default::default(),
+                &locator.domain,
+            ),
             provider,
         }
     }
 
+    /// Replace transactions which are not included in time according to the
+    /// given configuration.
+    pub fn with_transaction_replacement(mut self, conf: TransactionReplacementConf) -> Self {
+        self.tx_manager = TransactionManager::new(self.provider.clone(), conf, &self.domain);
+        self
+    }
+
     /// Returns a ContractCall that processes the provided message.
     /// If the provided tx_gas_limit is None, gas estimation occurs.
     async fn announce_contract_call(
@@ -86,7 +105,7 @@ where
             announcement.value.storage_location,
             serialized_signature.into(),
         );
-        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), self.domain.id()).await
+        fill_tx_gas_params(tx, tx_gas_limit, self.provider.clone(), &self.domain).await
     }
 }
 
@@ -158,7 +177,7 @@ where
         let contract_call = self
             .announce_contract_call(announcement, tx_gas_limit)
             .await?;
-        let receipt = report_tx(contract_call).await?;
+        let receipt = self.tx_manager.send(contract_call).await?;
         Ok(receipt.into())
     }
 }
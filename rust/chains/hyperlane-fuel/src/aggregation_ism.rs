@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use tracing::instrument;
+
+use hyperlane_core::{
+    AggregationIsm, ChainResult, ContractLocator, HyperlaneChain, HyperlaneContract,
+    HyperlaneDomain, HyperlaneMessage, HyperlaneProvider, H256,
+};
+
+use crate::{make_provider, ConnectionConf, FuelProvider, HyperlaneFuelError};
+
+/// A reference to a AggregationIsm contract on some Fuel chain
+#[derive(Debug)]
+pub struct FuelAggregationIsm {
+    address: H256,
+    provider: FuelProvider,
+}
+
+impl FuelAggregationIsm {
+    /// Create a reference to an aggregation ISM
+    pub fn new(conf: &ConnectionConf, locator: ContractLocator) -> ChainResult<Self> {
+        Ok(Self {
+            address: locator.address,
+            provider: FuelProvider::new(locator.domain.clone(), make_provider(conf)?),
+        })
+    }
+}
+
+impl HyperlaneContract for FuelAggregationIsm {
+    fn address(&self) -> H256 {
+        self.address
+    }
+}
+
+impl HyperlaneChain for FuelAggregationIsm {
+    fn domain(&self) -> &HyperlaneDomain {
+        self.provider.domain()
+    }
+
+    fn provider(&self) -> Box<dyn HyperlaneProvider> {
+        Box::new(self.provider.clone())
+    }
+}
+
+#[async_trait]
+impl AggregationIsm for FuelAggregationIsm {
+    /// Returns the modules and threshold needed to verify message
+    #[instrument(err, ret, skip(self))]
+    async fn modules_and_threshold(
+        &self,
+        _message: &HyperlaneMessage,
+    ) -> ChainResult<(Vec<H256>, u8)> {
+        Err(HyperlaneFuelError::Unsupported("Aggregation ISMs").into())
+    }
+}
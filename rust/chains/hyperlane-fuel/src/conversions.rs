@@ -52,3 +52,33 @@ impl_h256!(
     |v| fuels::prelude::ContractId::new(v.0),
     |v| H256::from(<[u8; 32]>::from(v))
 );
+
+impl_h256!(fuels::tx::Bytes32, |v| fuels::tx::Bytes32::new(v.0), |v| {
+    H256::from(*v)
+});
+
+impl_h256!(
+    fuels::prelude::Address,
+    |v| fuels::prelude::Address::new(v.0),
+    |v| H256::from(*v)
+);
+
+macro_rules! impl_message {
+    ($type:ty) => {
+        impl From<&hyperlane_core::HyperlaneMessage> for $type {
+            fn from(m: &hyperlane_core::HyperlaneMessage) -> Self {
+                Self {
+                    version: m.version,
+                    nonce: m.nonce,
+                    origin: m.origin,
+                    sender: fuels::types::Bits256(m.sender.0),
+                    destination: m.destination,
+                    recipient: fuels::types::Bits256(m.recipient.0),
+                    body: m.body.clone(),
+                }
+            }
+        }
+    };
+}
+
+impl_message!(crate::contracts::mailbox::Message);
@@ -0,0 +1,21 @@
+use hyperlane_core::{ChainCommunicationError, H256};
+
+/// Errors specific to fuel chains.
+#[derive(thiserror::Error, Debug)]
+pub enum HyperlaneFuelError {
+    /// Only script transactions, which call contracts, can be described
+    #[error("Transaction {0:?} is not a script transaction")]
+    NotAScriptTransaction(H256),
+    /// Contract state can only be read at the latest block
+    #[error("Fuel does not support querying point-in-time")]
+    PointInTimeQuery,
+    /// The vendored contract ABIs don't expose what is needed for the call
+    #[error("{0} is not yet supported on Fuel")]
+    Unsupported(&'static str),
+}
+
+impl From<HyperlaneFuelError> for ChainCommunicationError {
+    fn from(err: HyperlaneFuelError) -> Self {
+        ChainCommunicationError::from_other(err)
+    }
+}
@@ -1,25 +1,36 @@
 //! Implementation of hyperlane for fuel.
+//!
+//! Only the Mailbox ABI is vendored, so only the provider and the mailbox's
+//! count, checkpoint, delivery status and default ISM queries are supported.
+//! Message indexing, merkle proofs, ISMs, the IGP, validator announcements
+//! and message delivery return [`HyperlaneFuelError::Unsupported`] until ABIs
+//! generated from the deployed Sway contracts are vendored, along with
+//! integration tests for them against a local fuel-core node.
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
-// TODO: Remove once we start filling things in
-#![allow(unused_variables)]
 
+pub use aggregation_ism::*;
+pub use error::*;
 pub use interchain_gas::*;
 pub use mailbox::*;
 pub use multisig_ism::*;
 pub use provider::*;
 pub use routing_ism::*;
 pub use trait_builder::*;
+pub use validator_announce::*;
 
+mod aggregation_ism;
 mod contracts;
 mod conversions;
+mod error;
 mod interchain_gas;
 mod mailbox;
 mod multisig_ism;
 mod provider;
 mod routing_ism;
 mod trait_builder;
+mod validator_announce;
 
 /// Safe default imports of commonly used traits/types.
 pub mod prelude {
@@ -3,23 +3,30 @@ use std::fmt::{Debug, Formatter};
 use std::num::NonZeroU64;
 
 use async_trait::async_trait;
-use fuels::prelude::{Bech32ContractId, WalletUnlocked};
+use fuels::core::function_selector::resolve_fn_selector;
+use fuels::prelude::{Bech32ContractId, ContractId, WalletUnlocked};
+use fuels::types::traits::Parameterize;
+use fuels::types::{Bits256, Identity};
 use tracing::instrument;
 
 use hyperlane_core::{
-    accumulator::incremental::IncrementalMerkle, utils::fmt_bytes, ChainCommunicationError,
-    ChainResult, Checkpoint, ContractLocator, HyperlaneAbi, HyperlaneChain, HyperlaneContract,
-    HyperlaneDomain, HyperlaneMessage, HyperlaneProvider, Indexer, LogMeta, Mailbox,
-    TxCostEstimate, TxOutcome, H256, U256,
+    accumulator::incremental::IncrementalMerkle, utils::fmt_bytes, BatchItem,
+    ChainCommunicationError, ChainResult, Checkpoint, ContractLocator, HyperlaneAbi,
+    HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneMessage, HyperlaneProtocolError,
+    HyperlaneProvider, Indexer, LogMeta, Mailbox, MessageIndexer, TxCostEstimate, TxOutcome, H256,
+    U256,
 };
 
 use crate::{
-    contracts::mailbox::Mailbox as FuelMailboxInner, conversions::*, make_provider, ConnectionConf,
+    contracts::mailbox::{Mailbox as FuelMailboxInner, Message as FuelMessage},
+    conversions::*,
+    make_provider, ConnectionConf, FuelProvider, HyperlaneFuelError,
 };
 
 /// A reference to a Mailbox contract on some Fuel chain
 pub struct FuelMailbox {
     contract: FuelMailboxInner,
+    provider: FuelProvider,
     domain: HyperlaneDomain,
 }
 
@@ -31,11 +38,12 @@ impl FuelMailbox {
         mut wallet: WalletUnlocked,
     ) -> ChainResult<Self> {
         let provider = make_provider(conf)?;
-        wallet.set_provider(provider);
+        wallet.set_provider(provider.clone());
         let address = Bech32ContractId::from_h256(&locator.address);
 
         Ok(FuelMailbox {
             contract: FuelMailboxInner::new(address, wallet),
+            provider: FuelProvider::new(locator.domain.clone(), provider),
             domain: locator.domain.clone(),
         })
     }
@@ -53,7 +61,7 @@ impl HyperlaneChain for FuelMailbox {
     }
 
     fn provider(&self) -> Box<dyn HyperlaneProvider> {
-        todo!()
+        Box::new(self.provider.clone())
     }
 }
 
@@ -67,10 +75,7 @@ impl Debug for FuelMailbox {
 impl Mailbox for FuelMailbox {
     #[instrument(level = "debug", err, ret, skip(self))]
     async fn count(&self, lag: Option<NonZeroU64>) -> ChainResult<u32> {
-        assert!(
-            lag.is_none(),
-            "Fuel does not support querying point-in-time"
-        );
+        ensure_no_lag(lag)?;
         self.contract
             .methods()
             .count()
@@ -81,21 +86,25 @@ impl Mailbox for FuelMailbox {
     }
 
     #[instrument(level = "debug", err, ret, skip(self))]
-    async fn tree(&self, lag: Option<NonZeroU64>) -> ChainResult<IncrementalMerkle> {
-        todo!()
+    async fn tree(&self, _lag: Option<NonZeroU64>) -> ChainResult<IncrementalMerkle> {
+        // The mailbox does not expose the branch of its tree
+        Err(HyperlaneFuelError::Unsupported("Reading the mailbox tree").into())
     }
 
     #[instrument(level = "debug", err, ret, skip(self))]
     async fn delivered(&self, id: H256) -> ChainResult<bool> {
-        todo!()
+        self.contract
+            .methods()
+            .delivered(Bits256::from_h256(&id))
+            .simulate()
+            .await
+            .map(|r| r.value)
+            .map_err(ChainCommunicationError::from_other)
     }
 
     #[instrument(level = "debug", err, ret, skip(self))]
     async fn latest_checkpoint(&self, lag: Option<NonZeroU64>) -> ChainResult<Checkpoint> {
-        assert!(
-            lag.is_none(),
-            "Fuel does not support querying point-in-time"
-        );
+        ensure_no_lag(lag)?;
         let (root, index) = self
             .contract
             .methods()
@@ -115,22 +124,30 @@ impl Mailbox for FuelMailbox {
 
     #[instrument(err, ret, skip(self))]
     async fn default_ism(&self) -> ChainResult<H256> {
-        todo!()
+        self.contract
+            .methods()
+            .get_default_ism()
+            .simulate()
+            .await
+            .map(|r| r.value.into_h256())
+            .map_err(ChainCommunicationError::from_other)
     }
 
     #[instrument(err, ret, skip(self))]
-    async fn recipient_ism(&self, recipient: H256) -> ChainResult<H256> {
-        todo!()
+    async fn recipient_ism(&self, _recipient: H256) -> ChainResult<H256> {
+        // The mailbox does not expose the ISMs recipients specify
+        Err(HyperlaneFuelError::Unsupported("Reading recipient ISMs").into())
     }
 
     #[instrument(err, ret, skip(self))]
     async fn process(
         &self,
-        message: &HyperlaneMessage,
-        metadata: &[u8],
-        tx_gas_limit: Option<U256>,
+        _message: &HyperlaneMessage,
+        _metadata: &[u8],
+        _tx_gas_limit: Option<U256>,
     ) -> ChainResult<TxOutcome> {
-        todo!()
+        // The deployed mailbox's process() ignores the message it is given
+        Err(HyperlaneFuelError::Unsupported("Processing messages").into())
     }
 
     #[instrument(err, ret, skip(self), fields(msg=%message, metadata=%fmt_bytes(metadata)))]
@@ -139,51 +156,183 @@ impl Mailbox for FuelMailbox {
         message: &HyperlaneMessage,
         metadata: &[u8],
     ) -> ChainResult<TxCostEstimate> {
-        todo!()
+        Err(HyperlaneFuelError::Unsupported("Processing messages").into())
     }
 
     fn process_calldata(&self, message: &HyperlaneMessage, metadata: &[u8]) -> Vec<u8> {
-        todo!()
+        // The arguments are resolved relative to the start of the script data
+        let call = self
+            .contract
+            .methods()
+            .process(metadata.to_vec(), message.into())
+            .contract_call;
+        [call.encoded_selector.to_vec(), call.encoded_args.resolve(0)].concat()
+    }
+
+    fn supports_batching(&self) -> bool {
+        false
+    }
+
+    #[instrument(err, ret, skip(self))]
+    async fn simulate_process_batch(&self, _batch: &[BatchItem]) -> ChainResult<Vec<bool>> {
+        Err(HyperlaneProtocolError::BatchingNotSupported.into())
+    }
+
+    #[instrument(err, ret, skip(self))]
+    async fn process_batch(
+        &self,
+        _batch: &[BatchItem],
+        _tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome> {
+        Err(HyperlaneProtocolError::BatchingNotSupported.into())
+    }
+}
+
+/// Struct that retrieves event data for a Fuel Mailbox contract. The mailbox
+/// logs neither dispatched messages nor processed message ids, so only the
+/// message count can be read.
+pub struct FuelMailboxIndexer {
+    contract: FuelMailboxInner,
+    provider: FuelProvider,
+}
+
+impl FuelMailboxIndexer {
+    /// Create a new fuel mailbox indexer. The wallet is only used to simulate
+    /// calls and does not need to hold any coins.
+    pub fn new(
+        conf: &ConnectionConf,
+        locator: ContractLocator,
+        mut wallet: WalletUnlocked,
+    ) -> ChainResult<Self> {
+        let provider = make_provider(conf)?;
+        wallet.set_provider(provider.clone());
+        let address = Bech32ContractId::from_h256(&locator.address);
+
+        Ok(FuelMailboxIndexer {
+            contract: FuelMailboxInner::new(address, wallet),
+            provider: FuelProvider::new(locator.domain.clone(), provider),
+        })
     }
 }
 
-/// Struct that retrieves event data for a Fuel Mailbox contract
-#[derive(Debug)]
-pub struct FuelMailboxIndexer {}
+impl Debug for FuelMailboxIndexer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FuelMailboxIndexer")
+            .field("address", &self.contract.contract_id().into_h256())
+            .field("provider", &self.provider)
+            .finish()
+    }
+}
 
 #[async_trait]
 impl Indexer<HyperlaneMessage> for FuelMailboxIndexer {
+    #[instrument(err, skip(self))]
     async fn fetch_logs(
         &self,
-        from: u32,
-        to: u32,
+        _from: u32,
+        _to: u32,
     ) -> ChainResult<Vec<(HyperlaneMessage, LogMeta)>> {
-        todo!()
+        Err(HyperlaneFuelError::Unsupported("Indexing dispatched messages").into())
     }
 
+    #[instrument(level = "debug", err, ret, skip(self))]
     async fn get_finalized_block_number(&self) -> ChainResult<u32> {
-        todo!()
+        // Fuel has instant finality, so no blocks are subtracted from the tip
+        self.provider.latest_block_height().await
+    }
+}
+
+#[async_trait]
+impl MessageIndexer for FuelMailboxIndexer {
+    #[instrument(err, ret, skip(self))]
+    async fn fetch_count_at_tip(&self) -> ChainResult<(u32, u32)> {
+        // Fuel does not support querying point-in-time, so the count is read
+        // before the tip to never include messages dispatched after it.
+        let count = self
+            .contract
+            .methods()
+            .count()
+            .simulate()
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .value;
+        let tip = self.provider.latest_block_height().await?;
+        Ok((count, tip))
     }
 }
 
 #[async_trait]
 impl Indexer<H256> for FuelMailboxIndexer {
-    async fn fetch_logs(&self, from: u32, to: u32) -> ChainResult<Vec<(H256, LogMeta)>> {
-        todo!()
+    #[instrument(err, skip(self))]
+    async fn fetch_logs(&self, _from: u32, _to: u32) -> ChainResult<Vec<(H256, LogMeta)>> {
+        Err(HyperlaneFuelError::Unsupported("Indexing processed messages").into())
     }
 
+    #[instrument(level = "debug", err, ret, skip(self))]
     async fn get_finalized_block_number(&self) -> ChainResult<u32> {
-        todo!()
+        // Fuel has instant finality, so no blocks are subtracted from the tip
+        self.provider.latest_block_height().await
     }
 }
 
-struct FuelMailboxAbi;
+/// Fuel contract state can only be read at the latest block, which is final.
+fn ensure_no_lag(lag: Option<NonZeroU64>) -> ChainResult<()> {
+    match lag {
+        Some(_) => Err(HyperlaneFuelError::PointInTimeQuery.into()),
+        None => Ok(()),
+    }
+}
+
+/// Function selectors of the transactions which can be sent to a Fuel Mailbox
+pub struct FuelMailboxAbi;
 
 impl HyperlaneAbi for FuelMailboxAbi {
     const SELECTOR_SIZE_BYTES: usize = 8;
 
     fn fn_map() -> HashMap<Vec<u8>, &'static str> {
-        // Can't support this without Fuels exporting it in the generated code
-        todo!()
+        let bytes = Vec::<u8>::param_type();
+        [
+            (
+                "dispatch",
+                vec![u32::param_type(), Bits256::param_type(), bytes.clone()],
+            ),
+            ("process", vec![bytes, FuelMessage::param_type()]),
+            ("set_default_ism", vec![ContractId::param_type()]),
+            ("transfer_ownership", vec![Option::<Identity>::param_type()]),
+        ]
+        .into_iter()
+        .map(|(name, inputs)| (resolve_fn_selector(name, &inputs).to_vec(), name))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fuels::client::FuelClient;
+    use fuels::prelude::{Bech32ContractId, Provider, WalletUnlocked};
+
+    use hyperlane_core::{HyperlaneAbi, HyperlaneDomain, HyperlaneMessage, Mailbox, H256};
+
+    use super::{FuelMailbox, FuelMailboxAbi, FuelMailboxInner};
+    use crate::{conversions::*, FuelProvider};
+
+    #[test]
+    fn test_process_calldata_starts_with_selector() {
+        let provider = Provider::new(FuelClient::new("http://localhost:4000").unwrap());
+        let domain = HyperlaneDomain::new_test_domain("fuel");
+        let mailbox = FuelMailbox {
+            contract: FuelMailboxInner::new(
+                Bech32ContractId::from_h256(&H256::repeat_byte(1)),
+                WalletUnlocked::new_random(None),
+            ),
+            provider: FuelProvider::new(domain.clone(), provider),
+            domain,
+        };
+
+        let fn_map = FuelMailboxAbi::fn_map();
+        assert_eq!(fn_map.len(), 4);
+        let calldata = mailbox.process_calldata(&HyperlaneMessage::default(), &[1, 2, 3]);
+        let selector = &calldata[..FuelMailboxAbi::SELECTOR_SIZE_BYTES];
+        assert_eq!(fn_map.get(selector), Some(&"process"));
     }
 }
@@ -1,34 +1,166 @@
 use async_trait::async_trait;
+use fuels::prelude::Provider;
+use fuels::tx::field::{GasLimit, GasPrice, Inputs};
+use fuels::tx::{Bytes32, Input, Transaction as FuelTransaction};
+use fuels::types::block::Block;
 
 use hyperlane_core::{
-    BlockInfo, ChainResult, HyperlaneChain, HyperlaneDomain, HyperlaneProvider, TxnInfo, H256,
+    BlockInfo, ChainCommunicationError, ChainResult, HyperlaneChain, HyperlaneDomain,
+    HyperlaneProvider, HyperlaneProviderError, TxnInfo, H256, U256,
 };
 
+use crate::{conversions::*, HyperlaneFuelError};
+
 /// A wrapper around a fuel provider to get generic blockchain information.
-#[derive(Debug)]
-pub struct FuelProvider {}
+#[derive(Debug, Clone)]
+pub struct FuelProvider {
+    domain: HyperlaneDomain,
+    provider: Provider,
+}
+
+impl FuelProvider {
+    /// Create a new fuel provider for the given domain
+    pub fn new(domain: HyperlaneDomain, provider: Provider) -> Self {
+        Self { domain, provider }
+    }
+
+    /// Height of the latest block. Fuel has instant finality, since blocks
+    /// are produced by a single sequencer and are never reorged, so this is
+    /// also the latest finalized block.
+    pub(crate) async fn latest_block_height(&self) -> ChainResult<u32> {
+        let height = self
+            .provider
+            .latest_block_height()
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(height as u32)
+    }
+
+    async fn block_by_height(&self, height: u64) -> ChainResult<Block> {
+        self.provider
+            .block_by_height(height)
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .ok_or_else(|| HyperlaneProviderError::CouldNotFindBlockByNumber(height).into())
+    }
+}
 
 impl HyperlaneChain for FuelProvider {
     fn domain(&self) -> &HyperlaneDomain {
-        todo!()
+        &self.domain
     }
 
     fn provider(&self) -> Box<dyn HyperlaneProvider> {
-        todo!()
+        Box::new(self.clone())
     }
 }
 
 #[async_trait]
 impl HyperlaneProvider for FuelProvider {
     async fn get_block_by_hash(&self, hash: &H256) -> ChainResult<BlockInfo> {
-        todo!()
+        let block = self
+            .provider
+            .block(&Bytes32::from_h256(hash))
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .ok_or(HyperlaneProviderError::CouldNotFindObjectByHash(*hash))?;
+        Ok(block_info(&block))
+    }
+
+    async fn get_block_by_number(&self, number: u64) -> ChainResult<BlockInfo> {
+        Ok(block_info(&self.block_by_height(number).await?))
+    }
+
+    async fn get_block_number(&self) -> ChainResult<u64> {
+        Ok(self.latest_block_height().await?.into())
     }
 
     async fn get_txn_by_hash(&self, hash: &H256) -> ChainResult<TxnInfo> {
-        todo!()
+        let response = self
+            .provider
+            .get_transaction_by_id(&Bytes32::from_h256(hash))
+            .await
+            .map_err(ChainCommunicationError::from_other)?
+            .ok_or(HyperlaneProviderError::CouldNotFindObjectByHash(*hash))?;
+
+        let FuelTransaction::Script(tx) = response.transaction else {
+            return Err(HyperlaneFuelError::NotAScriptTransaction(*hash).into());
+        };
+        // Fuel has no account nonces; the sender is the owner of the coins
+        // paying for the transaction.
+        let sender = tx
+            .inputs()
+            .iter()
+            .find_map(|input| match input {
+                Input::CoinSigned { owner, .. } => Some(owner.into_h256()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let recipient = tx.inputs().iter().find_map(|input| match input {
+            Input::Contract { contract_id, .. } => Some(contract_id.into_h256()),
+            _ => None,
+        });
+
+        Ok(TxnInfo {
+            hash: *hash,
+            gas_limit: U256::from(*tx.gas_limit()),
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            gas_price: Some(U256::from(*tx.gas_price())),
+            nonce: 0,
+            sender,
+            recipient,
+            receipt: None,
+        })
     }
 
     async fn is_contract(&self, address: &H256) -> ChainResult<bool> {
-        todo!()
+        let contract = self
+            .provider
+            .client
+            .contract(&format!("{address:#x}"))
+            .await
+            .map_err(ChainCommunicationError::from_other)?;
+        Ok(contract.is_some())
+    }
+}
+
+fn block_info(block: &Block) -> BlockInfo {
+    BlockInfo {
+        hash: block.id.into_h256(),
+        timestamp: block
+            .header
+            .time
+            .map(|time| time.timestamp() as u64)
+            .unwrap_or_default(),
+        number: block.header.height,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fuels::prelude::launch_provider_and_get_wallet;
+
+    use hyperlane_core::{HyperlaneDomain, HyperlaneProvider, H256};
+
+    use super::FuelProvider;
+
+    #[tokio::test]
+    #[ignore = "requires a fuel-core binary"]
+    async fn test_block_queries() {
+        let wallet = launch_provider_and_get_wallet().await;
+        let provider = FuelProvider::new(
+            HyperlaneDomain::new_test_domain("fuel"),
+            wallet.get_provider().unwrap().clone(),
+        );
+
+        let number = provider.get_block_number().await.unwrap();
+        let by_number = provider.get_block_by_number(number).await.unwrap();
+        let by_hash = provider.get_block_by_hash(&by_number.hash).await.unwrap();
+        assert_eq!(by_hash.number, number);
+        assert_eq!(by_hash.hash, by_number.hash);
+        assert!(provider.get_block_by_number(number + 100).await.is_err());
+
+        assert!(!provider.is_contract(&H256::random()).await.unwrap());
     }
 }
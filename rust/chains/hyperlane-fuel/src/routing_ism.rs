@@ -1,34 +1,51 @@
 use async_trait::async_trait;
+use tracing::instrument;
 
 use hyperlane_core::{
-    ChainResult, HyperlaneChain, HyperlaneContract, HyperlaneDomain, HyperlaneMessage,
-    HyperlaneProvider, RoutingIsm, H256,
+    ChainResult, ContractLocator, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
+    HyperlaneMessage, HyperlaneProvider, RoutingIsm, H256,
 };
 
+use crate::{make_provider, ConnectionConf, FuelProvider, HyperlaneFuelError};
+
 /// A reference to a RoutingIsm contract on some Fuel chain
 #[derive(Debug)]
-pub struct FuelRoutingIsm {}
+pub struct FuelRoutingIsm {
+    address: H256,
+    provider: FuelProvider,
+}
+
+impl FuelRoutingIsm {
+    /// Create a reference to a routing ISM
+    pub fn new(conf: &ConnectionConf, locator: ContractLocator) -> ChainResult<Self> {
+        Ok(Self {
+            address: locator.address,
+            provider: FuelProvider::new(locator.domain.clone(), make_provider(conf)?),
+        })
+    }
+}
 
 impl HyperlaneContract for FuelRoutingIsm {
     fn address(&self) -> H256 {
-        todo!()
+        self.address
     }
 }
 
 impl HyperlaneChain for FuelRoutingIsm {
     fn domain(&self) -> &HyperlaneDomain {
-        todo!()
+        self.provider.domain()
     }
 
     fn provider(&self) -> Box<dyn HyperlaneProvider> {
-        todo!()
+        Box::new(self.provider.clone())
     }
 }
 
 #[async_trait]
 impl RoutingIsm for FuelRoutingIsm {
     /// Returns the ism needed to verify message
-    async fn route(&self, message: &HyperlaneMessage) -> ChainResult<H256> {
-        todo!()
+    #[instrument(err, ret, skip(self))]
+    async fn route(&self, _message: &HyperlaneMessage) -> ChainResult<H256> {
+        Err(HyperlaneFuelError::Unsupported("Routing ISMs").into())
     }
 }
@@ -1,31 +1,69 @@
 use async_trait::async_trait;
+use tracing::instrument;
 
 use hyperlane_core::{
-    ChainResult, HyperlaneChain, HyperlaneContract, HyperlaneDomain, ValidatorAnnounce, H256,
+    Announcement, ChainResult, ContractLocator, HyperlaneChain, HyperlaneContract, HyperlaneDomain,
+    HyperlaneProvider, SignedType, TxOutcome, ValidatorAnnounce, H256, U256,
 };
 
+use crate::{make_provider, ConnectionConf, FuelProvider, HyperlaneFuelError};
+
 /// A reference to a ValidatorAnnounce contract on some Fuel chain
 #[derive(Debug)]
-pub struct FuelValidatorAnnounce {}
+pub struct FuelValidatorAnnounce {
+    address: H256,
+    provider: FuelProvider,
+}
+
+impl FuelValidatorAnnounce {
+    /// Create a reference to a validator announce contract
+    pub fn new(conf: &ConnectionConf, locator: ContractLocator) -> ChainResult<Self> {
+        Ok(Self {
+            address: locator.address,
+            provider: FuelProvider::new(locator.domain.clone(), make_provider(conf)?),
+        })
+    }
+}
 
 impl HyperlaneContract for FuelValidatorAnnounce {
     fn address(&self) -> H256 {
-        todo!()
+        self.address
     }
 }
 
 impl HyperlaneChain for FuelValidatorAnnounce {
     fn domain(&self) -> &HyperlaneDomain {
-        todo!()
+        self.provider.domain()
+    }
+
+    fn provider(&self) -> Box<dyn HyperlaneProvider> {
+        Box::new(self.provider.clone())
     }
 }
 
 #[async_trait]
 impl ValidatorAnnounce for FuelValidatorAnnounce {
+    #[instrument(err, ret, skip(self))]
     async fn get_announced_storage_locations(
         &self,
-        validators: &[H256],
+        _validators: &[H256],
     ) -> ChainResult<Vec<Vec<String>>> {
-        todo!()
+        Err(HyperlaneFuelError::Unsupported("Validator announcements").into())
+    }
+
+    async fn announce_tokens_needed(
+        &self,
+        _announcement: SignedType<Announcement>,
+    ) -> ChainResult<U256> {
+        Err(HyperlaneFuelError::Unsupported("Validator announcements").into())
+    }
+
+    #[instrument(err, ret, skip(self))]
+    async fn announce(
+        &self,
+        _announcement: SignedType<Announcement>,
+        _tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome> {
+        Err(HyperlaneFuelError::Unsupported("Validator announcements").into())
     }
 }
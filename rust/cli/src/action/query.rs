@@ -7,7 +7,7 @@ use crate::{
 };
 use color_eyre::{eyre::Context, Result};
 use ethers::providers::Middleware;
-use hyperlane_core::H160;
+use hyperlane_core::{body::MessageBodyDecoders, H160};
 use relayer::settings::matching_list::MatchingList;
 
 /// Query for messages sent to a Hyperlane mailbox contract that match the provided matching list.
@@ -18,6 +18,7 @@ pub async fn query<M: Middleware + 'static>(
     start_block: i32,
     end_block: i32,
     matching_list: MatchingList,
+    body_decoders: &MessageBodyDecoders,
     verbose: bool,
 ) -> Result<()> {
     let mailbox = Arc::new(Mailbox::new(mailbox_address, Arc::clone(&client)));
@@ -37,7 +38,7 @@ pub async fn query<M: Middleware + 'static>(
     .await?;
 
     for log in &logs {
-        print_log_item(log, verbose)?;
+        print_log_item(log, body_decoders, verbose)?;
     }
 
     Ok(())
@@ -65,13 +66,17 @@ async fn get_current_block<M: Middleware + 'static>(client: Arc<M>) -> Result<u6
         .as_u64())
 }
 
-fn print_log_item(log: crate::query::MailboxLogItem<'_>, verbose: bool) -> Result<()> {
+fn print_log_item(
+    log: crate::query::MailboxLogItem<'_>,
+    body_decoders: &MessageBodyDecoders,
+    verbose: bool,
+) -> Result<()> {
     if verbose {
         println!("{:#?}", log.log);
     }
 
     print_log_item_first_line(&log)?;
-    print_log_item_details(&log)
+    print_log_item_details(&log, body_decoders)
 }
 
 fn print_log_item_first_line(log: &MailboxLogItem<'_>) -> Result<()> {
@@ -91,7 +96,10 @@ fn print_log_item_first_line(log: &MailboxLogItem<'_>) -> Result<()> {
     Ok(())
 }
 
-fn print_log_item_details(log: &MailboxLogItem<'_>) -> Result<()> {
+fn print_log_item_details(
+    log: &MailboxLogItem<'_>,
+    body_decoders: &MessageBodyDecoders,
+) -> Result<()> {
     println!(
         "  Tx hash  : {}",
         core::option_into_debug_string(&log.transaction_hash())
@@ -105,6 +113,15 @@ fn print_log_item_details(log: &MailboxLogItem<'_>) -> Result<()> {
     if let Some(id) = log.message_id()? {
         println!("  ID       : {:?}", id);
     };
+    if let Some(body) = log
+        .hyperlane_message()?
+        .and_then(|message| body_decoders.decode(&message))
+    {
+        println!("  Body     : {}", body.kind);
+        for (name, value) in &body.fields {
+            println!("    {name}: {value}");
+        }
+    }
 
     Ok(())
 }
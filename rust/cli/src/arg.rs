@@ -100,6 +100,13 @@ pub struct CliArgs {
     #[arg(short, long)]
     pub query_file: Option<PathBuf>,
 
+    /// Show the bodies of queried messages sent to a recipient as decoded
+    /// fields, given as RECIPIENT=DECODER. Can be repeated.
+    ///
+    /// Decoders: warpRouteTransfer, interchainAccountCall, interchainQuery
+    #[arg(long)]
+    pub body_decoder: Vec<String>,
+
     // /// Maximum number of messages to return.
     // /// If negative, will return last N matching messages.
     // #[arg(short, long, default_value = "-10")]
@@ -3,21 +3,16 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
-use hyperlane_core::KnownHyperlaneDomain;
-use itertools::Itertools;
-use strum::IntoEnumIterator;
+use hyperlane_core::DomainRegistry;
 
 // Potentially move this to an hl command?
 fn main() {
     println!("Known Hyperlane domains:");
 
-    for domain in KnownHyperlaneDomain::iter().sorted() {
+    for domain in DomainRegistry::global().domains() {
         println!(
             "{:>12}: {:<24} ({:?} {:?})",
-            domain as u32,
-            format!("{domain:?}"),
-            domain.domain_protocol(),
-            domain.domain_type(),
+            domain.id, domain.name, domain.protocol, domain.domain_type,
         );
     }
 }
@@ -87,6 +87,7 @@ async fn query_logs(params: &Params, provider: Arc<Provider<Http>>, chain_id: u3
             params.start_block,
             params.end_block,
             params.matching_list.clone(),
+            &params.body_decoders,
             params.verbose,
         )
         .await?;
@@ -9,22 +9,17 @@ use ethers::{
     signers::{LocalWallet, Signer},
 };
 use hyperlane_core::H256;
-use hyperlane_core::{
-    HyperlaneDomain, HyperlaneDomainProtocol, HyperlaneDomainType, KnownHyperlaneDomain,
-};
+use hyperlane_core::{HyperlaneDomain, HyperlaneDomainProtocol, HyperlaneDomainType};
 use std::sync::Arc;
 
 /// Get the Hyperlane domain for a given chain ID.
 pub fn get_hyperlane_domain(chain_id: u32) -> HyperlaneDomain {
-    match KnownHyperlaneDomain::try_from(chain_id) {
-        Ok(domain) => HyperlaneDomain::Known(domain),
-        Err(_) => HyperlaneDomain::Unknown {
-            domain_id: chain_id,
-            domain_name: "Unknown".to_string(),
-            domain_type: HyperlaneDomainType::Unknown,
-            domain_protocol: HyperlaneDomainProtocol::Ethereum,
-        },
-    }
+    HyperlaneDomain::from_registry(chain_id).unwrap_or_else(|| HyperlaneDomain::Unknown {
+        domain_id: chain_id,
+        domain_name: "Unknown".to_string(),
+        domain_type: HyperlaneDomainType::Unknown,
+        domain_protocol: HyperlaneDomainProtocol::Ethereum,
+    })
 }
 
 /// Get the chain client.
@@ -79,16 +74,11 @@ pub async fn get_provider(
 /// Print the Hyperlane domain for a given chain ID in a human-readable format.
 pub fn print_hyperlane_domain_details(context: &str, chain_id: u32) {
     let domain = get_hyperlane_domain(chain_id);
-    let domain_name = match &domain {
-        HyperlaneDomain::Known(domain) => format!("{domain:?}"),
-        HyperlaneDomain::Unknown { domain_name, .. } => domain_name.to_owned(),
-    };
-
     println!(
         "{}: {} {} {:?} {:?}",
         context,
         chain_id,
-        domain_name,
+        domain.name(),
         domain.domain_protocol(),
         domain.domain_type()
     );
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 use crate::arg::*;
 use color_eyre::eyre::eyre;
 use color_eyre::{eyre::Context, Report, Result};
-use hyperlane_core::{H160, H256, U256};
+use hyperlane_core::{body::MessageBodyDecoders, H160, H256, U256};
 use relayer::settings::matching_list::MatchItem;
 use relayer::settings::MatchingList;
 
@@ -57,6 +57,8 @@ pub struct Params {
     pub start_block: i32,
     /// End block for query.
     pub end_block: i32,
+    /// Decoders for the bodies of queried messages by recipient.
+    pub body_decoders: MessageBodyDecoders,
 }
 
 impl TryFrom<CliArgs> for Params {
@@ -64,6 +66,7 @@ impl TryFrom<CliArgs> for Params {
 
     fn try_from(args: CliArgs) -> Result<Self> {
         let matching_list = matching_list_from_args(&args)?;
+        let body_decoders = body_decoders_from_args(&args)?;
 
         Ok(Self {
             rpc_url: args.url,
@@ -92,10 +95,27 @@ impl TryFrom<CliArgs> for Params {
             matching_list,
             start_block: args.start_block,
             end_block: args.end_block,
+            body_decoders,
         })
     }
 }
 
+fn body_decoders_from_args(args: &CliArgs) -> Result<MessageBodyDecoders> {
+    let mut body_decoders = MessageBodyDecoders::default();
+    for arg in &args.body_decoder {
+        let (recipient, decoder) = arg
+            .split_once('=')
+            .ok_or_else(|| eyre!("Expected RECIPIENT=DECODER for body decoder, got '{arg}'"))?;
+        let recipient: H256 = if recipient.len() <= 42 {
+            recipient.parse::<H160>()?.into()
+        } else {
+            recipient.parse()?
+        };
+        body_decoders.register_builtin(recipient, decoder)?;
+    }
+    Ok(body_decoders)
+}
+
 fn read_payload(hex_str: &Option<String>, file: &Option<PathBuf>) -> Result<Vec<u8>> {
     if file.is_some() == hex_str.is_some() {
         return Err(eyre!("Specify exactly one of --payload and --file"));
@@ -158,7 +158,7 @@ impl MailboxLogItem<'_> {
     pub fn hyperlane_message(&self) -> Result<Option<HyperlaneMessage>> {
         Ok(if let Some(dispatch) = self.to_dispatch_event()? {
             let raw_message: RawHyperlaneMessage = dispatch.message.to_vec();
-            Some(raw_message.into())
+            Some(raw_message.try_into()?)
         } else {
             None
         })
@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::{
     sync::Arc,
@@ -13,7 +14,7 @@ use tracing::{debug, warn};
 
 use hyperlane_core::{
     ChainResult, ContractSyncCursor, HyperlaneMessage, HyperlaneMessageStore,
-    HyperlaneWatermarkedLogStore, Indexer, LogMeta, MessageIndexer,
+    HyperlaneWatermarkedLogStore, Indexer, LogMeta, MessageIndexer, H256,
 };
 
 use crate::contract_sync::eta_calculator::SyncerEtaCalculator;
@@ -21,6 +22,12 @@ use crate::contract_sync::eta_calculator::SyncerEtaCalculator;
 /// Time window for the moving average used in the eta calculator in seconds.
 const ETA_TIME_WINDOW: f64 = 2. * 60.;
 
+/// Number of most recently indexed messages which are checked for reorgs.
+const REORG_CHECK_DEPTH: u32 = 20;
+
+/// How often the most recently indexed messages are checked for reorgs.
+const REORG_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 /// A struct that holds the data needed for forwards and backwards
 /// message sync cursors.
 #[derive(Debug, new)]
@@ -34,6 +41,9 @@ pub(crate) struct MessageSyncCursor {
     next_block: u32,
     /// The next nonce that the cursor is looking for.
     next_nonce: u32,
+    /// When the indexed messages were last checked for reorgs.
+    #[new(value = "Instant::now()")]
+    last_reorg_check: Instant,
 }
 
 impl MessageSyncCursor {
@@ -53,6 +63,73 @@ impl MessageSyncCursor {
         }
     }
 
+    /// Re-fetch the logs of the most recently indexed messages and check
+    /// that they are still in the blocks they were indexed from. Returns the
+    /// lowest nonce which no longer matches the chain, if any.
+    async fn find_reorged_nonce(&self) -> ChainResult<Option<u32>> {
+        let mut by_block: BTreeMap<u32, Vec<(u32, H256, H256)>> = BTreeMap::new();
+        for nonce in self.next_nonce.saturating_sub(REORG_CHECK_DEPTH)..self.next_nonce {
+            let Some(message) = self.retrieve_message_by_nonce(nonce).await else {
+                continue;
+            };
+            // Stores which don't keep block hashes can't be checked
+            let Ok(Some(block_hash)) = self.db.retrieve_dispatched_block_hash(nonce).await else {
+                continue;
+            };
+            let Some(block_number) = self.retrieve_dispatched_block_number(nonce).await else {
+                continue;
+            };
+            by_block
+                .entry(block_number)
+                .or_default()
+                .push((nonce, message.id(), block_hash));
+        }
+
+        let mut reorged_nonce: Option<u32> = None;
+        for (block_number, stored) in by_block {
+            let logs = self.indexer.fetch_logs(block_number, block_number).await?;
+            for (nonce, id, block_hash) in stored {
+                let canonical = logs.iter().any(|(message, meta)| {
+                    message.nonce == nonce && message.id() == id && meta.block_hash == block_hash
+                });
+                if !canonical {
+                    reorged_nonce = Some(reorged_nonce.map_or(nonce, |n| n.min(nonce)));
+                }
+            }
+        }
+        Ok(reorged_nonce)
+    }
+
+    /// If any of the most recently indexed messages were reorged out, roll
+    /// them back and rewind the cursor so they are indexed again.
+    async fn rollback_reorged_messages(&mut self) -> ChainResult<()> {
+        let Some(reorged_nonce) = self.find_reorged_nonce().await? else {
+            return Ok(());
+        };
+        warn!(
+            reorged_nonce,
+            next_nonce = self.next_nonce,
+            "Indexed messages are no longer canonical, rolling back"
+        );
+        match self.db.rollback_messages(reorged_nonce).await {
+            Ok(removed) => warn!(removed, "Rolled back reorged messages"),
+            Err(err) => {
+                warn!(?err, "Failed to roll back reorged messages");
+                return Ok(());
+            }
+        }
+
+        // Re-index from the block of the last message which is still known to
+        // be canonical, or from the start if there is none.
+        let prev_block = match reorged_nonce.checked_sub(1) {
+            Some(prev_nonce) => self.retrieve_dispatched_block_number(prev_nonce).await,
+            None => None,
+        };
+        self.next_block = prev_block.unwrap_or(self.start_block);
+        self.next_nonce = reorged_nonce;
+        Ok(())
+    }
+
     async fn update(
         &mut self,
         logs: Vec<(HyperlaneMessage, LogMeta)>,
@@ -83,6 +160,11 @@ pub(crate) struct ForwardMessageSyncCursor(MessageSyncCursor);
 
 impl ForwardMessageSyncCursor {
     async fn get_next_range(&mut self) -> ChainResult<Option<(u32, u32, Duration)>> {
+        if self.0.last_reorg_check.elapsed() >= REORG_CHECK_INTERVAL {
+            self.0.last_reorg_check = Instant::now();
+            self.0.rollback_reorged_messages().await?;
+        }
+
         // Check if any new messages have been inserted into the DB,
         // and update the cursor accordingly.
         while self
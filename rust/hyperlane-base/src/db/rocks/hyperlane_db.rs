@@ -8,13 +8,16 @@ use tokio::time::sleep;
 use tracing::{debug, trace};
 
 use hyperlane_core::{
-    HyperlaneDomain, HyperlaneLogStore, HyperlaneMessage, HyperlaneMessageStore,
+    Encode, HyperlaneDomain, HyperlaneLogStore, HyperlaneMessage, HyperlaneMessageStore,
     HyperlaneWatermarkedLogStore, InterchainGasExpenditure, InterchainGasPayment,
     InterchainGasPaymentMeta, LogMeta, H256,
 };
 
 use super::{
-    storage_types::{InterchainGasExpenditureData, InterchainGasPaymentData},
+    storage_types::{
+        DeadLetter, InterchainGasExpenditureData, InterchainGasPaymentData, MerkleTreeSnapshot,
+        PendingMessageState, ProfitabilityTotals, RouteProfitability,
+    },
     DbError, TypedDB, DB,
 };
 
@@ -23,12 +26,21 @@ use super::{
 
 const MESSAGE_ID: &str = "message_id_";
 const MESSAGE_DISPATCHED_BLOCK_NUMBER: &str = "message_dispatched_block_number_";
+const MESSAGE_DISPATCHED_BLOCK_HASH: &str = "message_dispatched_block_hash_";
 const MESSAGE: &str = "message_";
 const NONCE_PROCESSED: &str = "nonce_processed_";
 const GAS_PAYMENT_FOR_MESSAGE_ID: &str = "gas_payment_for_message_id_v2_";
 const GAS_PAYMENT_META_PROCESSED: &str = "gas_payment_meta_processed_v2_";
 const GAS_EXPENDITURE_FOR_MESSAGE_ID: &str = "gas_expenditure_for_message_id_v2_";
+const PENDING_MESSAGE_STATE_FOR_MESSAGE_ID: &str = "pending_message_state_for_message_id_v2_";
+const DEAD_LETTER_FOR_MESSAGE_ID: &str = "dead_letter_for_message_id_";
+const PROFITABILITY_FOR_ROUTE: &str = "profitability_for_route_";
+const PROFITABILITY_FOR_SENDER: &str = "profitability_for_sender_";
+const MESSAGE_ROLLBACK_COUNT: &str = "message_rollback_count";
+const MESSAGE_ROLLBACK_FROM_NONCE: &str = "message_rollback_from_nonce_";
 const LATEST_INDEXED_GAS_PAYMENT_BLOCK: &str = "latest_indexed_gas_payment_block";
+const MERKLE_TREE_SNAPSHOT: &str = "merkle_tree_snapshot_";
+const LATEST_MERKLE_TREE_SNAPSHOT: &str = "latest_merkle_tree_snapshot";
 
 type DbResult<T> = std::result::Result<T, DbError>;
 
@@ -73,11 +85,8 @@ impl HyperlaneRocksDB {
     /// - `nonce` --> `id`
     /// - `id` --> `message`
     /// - `nonce` --> `dispatched block number`
-    fn store_message(
-        &self,
-        message: &HyperlaneMessage,
-        dispatched_block_number: u64,
-    ) -> DbResult<bool> {
+    /// - `nonce` --> `dispatched block hash`
+    fn store_message(&self, message: &HyperlaneMessage, meta: &LogMeta) -> DbResult<bool> {
         if let Ok(Some(_)) = self.retrieve_message_id_by_nonce(&message.nonce) {
             trace!(msg=?message, "Message already stored in db");
             return Ok(false);
@@ -91,7 +100,9 @@ impl HyperlaneRocksDB {
         // - `nonce` --> `id`
         self.store_message_id_by_nonce(&message.nonce, &id)?;
         // - `nonce` --> `dispatched block number`
-        self.store_dispatched_block_number_by_nonce(&message.nonce, &dispatched_block_number)?;
+        self.store_dispatched_block_number_by_nonce(&message.nonce, &meta.block_number)?;
+        // - `nonce` --> `dispatched block hash`
+        self.store_dispatched_block_hash_by_nonce(&message.nonce, &meta.block_hash)?;
         Ok(true)
     }
 
@@ -104,6 +115,62 @@ impl HyperlaneRocksDB {
         }
     }
 
+    /// Remove the message with the given nonce and all messages stored
+    /// contiguously after it, along with their dispatch blocks and delivery
+    /// state. The rollback is recorded so that state derived from the
+    /// messages, e.g. merkle trees, can be rolled back too.
+    pub fn rollback_messages(&self, from_nonce: u32) -> DbResult<u32> {
+        let mut nonce = from_nonce;
+        while let Some(id) = self.retrieve_message_id_by_nonce(&nonce)? {
+            debug!(nonce, ?id, "Rolling back message");
+            self.delete(MESSAGE, id.to_vec())?;
+            self.delete(PENDING_MESSAGE_STATE_FOR_MESSAGE_ID, id.to_vec())?;
+            self.delete(MESSAGE_ID, nonce.to_vec())?;
+            self.delete(MESSAGE_DISPATCHED_BLOCK_NUMBER, nonce.to_vec())?;
+            self.delete(MESSAGE_DISPATCHED_BLOCK_HASH, nonce.to_vec())?;
+            self.delete(NONCE_PROCESSED, nonce.to_vec())?;
+            nonce += 1;
+        }
+
+        let rollbacks = self.retrieve_message_rollback_count()?;
+        self.store_keyed_encodable(MESSAGE_ROLLBACK_FROM_NONCE, &rollbacks, &from_nonce)?;
+        self.store_encodable("", MESSAGE_ROLLBACK_COUNT, &(rollbacks + 1))?;
+        Ok(nonce - from_nonce)
+    }
+
+    /// Number of times messages have been rolled back.
+    pub fn retrieve_message_rollback_count(&self) -> DbResult<u32> {
+        Ok(self
+            .retrieve_decodable("", MESSAGE_ROLLBACK_COUNT)?
+            .unwrap_or_default())
+    }
+
+    /// The lowest nonce rolled back by the rollbacks after the first `seen`
+    /// ones, if there were any, along with the total number of rollbacks.
+    pub fn retrieve_message_rollbacks_since(&self, seen: u32) -> DbResult<(u32, Option<u32>)> {
+        let count = self.retrieve_message_rollback_count()?;
+        let mut lowest: Option<u32> = None;
+        for i in seen..count {
+            if let Some(nonce) = self.retrieve_keyed_decodable(MESSAGE_ROLLBACK_FROM_NONCE, &i)? {
+                lowest = Some(lowest.map_or(nonce, |l: u32| l.min(nonce)));
+            }
+        }
+        Ok((count, lowest))
+    }
+
+    /// Store a snapshot of the message merkle tree, keyed by its number of
+    /// leaves, and mark it as the latest one.
+    pub fn store_merkle_tree_snapshot(&self, snapshot: &MerkleTreeSnapshot) -> DbResult<()> {
+        let count = snapshot.tree.count() as u32;
+        self.store_merkle_tree_snapshot_by_count(&count, snapshot)?;
+        self.store_encodable("", LATEST_MERKLE_TREE_SNAPSHOT, &count)
+    }
+
+    /// Number of leaves of the latest snapshot of the message merkle tree.
+    pub fn retrieve_latest_merkle_tree_snapshot_count(&self) -> DbResult<Option<u32>> {
+        self.retrieve_decodable("", LATEST_MERKLE_TREE_SNAPSHOT)
+    }
+
     // TODO(james): this is a quick-fix for the prover_sync and I don't like it
     /// poll db ever 100 milliseconds waiting for a leaf.
     pub fn wait_for_message_nonce(&self, nonce: u32) -> impl Future<Output = DbResult<H256>> {
@@ -198,6 +265,64 @@ impl HyperlaneRocksDB {
             .complete(message_id))
     }
 
+    /// Retrieve the delivery state of every message the relayer was working
+    /// on, by message id
+    pub fn retrieve_pending_message_states(&self) -> DbResult<Vec<(H256, PendingMessageState)>> {
+        self.retrieve_all_keyed_decodable(PENDING_MESSAGE_STATE_FOR_MESSAGE_ID)
+    }
+
+    /// Remove the delivery state of a message the relayer is done with
+    pub fn delete_pending_message_state_by_message_id(&self, message_id: &H256) -> DbResult<()> {
+        self.delete(PENDING_MESSAGE_STATE_FOR_MESSAGE_ID, message_id.to_vec())
+    }
+
+    /// Retrieve all dead-lettered messages of this domain
+    pub fn retrieve_dead_letters(&self) -> DbResult<Vec<DeadLetter>> {
+        self.retrieve_all_decodable(DEAD_LETTER_FOR_MESSAGE_ID)
+    }
+
+    /// Remove a message from the dead letters
+    pub fn delete_dead_letter_by_message_id(&self, message_id: &H256) -> DbResult<()> {
+        self.delete(DEAD_LETTER_FOR_MESSAGE_ID, message_id.to_vec())
+    }
+
+    /// Add to the profitability totals of the route to `destination` and of
+    /// `sender` on that route. Callers must not update the totals of the same
+    /// route concurrently.
+    pub fn add_to_profitability_totals(
+        &self,
+        destination: u32,
+        sender: H256,
+        delta: &ProfitabilityTotals,
+    ) -> DbResult<()> {
+        for (prefix, sender) in [
+            (PROFITABILITY_FOR_ROUTE, None),
+            (PROFITABILITY_FOR_SENDER, Some(sender)),
+        ] {
+            let mut key = destination.to_vec();
+            key.extend(sender.map(|s| s.to_vec()).unwrap_or_default());
+            let mut route = self
+                .retrieve_decodable(prefix, &key)?
+                .unwrap_or(RouteProfitability {
+                    destination,
+                    sender,
+                    totals: Default::default(),
+                });
+            route.totals.add(delta);
+            self.store_encodable(prefix, &key, &route)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve the profitability totals of every route from this domain
+    /// followed by those of every sender on them
+    pub fn retrieve_profitability_totals(&self) -> DbResult<Vec<RouteProfitability>> {
+        let mut totals: Vec<RouteProfitability> =
+            self.retrieve_all_decodable(PROFITABILITY_FOR_ROUTE)?;
+        totals.extend(self.retrieve_all_decodable(PROFITABILITY_FOR_SENDER)?);
+        Ok(totals)
+    }
+
     /// Retrieve the total gas payment for a message
     pub fn retrieve_gas_expenditure_by_message_id(
         &self,
@@ -216,7 +341,7 @@ impl HyperlaneLogStore<HyperlaneMessage> for HyperlaneRocksDB {
     async fn store_logs(&self, messages: &[(HyperlaneMessage, LogMeta)]) -> Result<u32> {
         let mut stored = 0;
         for (message, meta) in messages {
-            let stored_message = self.store_message(message, meta.block_number)?;
+            let stored_message = self.store_message(message, meta)?;
             if stored_message {
                 stored += 1;
             }
@@ -258,6 +383,18 @@ impl HyperlaneMessageStore for HyperlaneRocksDB {
         let number = self.retrieve_dispatched_block_number_by_nonce(&nonce)?;
         Ok(number)
     }
+
+    /// Retrieve dispatched block hash by message nonce
+    async fn retrieve_dispatched_block_hash(&self, nonce: u32) -> Result<Option<H256>> {
+        let hash = self.retrieve_dispatched_block_hash_by_nonce(&nonce)?;
+        Ok(hash)
+    }
+
+    /// Remove messages which were reorged out
+    async fn rollback_messages(&self, from_nonce: u32) -> Result<u32> {
+        let removed = self.rollback_messages(from_nonce)?;
+        Ok(removed)
+    }
 }
 
 /// Note that for legacy reasons this watermark may be shared across multiple cursors, some of which may not have anything to do with gas payments
@@ -306,9 +443,31 @@ macro_rules! make_store_and_retrieve {
 }
 
 make_store_and_retrieve!(pub, message_id_by_nonce, MESSAGE_ID, u32, H256);
-make_store_and_retrieve!(pub(self), message_by_id, MESSAGE, H256, HyperlaneMessage);
+make_store_and_retrieve!(pub, message_by_id, MESSAGE, H256, HyperlaneMessage);
 make_store_and_retrieve!(pub(self), dispatched_block_number_by_nonce, MESSAGE_DISPATCHED_BLOCK_NUMBER, u32, u64);
+make_store_and_retrieve!(pub, dispatched_block_hash_by_nonce, MESSAGE_DISPATCHED_BLOCK_HASH, u32, H256);
 make_store_and_retrieve!(pub, processed_by_nonce, NONCE_PROCESSED, u32, bool);
 make_store_and_retrieve!(pub(self), processed_by_gas_payment_meta, GAS_PAYMENT_META_PROCESSED, InterchainGasPaymentMeta, bool);
 make_store_and_retrieve!(pub(self), interchain_gas_expenditure_data_by_message_id, GAS_EXPENDITURE_FOR_MESSAGE_ID, H256, InterchainGasExpenditureData);
 make_store_and_retrieve!(pub(self), interchain_gas_payment_data_by_message_id, GAS_PAYMENT_FOR_MESSAGE_ID, H256, InterchainGasPaymentData);
+make_store_and_retrieve!(
+    pub,
+    pending_message_state_by_message_id,
+    PENDING_MESSAGE_STATE_FOR_MESSAGE_ID,
+    H256,
+    PendingMessageState
+);
+make_store_and_retrieve!(
+    pub,
+    dead_letter_by_message_id,
+    DEAD_LETTER_FOR_MESSAGE_ID,
+    H256,
+    DeadLetter
+);
+make_store_and_retrieve!(
+    pub,
+    merkle_tree_snapshot_by_count,
+    MERKLE_TREE_SNAPSHOT,
+    u32,
+    MerkleTreeSnapshot
+);
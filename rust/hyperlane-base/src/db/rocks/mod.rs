@@ -6,6 +6,9 @@ use rocksdb::{Options, DB as Rocks};
 use tracing::info;
 
 pub use hyperlane_db::*;
+pub use storage_types::{
+    DeadLetter, MerkleTreeSnapshot, PendingMessageState, ProfitabilityTotals, RouteProfitability,
+};
 pub use typed_db::*;
 
 /// Shared functionality surrounding use of rocksdb
@@ -16,7 +19,7 @@ mod hyperlane_db;
 /// Type-specific db operations
 mod typed_db;
 
-/// Internal-use storage types.
+/// Storage types which are not part of hyperlane-core.
 mod storage_types;
 
 /// Database test utilities.
@@ -103,4 +106,23 @@ impl DB {
     pub fn retrieve(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
         Ok(self.0.get(key)?)
     }
+
+    /// Delete a value from the DB
+    pub fn delete(&self, key: &[u8]) -> Result<()> {
+        Ok(self.0.delete(key)?)
+    }
+
+    /// Retrieve all key-value pairs whose keys start with `prefix`, in key
+    /// order
+    pub fn retrieve_by_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        for entry in self.0.prefix_iterator(prefix) {
+            let (key, value) = entry?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            entries.push((key.into_vec(), value.into_vec()));
+        }
+        Ok(entries)
+    }
 }
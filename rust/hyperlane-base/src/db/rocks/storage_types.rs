@@ -1,10 +1,106 @@
 use std::io::{Read, Write};
 
 use hyperlane_core::{
-    Decode, Encode, HyperlaneProtocolError, InterchainGasExpenditure, InterchainGasPayment, H256,
-    U256,
+    accumulator::{incremental::IncrementalMerkle, TREE_DEPTH},
+    Decode, Encode, HyperlaneMessage, HyperlaneProtocolError, InterchainGasExpenditure,
+    InterchainGasPayment, H256, U256,
 };
 
+/// Delivery state of a message the relayer is working on, persisted so that
+/// the submitter queues can be rebuilt after a restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingMessageState {
+    /// Number of times delivery has been attempted since the last reset.
+    pub num_retries: u32,
+    /// Unix timestamp (in seconds) before which the message should not be
+    /// attempted again.
+    pub next_attempt_after: Option<u64>,
+    /// Whether the message has been submitted and is waiting for
+    /// confirmation.
+    pub submitted: bool,
+    /// Hash of the last transaction submitted to deliver the message.
+    pub submitted_tx: Option<H256>,
+    /// Number of the block the submitted transaction was included in.
+    pub submitted_block_number: Option<u64>,
+    /// Hash of the block the submitted transaction was included in.
+    pub submitted_block_hash: Option<H256>,
+    /// Gas limit estimated when the metadata was last built.
+    pub gas_limit: Option<U256>,
+    /// Unix timestamp (in seconds) of when the relayer first saw the
+    /// message.
+    pub first_seen_at: Option<u64>,
+    /// ISM metadata that was last built for the message.
+    pub metadata: Option<Vec<u8>>,
+}
+
+/// A message the relayer gave up on, kept so operators can inspect it and
+/// re-inject it once the underlying issue is fixed.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// The message that was given up on.
+    pub message: HyperlaneMessage,
+    /// Machine readable class of the error, e.g. `recipient_not_contract`.
+    pub error_class: String,
+    /// Human readable reason the message was given up on.
+    pub reason: String,
+    /// Unix timestamp (in seconds) of when the message was given up on.
+    pub timestamp: u64,
+}
+
+/// Running totals of what relaying messages from this domain earned and
+/// cost the relayer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfitabilityTotals {
+    /// Number of messages the relayer delivered.
+    pub messages_delivered: u64,
+    /// Number of times a message was submitted, including reverted attempts.
+    pub submissions: u64,
+    /// Gas payments of the delivered messages, in the origin's native token.
+    pub tokens_received: U256,
+    /// Destination gas paid for by the delivered messages.
+    pub gas_paid_for: U256,
+    /// Destination gas used by the submissions.
+    pub gas_used: U256,
+    /// Native tokens spent on the destination by the submissions.
+    pub tokens_spent: U256,
+}
+
+impl ProfitabilityTotals {
+    /// Add another set of totals to these.
+    pub fn add(&mut self, other: &Self) {
+        self.messages_delivered = self
+            .messages_delivered
+            .saturating_add(other.messages_delivered);
+        self.submissions = self.submissions.saturating_add(other.submissions);
+        self.tokens_received = self.tokens_received.saturating_add(other.tokens_received);
+        self.gas_paid_for = self.gas_paid_for.saturating_add(other.gas_paid_for);
+        self.gas_used = self.gas_used.saturating_add(other.gas_used);
+        self.tokens_spent = self.tokens_spent.saturating_add(other.tokens_spent);
+    }
+}
+
+/// Profitability totals of the route from this domain to a destination, or
+/// of a single sender on that route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteProfitability {
+    /// Destination domain of the route.
+    pub destination: u32,
+    /// Sender the totals are restricted to, or None for the whole route.
+    pub sender: Option<H256>,
+    /// The totals.
+    pub totals: ProfitabilityTotals,
+}
+
+/// Frontier of the merkle tree of this domain's messages, persisted so the
+/// tree can be restored without re-ingesting every message.
+#[derive(Debug, Clone, Copy)]
+pub struct MerkleTreeSnapshot {
+    /// The tree after ingesting the first `tree.count()` messages.
+    pub tree: IncrementalMerkle,
+    /// Number of message rollbacks the tree accounted for.
+    pub rollbacks: u32,
+}
+
 /// Subset of `InterchainGasPayment` excluding the message id which is stored in
 /// the key.
 #[derive(Debug, Copy, Clone)]
@@ -120,3 +216,226 @@ impl Decode for InterchainGasExpenditureData {
         })
     }
 }
+
+impl Encode for PendingMessageState {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let mut written = self.num_retries.write_to(writer)?;
+        written += self.next_attempt_after.is_some().write_to(writer)?;
+        written += self
+            .next_attempt_after
+            .unwrap_or_default()
+            .write_to(writer)?;
+        written += self.submitted.write_to(writer)?;
+        written += self.submitted_tx.is_some().write_to(writer)?;
+        written += self.submitted_tx.unwrap_or_default().write_to(writer)?;
+        written += self.submitted_block_number.is_some().write_to(writer)?;
+        written += self
+            .submitted_block_number
+            .unwrap_or_default()
+            .write_to(writer)?;
+        written += self.submitted_block_hash.is_some().write_to(writer)?;
+        written += self
+            .submitted_block_hash
+            .unwrap_or_default()
+            .write_to(writer)?;
+        written += self.gas_limit.is_some().write_to(writer)?;
+        written += self.gas_limit.unwrap_or_default().write_to(writer)?;
+        written += self.first_seen_at.is_some().write_to(writer)?;
+        written += self.first_seen_at.unwrap_or_default().write_to(writer)?;
+        // metadata is variable length so it must come last
+        written += self.metadata.is_some().write_to(writer)?;
+        if let Some(metadata) = &self.metadata {
+            writer.write_all(metadata)?;
+            written += metadata.len();
+        }
+        Ok(written)
+    }
+}
+
+impl Decode for PendingMessageState {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: Read,
+        Self: Sized,
+    {
+        let num_retries = u32::read_from(reader)?;
+        let has_next_attempt_after = bool::read_from(reader)?;
+        let next_attempt_after = u64::read_from(reader)?;
+        let submitted = bool::read_from(reader)?;
+        let has_submitted_tx = bool::read_from(reader)?;
+        let submitted_tx = H256::read_from(reader)?;
+        let has_submitted_block_number = bool::read_from(reader)?;
+        let submitted_block_number = u64::read_from(reader)?;
+        let has_submitted_block_hash = bool::read_from(reader)?;
+        let submitted_block_hash = H256::read_from(reader)?;
+        let has_gas_limit = bool::read_from(reader)?;
+        let gas_limit = U256::read_from(reader)?;
+        let has_first_seen_at = bool::read_from(reader)?;
+        let first_seen_at = u64::read_from(reader)?;
+        let has_metadata = bool::read_from(reader)?;
+        let metadata = if has_metadata {
+            let mut metadata = vec![];
+            reader.read_to_end(&mut metadata)?;
+            Some(metadata)
+        } else {
+            None
+        };
+        Ok(Self {
+            num_retries,
+            next_attempt_after: has_next_attempt_after.then_some(next_attempt_after),
+            submitted,
+            submitted_tx: has_submitted_tx.then_some(submitted_tx),
+            submitted_block_number: has_submitted_block_number.then_some(submitted_block_number),
+            submitted_block_hash: has_submitted_block_hash.then_some(submitted_block_hash),
+            gas_limit: has_gas_limit.then_some(gas_limit),
+            first_seen_at: has_first_seen_at.then_some(first_seen_at),
+            metadata,
+        })
+    }
+}
+
+impl Encode for DeadLetter {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let mut written = self.timestamp.write_to(writer)?;
+        written += write_string(&self.error_class, writer)?;
+        written += write_string(&self.reason, writer)?;
+        // the message body is variable length so it must come last
+        written += self.message.write_to(writer)?;
+        Ok(written)
+    }
+}
+
+impl Decode for DeadLetter {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: Read,
+        Self: Sized,
+    {
+        let timestamp = u64::read_from(reader)?;
+        let error_class = read_string(reader)?;
+        let reason = read_string(reader)?;
+        let message = HyperlaneMessage::read_from(reader)?;
+        Ok(Self {
+            message,
+            error_class,
+            reason,
+            timestamp,
+        })
+    }
+}
+
+impl Encode for ProfitabilityTotals {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        Ok(self.messages_delivered.write_to(writer)?
+            + self.submissions.write_to(writer)?
+            + self.tokens_received.write_to(writer)?
+            + self.gas_paid_for.write_to(writer)?
+            + self.gas_used.write_to(writer)?
+            + self.tokens_spent.write_to(writer)?)
+    }
+}
+
+impl Decode for ProfitabilityTotals {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: Read,
+        Self: Sized,
+    {
+        Ok(Self {
+            messages_delivered: u64::read_from(reader)?,
+            submissions: u64::read_from(reader)?,
+            tokens_received: U256::read_from(reader)?,
+            gas_paid_for: U256::read_from(reader)?,
+            gas_used: U256::read_from(reader)?,
+            tokens_spent: U256::read_from(reader)?,
+        })
+    }
+}
+
+impl Encode for RouteProfitability {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        Ok(self.destination.write_to(writer)?
+            + self.sender.is_some().write_to(writer)?
+            + self.sender.unwrap_or_default().write_to(writer)?
+            + self.totals.write_to(writer)?)
+    }
+}
+
+impl Decode for RouteProfitability {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: Read,
+        Self: Sized,
+    {
+        let destination = u32::read_from(reader)?;
+        let has_sender = bool::read_from(reader)?;
+        let sender = H256::read_from(reader)?;
+        let totals = ProfitabilityTotals::read_from(reader)?;
+        Ok(Self {
+            destination,
+            sender: has_sender.then_some(sender),
+            totals,
+        })
+    }
+}
+
+impl Encode for MerkleTreeSnapshot {
+    fn write_to<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: Write,
+    {
+        let mut written = (self.tree.count() as u32).write_to(writer)?;
+        written += self.rollbacks.write_to(writer)?;
+        for node in self.tree.branch() {
+            written += node.write_to(writer)?;
+        }
+        Ok(written)
+    }
+}
+
+impl Decode for MerkleTreeSnapshot {
+    fn read_from<R>(reader: &mut R) -> Result<Self, HyperlaneProtocolError>
+    where
+        R: Read,
+        Self: Sized,
+    {
+        let count = u32::read_from(reader)?;
+        let rollbacks = u32::read_from(reader)?;
+        let mut branch = [H256::zero(); TREE_DEPTH];
+        for node in branch.iter_mut() {
+            *node = H256::read_from(reader)?;
+        }
+        Ok(Self {
+            tree: IncrementalMerkle::new(branch, count as usize),
+            rollbacks,
+        })
+    }
+}
+
+/// Write a string prefixed with its length.
+fn write_string<W: Write>(s: &str, writer: &mut W) -> std::io::Result<usize> {
+    let written = (s.len() as u32).write_to(writer)?;
+    writer.write_all(s.as_bytes())?;
+    Ok(written + s.len())
+}
+
+/// Read a string written by `write_string`.
+fn read_string<R: Read>(reader: &mut R) -> Result<String, HyperlaneProtocolError> {
+    let len = u32::read_from(reader)?;
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e).into())
+}
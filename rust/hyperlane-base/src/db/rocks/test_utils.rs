@@ -35,7 +35,7 @@ mod test {
         U256,
     };
 
-    use crate::db::HyperlaneRocksDB;
+    use crate::db::{DeadLetter, HyperlaneRocksDB, PendingMessageState, ProfitabilityTotals};
 
     use super::*;
 
@@ -75,4 +75,212 @@ mod test {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn db_stores_and_retrieves_pending_message_state() {
+        run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("db_stores_and_retrieves_pending_message_state"),
+                db,
+            );
+
+            let id = H256::from_low_u64_be(7);
+            assert_eq!(
+                db.retrieve_pending_message_state_by_message_id(&id)
+                    .unwrap(),
+                None
+            );
+
+            let state = PendingMessageState {
+                num_retries: 3,
+                next_attempt_after: Some(1_700_000_000),
+                submitted: true,
+                submitted_tx: Some(H256::from_low_u64_be(8)),
+                submitted_block_number: Some(1234),
+                submitted_block_hash: Some(H256::from_low_u64_be(9)),
+                gas_limit: Some(U256::from(150_000)),
+                first_seen_at: Some(1_699_999_000),
+                metadata: Some(vec![1, 2, 3, 4]),
+            };
+            db.store_pending_message_state_by_message_id(&id, &state)
+                .unwrap();
+            assert_eq!(
+                db.retrieve_pending_message_state_by_message_id(&id)
+                    .unwrap(),
+                Some(state.clone())
+            );
+
+            let empty = PendingMessageState::default();
+            db.store_pending_message_state_by_message_id(&id, &empty)
+                .unwrap();
+            assert_eq!(
+                db.retrieve_pending_message_state_by_message_id(&id)
+                    .unwrap(),
+                Some(empty.clone())
+            );
+
+            let other_id = H256::from_low_u64_be(3);
+            db.store_pending_message_state_by_message_id(&other_id, &state)
+                .unwrap();
+            assert_eq!(
+                db.retrieve_pending_message_states().unwrap(),
+                vec![(other_id, state), (id, empty)]
+            );
+
+            db.delete_pending_message_state_by_message_id(&id).unwrap();
+            assert_eq!(
+                db.retrieve_pending_message_state_by_message_id(&id)
+                    .unwrap(),
+                None
+            );
+            assert_eq!(db.retrieve_pending_message_states().unwrap().len(), 1);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_stores_lists_and_deletes_dead_letters() {
+        run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("db_stores_lists_and_deletes_dead_letters"),
+                db,
+            );
+            assert!(db.retrieve_dead_letters().unwrap().is_empty());
+
+            let dead_letter = |nonce| DeadLetter {
+                message: HyperlaneMessage {
+                    nonce,
+                    body: vec![1, 2, 3],
+                    ..Default::default()
+                },
+                error_class: "recipient_not_contract".into(),
+                reason: "Recipient is not a contract".into(),
+                timestamp: 1_700_000_000,
+            };
+            let (first, second) = (dead_letter(1), dead_letter(2));
+            for dl in [&first, &second] {
+                db.store_dead_letter_by_message_id(&dl.message.id(), dl)
+                    .unwrap();
+            }
+
+            let stored = db
+                .retrieve_dead_letter_by_message_id(&first.message.id())
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                RawHyperlaneMessage::from(&stored.message),
+                RawHyperlaneMessage::from(&first.message)
+            );
+            assert_eq!(stored.error_class, first.error_class);
+            assert_eq!(stored.reason, first.reason);
+            assert_eq!(stored.timestamp, first.timestamp);
+
+            assert_eq!(db.retrieve_dead_letters().unwrap().len(), 2);
+            db.delete_dead_letter_by_message_id(&first.message.id())
+                .unwrap();
+            let remaining = db.retrieve_dead_letters().unwrap();
+            assert_eq!(remaining.len(), 1);
+            assert_eq!(remaining[0].message.nonce, 2);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_accumulates_profitability_totals() {
+        run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("db_accumulates_profitability_totals"),
+                db,
+            );
+            assert!(db.retrieve_profitability_totals().unwrap().is_empty());
+
+            let (alice, bob) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+            let submission = ProfitabilityTotals {
+                submissions: 1,
+                gas_used: U256::from(100),
+                tokens_spent: U256::from(1000),
+                ..Default::default()
+            };
+            let delivery = ProfitabilityTotals {
+                messages_delivered: 1,
+                tokens_received: U256::from(5000),
+                gas_paid_for: U256::from(150),
+                ..Default::default()
+            };
+            db.add_to_profitability_totals(7, alice, &submission)
+                .unwrap();
+            db.add_to_profitability_totals(7, alice, &delivery).unwrap();
+            db.add_to_profitability_totals(7, bob, &submission).unwrap();
+
+            let totals = db.retrieve_profitability_totals().unwrap();
+            assert_eq!(totals.len(), 3);
+            let route = totals.iter().find(|t| t.sender.is_none()).unwrap();
+            assert_eq!(route.destination, 7);
+            assert_eq!(route.totals.messages_delivered, 1);
+            assert_eq!(route.totals.submissions, 2);
+            assert_eq!(route.totals.gas_used, U256::from(200));
+            assert_eq!(route.totals.tokens_spent, U256::from(2000));
+            assert_eq!(route.totals.tokens_received, U256::from(5000));
+            let alice_totals = totals.iter().find(|t| t.sender == Some(alice)).unwrap();
+            assert_eq!(alice_totals.totals.submissions, 1);
+            assert_eq!(alice_totals.totals.gas_paid_for, U256::from(150));
+            let bob_totals = totals.iter().find(|t| t.sender == Some(bob)).unwrap();
+            assert_eq!(bob_totals.totals.messages_delivered, 0);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn db_rolls_back_messages() {
+        run_test_db(|db| async move {
+            let db = HyperlaneRocksDB::new(
+                &HyperlaneDomain::new_test_domain("db_rolls_back_messages"),
+                db,
+            );
+
+            let logs: Vec<_> = (0..5)
+                .map(|nonce| {
+                    let m = HyperlaneMessage {
+                        nonce,
+                        ..Default::default()
+                    };
+                    let meta = LogMeta {
+                        address: H256::zero(),
+                        block_number: nonce as u64,
+                        block_hash: H256::from_low_u64_be(nonce as u64),
+                        transaction_hash: H256::zero(),
+                        transaction_index: 0,
+                        log_index: U256::zero(),
+                    };
+                    (m, meta)
+                })
+                .collect();
+            db.store_logs(&logs).await.unwrap();
+            db.store_processed_by_nonce(&3, &true).unwrap();
+            assert_eq!(db.retrieve_message_rollbacks_since(0).unwrap(), (0, None));
+
+            assert_eq!(db.rollback_messages(3).unwrap(), 2);
+            assert!(db.retrieve_message_by_nonce(2).unwrap().is_some());
+            for nonce in 3..5 {
+                assert!(db.retrieve_message_by_nonce(nonce).unwrap().is_none());
+                assert!(db
+                    .retrieve_message_by_id(&logs[nonce as usize].0.id())
+                    .unwrap()
+                    .is_none());
+                assert!(db
+                    .retrieve_dispatched_block_hash_by_nonce(&nonce)
+                    .unwrap()
+                    .is_none());
+            }
+            assert_eq!(db.retrieve_processed_by_nonce(&3).unwrap(), None);
+
+            db.rollback_messages(1).unwrap();
+            assert_eq!(
+                db.retrieve_message_rollbacks_since(0).unwrap(),
+                (2, Some(1))
+            );
+            assert_eq!(db.retrieve_message_rollbacks_since(2).unwrap(), (2, None));
+        })
+        .await;
+    }
 }
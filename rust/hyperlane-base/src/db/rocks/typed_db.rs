@@ -67,6 +67,38 @@ impl TypedDB {
             .map_err(Into::into)
     }
 
+    /// Delete a value
+    pub fn delete(&self, prefix: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Result<()> {
+        self.db
+            .delete(&self.prefixed_key(prefix.as_ref(), key.as_ref()))
+    }
+
+    /// Retrieve all decodable values stored under a prefix
+    pub fn retrieve_all_decodable<V: Decode>(&self, prefix: impl AsRef<[u8]>) -> Result<Vec<V>> {
+        self.db
+            .retrieve_by_prefix(&self.prefixed_key(prefix.as_ref(), &[]))?
+            .into_iter()
+            .map(|(_, v)| V::read_from(&mut v.as_slice()).map_err(Into::into))
+            .collect()
+    }
+
+    /// Retrieve all decodable kv pairs stored under a prefix
+    pub fn retrieve_all_keyed_decodable<K: Decode, V: Decode>(
+        &self,
+        prefix: impl AsRef<[u8]>,
+    ) -> Result<Vec<(K, V)>> {
+        let prefix = self.prefixed_key(prefix.as_ref(), &[]);
+        self.db
+            .retrieve_by_prefix(&prefix)?
+            .into_iter()
+            .map(|(k, v)| -> Result<(K, V)> {
+                let key = K::read_from(&mut &k[prefix.len()..])?;
+                let value = V::read_from(&mut v.as_slice())?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+
     /// Store encodable kv pair
     pub fn store_keyed_encodable<K: Encode, V: Encode>(
         &self,
@@ -14,9 +14,11 @@ use tokio::task::JoinHandle;
 use tracing::warn;
 
 use ethers_prometheus::{json_rpc_client::JsonRpcClientMetrics, middleware::MiddlewareMetrics};
+use hyperlane_ethereum::SignerPoolMetrics;
 
 use crate::metrics::{
-    json_rpc_client::create_json_rpc_client_metrics, provider::create_provider_metrics,
+    json_rpc_client::create_json_rpc_client_metrics,
+    provider::{create_provider_metrics, create_signer_pool_metrics},
 };
 
 /// Macro to prefix a string with the namespace.
@@ -26,6 +28,11 @@ macro_rules! namespaced {
     };
 }
 
+/// Buckets for message latency histograms, from a few seconds up to a day.
+const MESSAGE_LATENCY_BUCKETS: &[f64] = &[
+    5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0, 21600.0, 86400.0,
+];
+
 /// Metrics for a particular domain
 pub struct CoreMetrics {
     /// Metrics registry for adding new metrics and gathering reports
@@ -43,6 +50,13 @@ pub struct CoreMetrics {
 
     operations_processed_count: IntCounterVec,
     messages_processed_count: IntCounterVec,
+    message_delivery_reorgs: IntCounterVec,
+    unsupported_message_versions: IntCounterVec,
+    malformed_message_logs: IntCounterVec,
+
+    message_delivery_latency: HistogramVec,
+    message_metadata_wait: HistogramVec,
+    message_gas_payment_wait: HistogramVec,
 
     latest_checkpoint: IntGaugeVec,
 
@@ -52,6 +66,10 @@ pub struct CoreMetrics {
 
     /// Set of provider-specific metrics. These only need to get created once.
     provider_metrics: OnceCell<MiddlewareMetrics>,
+
+    /// Metrics for chains with several signers. These only need to get
+    /// created once.
+    signer_pool_metrics: OnceCell<SignerPoolMetrics>,
 }
 
 impl CoreMetrics {
@@ -160,6 +178,69 @@ impl CoreMetrics {
             registry
         )?;
 
+        let message_delivery_reorgs = register_int_counter_vec_with_registry!(
+            opts!(
+                namespaced!("message_delivery_reorgs"),
+                "Number of message deliveries whose transaction was reorged out",
+                const_labels_ref
+            ),
+            &["origin", "remote"],
+            registry
+        )?;
+
+        let unsupported_message_versions = register_int_counter_vec_with_registry!(
+            opts!(
+                namespaced!("unsupported_message_versions"),
+                "Number of messages skipped because their version is not supported",
+                const_labels_ref
+            ),
+            &["origin", "version"],
+            registry
+        )?;
+
+        let malformed_message_logs = register_int_counter_vec_with_registry!(
+            opts!(
+                namespaced!("malformed_message_logs"),
+                "Number of dispatch logs skipped because their message could not be decoded",
+                const_labels_ref
+            ),
+            &["origin"],
+            registry
+        )?;
+
+        let message_delivery_latency = register_histogram_vec_with_registry!(
+            histogram_opts!(
+                namespaced!("message_delivery_latency_seconds"),
+                "Time from a message's dispatch block to the block it was delivered in",
+                MESSAGE_LATENCY_BUCKETS.to_vec(),
+                const_labels.clone()
+            ),
+            &["origin", "remote", "ism_type"],
+            registry
+        )?;
+
+        let message_metadata_wait = register_histogram_vec_with_registry!(
+            histogram_opts!(
+                namespaced!("message_metadata_wait_seconds"),
+                "Time a message waited for its ISM metadata, e.g. a validator quorum",
+                MESSAGE_LATENCY_BUCKETS.to_vec(),
+                const_labels.clone()
+            ),
+            &["origin", "remote", "ism_type"],
+            registry
+        )?;
+
+        let message_gas_payment_wait = register_histogram_vec_with_registry!(
+            histogram_opts!(
+                namespaced!("message_gas_payment_wait_seconds"),
+                "Time a message waited for its gas payment requirement to be met",
+                MESSAGE_LATENCY_BUCKETS.to_vec(),
+                const_labels.clone()
+            ),
+            &["origin", "remote", "ism_type"],
+            registry
+        )?;
+
         Ok(Self {
             agent_name: for_agent.into(),
             registry,
@@ -176,11 +257,19 @@ impl CoreMetrics {
 
             operations_processed_count,
             messages_processed_count,
+            message_delivery_reorgs,
+            unsupported_message_versions,
+            malformed_message_logs,
+
+            message_delivery_latency,
+            message_metadata_wait,
+            message_gas_payment_wait,
 
             latest_checkpoint,
 
             json_rpc_client_metrics: OnceCell::new(),
             provider_metrics: OnceCell::new(),
+            signer_pool_metrics: OnceCell::new(),
         })
     }
 
@@ -193,6 +282,15 @@ impl CoreMetrics {
             .clone()
     }
 
+    /// Create the signer pool metrics attached to this core metrics instance.
+    pub fn signer_pool_metrics(&self) -> SignerPoolMetrics {
+        self.signer_pool_metrics
+            .get_or_init(|| {
+                create_signer_pool_metrics(self).expect("Failed to create signer pool metrics!")
+            })
+            .clone()
+    }
+
     /// Create the json rpc provider metrics attached to this core metrics
     /// instance.
     pub fn json_rpc_client_metrics(&self) -> JsonRpcClientMetrics {
@@ -370,6 +468,71 @@ impl CoreMetrics {
         self.messages_processed_count.clone()
     }
 
+    /// The number of times the block containing the transaction which
+    /// delivered a message was reorged out of the destination chain before
+    /// reaching finality, in which case the message is submitted again.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain we delivered the message to.
+    pub fn message_delivery_reorgs(&self) -> IntCounterVec {
+        self.message_delivery_reorgs.clone()
+    }
+
+    /// The number of messages an agent skipped because it does not support
+    /// their version.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `version`: Version of the message.
+    pub fn unsupported_message_versions(&self) -> IntCounterVec {
+        self.unsupported_message_versions.clone()
+    }
+
+    /// The number of dispatch logs an indexer skipped because the message
+    /// they carry could not be decoded.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the log was emitted on.
+    pub fn malformed_message_logs(&self) -> IntCounterVec {
+        self.malformed_message_logs.clone()
+    }
+
+    /// Seconds from the timestamp of the block a message was dispatched in to
+    /// the timestamp of the block the relayer delivered it in. Only messages
+    /// the relayer delivered itself are observed.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain we delivered the message to.
+    /// - `ism_type`: Module type of the recipient's ISM.
+    pub fn message_delivery_latency(&self) -> HistogramVec {
+        self.message_delivery_latency.clone()
+    }
+
+    /// Seconds from the relayer first preparing a message to being able to
+    /// build its ISM metadata, e.g. waiting for a quorum of validators to
+    /// sign a checkpoint.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain we delivered the message to.
+    /// - `ism_type`: Module type of the recipient's ISM.
+    pub fn message_metadata_wait(&self) -> HistogramVec {
+        self.message_metadata_wait.clone()
+    }
+
+    /// Seconds from the metadata of a message being available to the
+    /// message meeting the gas payment requirement.
+    ///
+    /// Labels:
+    /// - `origin`: Chain the message came from.
+    /// - `remote`: Chain we delivered the message to.
+    /// - `ism_type`: Module type of the recipient's ISM.
+    pub fn message_gas_payment_wait(&self) -> HistogramVec {
+        self.message_gas_payment_wait.clone()
+    }
+
     /// Measure of span durations provided by tracing.
     ///
     /// Labels:
@@ -1,6 +1,7 @@
 use eyre::Result;
 
 use ethers_prometheus::middleware::*;
+use hyperlane_ethereum::SignerPoolMetrics;
 
 use crate::CoreMetrics;
 
@@ -53,3 +54,23 @@ pub(crate) fn create_provider_metrics(metrics: &CoreMetrics) -> Result<Middlewar
         )?)
         .build()?)
 }
+
+pub(crate) fn create_signer_pool_metrics(metrics: &CoreMetrics) -> Result<SignerPoolMetrics> {
+    Ok(SignerPoolMetrics {
+        nonce: metrics.new_int_gauge(
+            "signer_nonce",
+            "Nonce of the latest included transaction of each signer in a pool",
+            &["chain", "wallet_address"],
+        )?,
+        in_flight: metrics.new_int_gauge(
+            "signer_in_flight_transactions",
+            "Transactions sent by each signer in a pool which have not been included yet",
+            &["chain", "wallet_address"],
+        )?,
+        available: metrics.new_int_gauge(
+            "signer_available",
+            "Whether each signer in a pool has enough balance and no stuck transactions",
+            &["chain", "wallet_address"],
+        )?,
+    })
+}
@@ -2,14 +2,15 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::{collections::HashMap, sync::Arc};
 
-use eyre::{eyre, Context, Result};
+use eyre::{eyre, Context, Report, Result};
 use futures_util::future::try_join_all;
 use serde::Deserialize;
 
 use hyperlane_core::{
-    config::*, Delivery, HyperlaneChain, HyperlaneDomain, HyperlaneMessageStore, HyperlaneProvider,
-    HyperlaneWatermarkedLogStore, InterchainGasPaymaster, InterchainGasPayment, Mailbox,
-    MultisigIsm, ValidatorAnnounce, H256,
+    body::MessageBodyDecoders, config::*, Delivery, DomainRegistry, HyperlaneChain,
+    HyperlaneDomain, HyperlaneMessageStore, HyperlaneProvider, HyperlaneWatermarkedLogStore,
+    InterchainGasPaymaster, InterchainGasPayment, Mailbox, MultisigIsm, RawDomainRegistry,
+    ValidatorAnnounce, H160, H256,
 };
 
 use crate::{
@@ -54,6 +55,8 @@ pub struct Settings {
     pub metrics_port: u16,
     /// The tracing configuration
     pub tracing: TracingConfig,
+    /// Decoders used to show the bodies of messages sent to some recipients
+    pub body_decoders: MessageBodyDecoders,
 }
 
 /// Raw base settings.
@@ -64,6 +67,11 @@ pub struct RawSettings {
     defaultsigner: Option<RawSignerConf>,
     metrics: Option<StrOrInt>,
     tracing: Option<TracingConfig>,
+    /// Name of the builtin body decoder to use by recipient address
+    bodydecoders: Option<HashMap<String, String>>,
+    /// Path to a JSON or YAML file of domains to add to or override in the
+    /// bundled domain registry
+    domainregistry: Option<String>,
 }
 
 impl FromRawConf<'_, RawSettings, Option<&HashSet<&str>>> for Settings {
@@ -73,6 +81,10 @@ impl FromRawConf<'_, RawSettings, Option<&HashSet<&str>>> for Settings {
         filter: Option<&HashSet<&str>>,
     ) -> Result<Self, ConfigParsingError> {
         let mut err = ConfigParsingError::default();
+        // chains are resolved against the registry so it must be updated first
+        if let Some(path) = raw.domainregistry {
+            load_domain_registry(&path).take_err(&mut err, || cwp + "domainregistry");
+        }
         let chains: HashMap<String, ChainConf> = if let Some(mut chains) = raw.chains {
             let default_signer: Option<SignerConf> = raw.defaultsigner.and_then(|r| {
                 r.parse_config(&cwp.join("defaultsigner"))
@@ -108,15 +120,46 @@ impl FromRawConf<'_, RawSettings, Option<&HashSet<&str>>> for Settings {
             .and_then(|port| port.try_into().take_err(&mut err, || cwp + "metrics"))
             .unwrap_or(9090);
 
+        let mut body_decoders = MessageBodyDecoders::default();
+        let body_decoders_path = cwp.join("bodydecoders");
+        for (recipient, decoder) in raw.bodydecoders.unwrap_or_default() {
+            let path = || body_decoders_path.join(&recipient);
+            let parsed = if recipient.len() <= 42 {
+                recipient
+                    .parse::<H160>()
+                    .map(H256::from)
+                    .map_err(Report::from)
+            } else {
+                recipient.parse::<H256>().map_err(Report::from)
+            };
+            if let Some(recipient) = parsed.take_err(&mut err, path) {
+                body_decoders
+                    .register_builtin(recipient, &decoder)
+                    .take_err(&mut err, path);
+            }
+        }
+
         err.into_result()?;
         Ok(Self {
             chains,
             metrics_port: metrics,
             tracing,
+            body_decoders,
         })
     }
 }
 
+/// Add the domains in a registry file to the global domain registry.
+fn load_domain_registry(path: &str) -> Result<()> {
+    let raw: RawDomainRegistry = config::Config::builder()
+        .add_source(config::File::with_name(path))
+        .build()?
+        .try_deserialize()
+        .with_context(|| format!("Invalid domain registry file `{path}`"))?;
+    DomainRegistry::extend_global(raw.domains)?;
+    Ok(())
+}
+
 impl Settings {
     /// Generate an agent core
     pub fn build_hyperlane_core(&self, metrics: Arc<CoreMetrics>) -> HyperlaneAgentCore {
@@ -170,6 +213,7 @@ impl Settings {
             chains: self.chains.clone(),
             metrics_port: self.metrics_port,
             tracing: self.tracing.clone(),
+            body_decoders: self.body_decoders.clone(),
         }
     }
 }
@@ -8,10 +8,10 @@ use ethers_prometheus::middleware::{
     ChainInfo, ContractInfo, PrometheusMiddlewareConf, WalletInfo,
 };
 use hyperlane_core::{
-    config::*, ContractLocator, HyperlaneAbi, HyperlaneDomain, HyperlaneDomainProtocol,
-    HyperlaneProvider, HyperlaneSigner, Indexer, InterchainGasPaymaster, InterchainGasPayment,
-    InterchainSecurityModule, Mailbox, MessageIndexer, MultisigIsm, RoutingIsm, ValidatorAnnounce,
-    H160, H256,
+    config::*, AggregationIsm, ContractLocator, HyperlaneAbi, HyperlaneDomain,
+    HyperlaneDomainProtocol, HyperlaneProvider, HyperlaneSigner, Indexer, InterchainGasPaymaster,
+    InterchainGasPayment, InterchainSecurityModule, Mailbox, MessageIndexer, MultisigIsm,
+    RoutingIsm, ValidatorAnnounce, H160, H256,
 };
 use hyperlane_ethereum::{
     self as h_eth, BuildableWithProvider, EthereumInterchainGasPaymasterAbi, EthereumMailboxAbi,
@@ -78,6 +78,9 @@ pub struct CoreContractAddresses {
     pub interchain_gas_paymaster: H256,
     /// Address of the ValidatorAnnounce contract
     pub validator_announce: H256,
+    /// Optional address of a multicall contract used to deliver batches of
+    /// messages in a single transaction
+    pub multicall: Option<H256>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -86,6 +89,7 @@ struct RawCoreContractAddresses {
     mailbox: Option<String>,
     interchain_gas_paymaster: Option<String>,
     validator_announce: Option<String>,
+    multicall: Option<String>,
 }
 
 impl FromRawConf<'_, RawCoreContractAddresses> for CoreContractAddresses {
@@ -97,6 +101,18 @@ impl FromRawConf<'_, RawCoreContractAddresses> for CoreContractAddresses {
         let mut err = ConfigParsingError::default();
 
         macro_rules! parse_addr {
+            (@value $name:ident, $v:expr) => {{
+                let path = || cwp + stringify!($name);
+                let v = $v;
+                if v.len() <= 42 {
+                    v.parse::<H160>().take_err(&mut err, path).map(Into::into)
+                } else {
+                    v.parse().take_err(&mut err, path)
+                }
+            }};
+            (optional $name:ident) => {
+                raw.$name.and_then(|v| parse_addr!(@value $name, v))
+            };
             ($name:ident) => {{
                 let path = || cwp + stringify!($name);
                 raw.$name
@@ -107,25 +123,21 @@ impl FromRawConf<'_, RawCoreContractAddresses> for CoreContractAddresses {
                         )
                     })
                     .take_err(&mut err, path)
-                    .and_then(|v| {
-                        if v.len() <= 42 {
-                            v.parse::<H160>().take_err(&mut err, path).map(Into::into)
-                        } else {
-                            v.parse().take_err(&mut err, path)
-                        }
-                    })
+                    .and_then(|v| parse_addr!(@value $name, v))
             }};
         }
 
         let mb = parse_addr!(mailbox);
         let igp = parse_addr!(interchain_gas_paymaster);
         let va = parse_addr!(validator_announce);
+        let multicall = parse_addr!(optional multicall);
 
         err.into_result()?;
         Ok(Self {
             mailbox: mb.unwrap(),
             interchain_gas_paymaster: igp.unwrap(),
             validator_announce: va.unwrap(),
+            multicall,
         })
     }
 }
@@ -177,6 +189,11 @@ pub struct ChainConf {
     pub domain: HyperlaneDomain,
     /// Signer configuration for this chain
     pub signer: Option<SignerConf>,
+    /// Additional signers to spread transactions across. When set, `signer`
+    /// is the first of them unless configured explicitly.
+    pub signers: Vec<SignerConf>,
+    /// Settings for choosing between signers when there are several
+    pub signer_pool: h_eth::SignerPoolConf,
     /// Number of blocks until finality
     pub finality_blocks: u32,
     /// Addresses of contracts on the chain
@@ -189,6 +206,8 @@ pub struct ChainConf {
     pub metrics_conf: PrometheusMiddlewareConf,
     /// Settings for event indexing
     pub index: IndexSettings,
+    /// Settings for replacing transactions which are not included in time
+    pub transaction_replacement: h_eth::TransactionReplacementConf,
 }
 
 /// A raw chain setup is a domain ID, an address on that chain (where the
@@ -199,6 +218,10 @@ pub struct RawChainConf {
     name: Option<String>,
     domain: Option<StrOrInt>,
     pub(super) signer: Option<RawSignerConf>,
+    #[serde(default)]
+    signers: Option<Vec<RawSignerConf>>,
+    #[serde(default)]
+    signer_pool: Option<h_eth::RawSignerPoolConf>,
     finality_blocks: Option<StrOrInt>,
     addresses: Option<RawCoreContractAddresses>,
     #[serde(flatten, default)]
@@ -208,6 +231,8 @@ pub struct RawChainConf {
     metrics_conf: Option<PrometheusMiddlewareConf>,
     #[serde(default)]
     index: Option<RawIndexSettings>,
+    #[serde(default)]
+    transaction_replacement: Option<h_eth::RawTransactionReplacementConf>,
 }
 
 impl FromRawConf<'_, RawChainConf> for ChainConf {
@@ -255,10 +280,32 @@ impl FromRawConf<'_, RawChainConf> for ChainConf {
                     .take_config_err(&mut err)
             });
 
-        let signer = raw.signer.and_then(|v| -> Option<SignerConf> {
-            v.parse_config(&cwp.join("signer"))
-                .take_config_err(&mut err)
-        });
+        let signers: Vec<SignerConf> = raw
+            .signers
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, v)| {
+                v.parse_config(&cwp.join("signers").join(i.to_string()))
+                    .take_config_err(&mut err)
+            })
+            .collect();
+
+        let signer = raw
+            .signer
+            .and_then(|v| -> Option<SignerConf> {
+                v.parse_config(&cwp.join("signer"))
+                    .take_config_err(&mut err)
+            })
+            .or_else(|| signers.first().cloned());
+
+        let signer_pool = raw
+            .signer_pool
+            .and_then(|v| {
+                v.parse_config(&cwp.join("signerPool"))
+                    .take_config_err(&mut err)
+            })
+            .unwrap_or_default();
 
         let finality_blocks = raw
             .finality_blocks
@@ -274,6 +321,14 @@ impl FromRawConf<'_, RawChainConf> for ChainConf {
             .and_then(|v| v.parse_config(&cwp.join("index")).take_config_err(&mut err))
             .unwrap_or_default();
 
+        let transaction_replacement = raw
+            .transaction_replacement
+            .and_then(|v| {
+                v.parse_config(&cwp.join("transactionReplacement"))
+                    .take_config_err(&mut err)
+            })
+            .unwrap_or_default();
+
         let metrics_conf = raw.metrics_conf.unwrap_or_default();
 
         err.into_result()?;
@@ -282,9 +337,12 @@ impl FromRawConf<'_, RawChainConf> for ChainConf {
             domain: domain.unwrap(),
             addresses: addresses.unwrap(),
             signer,
+            signers,
+            signer_pool,
             finality_blocks,
             index,
             metrics_conf,
+            transaction_replacement,
         })
     }
 }
@@ -313,7 +371,12 @@ impl ChainConf {
                     .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(conf) => h_fuel::make_provider(conf)
+                .map(|provider| {
+                    Box::new(h_fuel::FuelProvider::new(self.domain.clone(), provider))
+                        as Box<dyn HyperlaneProvider>
+                })
+                .map_err(Into::into),
         }
         .context(ctx)
     }
@@ -325,13 +388,21 @@ impl ChainConf {
 
         match &self.connection()? {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::MailboxBuilder {})
-                    .await
+                self.build_ethereum(
+                    conf,
+                    &locator,
+                    metrics,
+                    h_eth::MailboxBuilder {
+                        multicall: self.addresses.multicall,
+                        transaction_replacement: self.transaction_replacement.clone(),
+                    },
+                )
+                .await
             }
 
             ChainConnectionConf::Fuel(conf) => {
-                let wallet = self.fuel_signer().await.context(ctx)?;
-                hyperlane_fuel::FuelMailbox::new(conf, locator, wallet)
+                let wallet = self.fuel_wallet().await.context(ctx)?;
+                h_fuel::FuelMailbox::new(conf, locator, wallet)
                     .map(|m| Box::new(m) as Box<dyn Mailbox>)
                     .map_err(Into::into)
             }
@@ -355,12 +426,15 @@ impl ChainConf {
                     metrics,
                     h_eth::MessageIndexerBuilder {
                         finality_blocks: self.finality_blocks,
+                        malformed_messages: metrics
+                            .malformed_message_logs()
+                            .with_label_values(&[self.domain.name()]),
                     },
                 )
                 .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -386,7 +460,7 @@ impl ChainConf {
                 .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -411,7 +485,7 @@ impl ChainConf {
                 .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -438,7 +512,7 @@ impl ChainConf {
                 .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -451,11 +525,18 @@ impl ChainConf {
         let locator = self.locator(self.addresses.validator_announce);
         match &self.connection()? {
             ChainConnectionConf::Ethereum(conf) => {
-                self.build_ethereum(conf, &locator, metrics, h_eth::ValidatorAnnounceBuilder {})
-                    .await
+                self.build_ethereum(
+                    conf,
+                    &locator,
+                    metrics,
+                    h_eth::ValidatorAnnounceBuilder {
+                        transaction_replacement: self.transaction_replacement.clone(),
+                    },
+                )
+                .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context("Building ValidatorAnnounce")
     }
@@ -481,7 +562,7 @@ impl ChainConf {
                 .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -501,7 +582,7 @@ impl ChainConf {
                     .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -524,7 +605,27 @@ impl ChainConf {
                     .await
             }
 
-            ChainConnectionConf::Fuel(_) => todo!(),
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
+        }
+        .context(ctx)
+    }
+
+    /// Try to convert the chain setting into an AggregationIsm Ism contract
+    pub async fn build_aggregation_ism(
+        &self,
+        address: H256,
+        metrics: &CoreMetrics,
+    ) -> Result<Box<dyn AggregationIsm>> {
+        let ctx = "Building aggregation ISM";
+        let locator = self.locator(address);
+
+        match &self.connection()? {
+            ChainConnectionConf::Ethereum(conf) => {
+                self.build_ethereum(conf, &locator, metrics, h_eth::AggregationIsmBuilder {})
+                    .await
+            }
+
+            ChainConnectionConf::Fuel(_) => Err(eyre!("Not yet supported on Fuel")),
         }
         .context(ctx)
     }
@@ -537,14 +638,30 @@ impl ChainConf {
         }
     }
 
-    async fn ethereum_signer(&self) -> Result<Option<h_eth::Signers>> {
-        self.signer().await
+    /// Build the signers to send ethereum transactions from: the configured
+    /// `signer` followed by any additional `signers` which differ from it.
+    async fn ethereum_signers(&self) -> Result<Vec<h_eth::Signers>> {
+        let mut signers: Vec<h_eth::Signers> = Vec::new();
+        for conf in self.signer.iter().chain(self.signers.iter()) {
+            let signer: h_eth::Signers = conf.build().await?;
+            if !signers
+                .iter()
+                .any(|s| s.eth_address() == signer.eth_address())
+            {
+                signers.push(signer);
+            }
+        }
+        Ok(signers)
     }
 
-    async fn fuel_signer(&self) -> Result<fuels::prelude::WalletUnlocked> {
-        self.signer().await.and_then(|opt| {
-            opt.ok_or_else(|| eyre!("Fuel requires a signer to construct contract instances"))
-        })
+    /// Fuel contract instances need a wallet even for read-only calls, which
+    /// are simulated without spending its coins. Without a configured signer a
+    /// random wallet is used, which can't pay for transactions.
+    async fn fuel_wallet(&self) -> Result<fuels::prelude::WalletUnlocked> {
+        Ok(self
+            .signer()
+            .await?
+            .unwrap_or_else(|| fuels::prelude::WalletUnlocked::new_random(None)))
     }
 
     /// Get a clone of the ethereum metrics conf with correctly configured
@@ -552,7 +669,7 @@ impl ChainConf {
     fn metrics_conf(
         &self,
         agent_name: &str,
-        signer: &Option<impl HyperlaneSigner>,
+        signers: &[impl HyperlaneSigner],
     ) -> PrometheusMiddlewareConf {
         let mut cfg = self.metrics_conf.clone();
 
@@ -562,7 +679,7 @@ impl ChainConf {
             });
         }
 
-        if let Some(signer) = signer {
+        for signer in signers {
             cfg.wallets
                 .entry(signer.eth_address())
                 .or_insert_with(|| WalletInfo {
@@ -618,12 +735,17 @@ impl ChainConf {
     where
         B: BuildableWithProvider + Sync,
     {
-        let signer = self.ethereum_signer().await?;
-        let metrics_conf = self.metrics_conf(metrics.agent_name(), &signer);
+        let signers = self.ethereum_signers().await?;
+        let metrics_conf = self.metrics_conf(metrics.agent_name(), &signers);
+        let signer_pool = (!signers.is_empty()).then(|| h_eth::SignerPool {
+            signers,
+            conf: self.signer_pool.clone(),
+            metrics: Some(metrics.signer_pool_metrics()),
+        });
         let rpc_metrics = Some(metrics.json_rpc_client_metrics());
         let middleware_metrics = Some((metrics.provider_metrics(), metrics_conf));
         let res = builder
-            .build_with_connection_conf(conf, locator, signer, rpc_metrics, middleware_metrics)
+            .build_with_connection_conf(conf, locator, signer_pool, rpc_metrics, middleware_metrics)
             .await;
         Ok(res?)
     }
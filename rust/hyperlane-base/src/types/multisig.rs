@@ -1,33 +1,68 @@
 use std::collections::{hash_map::Entry, HashMap};
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use derive_new::new;
 use ethers::prelude::Address;
 use eyre::Result;
+use futures_util::{future::join_all, stream::FuturesUnordered, StreamExt};
+use tokio::time::timeout;
 use tracing::{debug, instrument, trace};
 
 use hyperlane_core::{
-    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, SignedCheckpointWithSigner,
-    H160, H256,
+    Checkpoint, CheckpointWithMessageId, MultisigSignedCheckpoint, Signable,
+    SignedCheckpointWithSigner, SignedType, H160, H256,
 };
 
 use crate::CheckpointSyncer;
 
+/// How long to wait on a single validator's checkpoint syncer before giving up
+/// on it.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a validator's latest index is reused for before fetching it again.
+const LATEST_INDEX_TTL: Duration = Duration::from_secs(5);
+
+/// How many indices below the highest quorum index to look for a quorum at
+/// concurrently.
+const RANGE_FETCH_CONCURRENCY: usize = 4;
+
 /// Fetches signed checkpoints from multiple validators to create
 /// MultisigSignedCheckpoints
 #[derive(Clone, Debug, new)]
 pub struct MultisigCheckpointSyncer {
     /// The checkpoint syncer for each valid validator signer address
     checkpoint_syncers: HashMap<Address, Arc<dyn CheckpointSyncer>>,
+    /// The latest index of each validator and when it was fetched
+    #[new(default)]
+    latest_indices: Arc<Mutex<HashMap<Address, (Instant, u32)>>>,
+}
+
+/// A checkpoint type validators sign which a quorum can be fetched for.
+trait QuorumCheckpoint: Signable + Eq + Copy {
+    fn checkpoint(&self) -> &Checkpoint;
+}
+
+impl QuorumCheckpoint for Checkpoint {
+    fn checkpoint(&self) -> &Checkpoint {
+        self
+    }
+}
+
+impl QuorumCheckpoint for CheckpointWithMessageId {
+    fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
 }
 
 impl MultisigCheckpointSyncer {
     /// Attempts to get the latest checkpoint with a quorum of signatures among
     /// validators.
     ///
-    /// First iterates through the `latest_index` of each validator's checkpoint
-    /// syncer, looking for the highest index that >= `threshold` validators
-    /// have returned.
+    /// First fetches the `latest_index` of each validator's checkpoint syncer,
+    /// looking for the highest index that >= `threshold` validators have
+    /// returned.
     ///
     /// Attempts to find a quorum of signed checkpoints from that index,
     /// iterating backwards if unsuccessful, until the (optional) index is
@@ -42,56 +77,14 @@ impl MultisigCheckpointSyncer {
         minimum_index: u32,
         maximum_index: u32,
     ) -> Result<Option<MultisigSignedCheckpoint<Checkpoint>>> {
-        // Get the latest_index from each validator's checkpoint syncer.
-        let mut latest_indices = Vec::with_capacity(validators.len());
-        for validator in validators {
-            let address = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&address) {
-                // Gracefully handle errors getting the latest_index
-                match checkpoint_syncer.latest_index().await {
-                    Ok(Some(index)) => {
-                        trace!(?address, ?index, "Validator returned latest index");
-                        latest_indices.push(index);
-                    }
-                    err => {
-                        debug!(?address, ?err, "Failed to get latest index from validator");
-                    }
-                }
-            }
-        }
-        debug!(
-            ?latest_indices,
-            "Fetched latest indices from checkpoint syncers"
-        );
-
-        if latest_indices.is_empty() {
-            debug!("No validators returned a latest index");
-            return Ok(None);
-        }
-
-        // Sort in descending order. The n'th index will represent
-        // the highest index for which we (supposedly) have (n+1) signed checkpoints
-        latest_indices.sort_by(|a, b| b.cmp(a));
-        if let Some(&highest_quorum_index) = latest_indices.get(threshold - 1) {
-            // The highest viable checkpoint index is the minimum of the highest index
-            // we (supposedly) have a quorum for, and the maximum index for which we can
-            // generate a proof.
-            let start_index = highest_quorum_index.min(maximum_index);
-            if minimum_index > start_index {
-                debug!(%start_index, %highest_quorum_index, "Highest quorum index is below the minimum index");
-                return Ok(None);
-            }
-            for index in (minimum_index..=start_index).rev() {
-                if let Ok(Some(checkpoint)) = self
-                    .legacy_fetch_checkpoint(index, validators, threshold)
-                    .await
-                {
-                    return Ok(Some(checkpoint));
-                }
-            }
-        }
-        debug!("No checkpoint found in range");
-        Ok(None)
+        self.fetch_quorum_in_range(
+            validators,
+            threshold,
+            minimum_index,
+            maximum_index,
+            |syncer, index| async move { syncer.legacy_fetch_checkpoint(index).await },
+        )
+        .await
     }
 
     /// Fetches a MultisigSignedCheckpoint if there is a quorum.
@@ -103,99 +96,18 @@ impl MultisigCheckpointSyncer {
         validators: &[H256],
         threshold: usize,
     ) -> Result<Option<MultisigSignedCheckpoint<Checkpoint>>> {
-        // Keeps track of signed validator checkpoints for a particular root.
-        // In practice, it's likely that validators will all sign the same root for a
-        // particular index, but we'd like to be robust to this not being the case
-        let mut signed_checkpoints_per_root: HashMap<
-            H256,
-            Vec<SignedCheckpointWithSigner<Checkpoint>>,
-        > = HashMap::new();
-
-        for validator in validators {
-            let addr = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) {
-                // Gracefully ignore an error fetching the checkpoint from a validator's
-                // checkpoint syncer, which can happen if the validator has not
-                // signed the checkpoint at `index`.
-                if let Ok(Some(signed_checkpoint)) =
-                    checkpoint_syncer.legacy_fetch_checkpoint(index).await
-                {
-                    // If the signed checkpoint is for a different index, ignore it
-                    if signed_checkpoint.value.index != index {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            checkpoint_index = signed_checkpoint.value.index,
-                            "Checkpoint index mismatch"
-                        );
-                        continue;
-                    }
-                    // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
-                    if H256::from(signer) != *validator {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            "Checkpoint signature mismatch"
-                        );
-                        continue;
-                    }
-
-                    // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
-                    let signed_checkpoint_with_signer = SignedCheckpointWithSigner {
-                        signer,
-                        signed_checkpoint,
-                    };
-                    let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
-
-                    let signature_count = match signed_checkpoints_per_root.entry(root) {
-                        Entry::Occupied(mut entry) => {
-                            let vec = entry.get_mut();
-                            vec.push(signed_checkpoint_with_signer);
-                            vec.len()
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(vec![signed_checkpoint_with_signer]);
-                            1 // length of 1
-                        }
-                    };
-                    debug!(
-                        validator = format!("{validator:#x}"),
-                        index,
-                        root = format!("{root:#x}"),
-                        signature_count,
-                        "Found signed checkpoint"
-                    );
-                    // If we've hit a quorum, create a MultisigSignedCheckpoint
-                    if signature_count >= threshold {
-                        if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
-                            let checkpoint =
-                                MultisigSignedCheckpoint::try_from(signed_checkpoints)?;
-                            debug!(?checkpoint, "Fetched multisig checkpoint");
-                            return Ok(Some(checkpoint));
-                        }
-                    }
-                } else {
-                    debug!(
-                        validator = format!("{validator:#x}"),
-                        index = index,
-                        "Unable to find signed checkpoint"
-                    );
-                }
-            } else {
-                debug!(%validator, "Unable to find checkpoint syncer");
-                continue;
-            }
-        }
-        Ok(None)
+        self.fetch_quorum(validators, threshold, index, |syncer| async move {
+            syncer.legacy_fetch_checkpoint(index).await
+        })
+        .await
     }
 
     /// Attempts to get the latest checkpoint with a quorum of signatures among
     /// validators.
     ///
-    /// First iterates through the `latest_index` of each validator's checkpoint
-    /// syncer, looking for the highest index that >= `threshold` validators
-    /// have returned.
+    /// First fetches the `latest_index` of each validator's checkpoint syncer,
+    /// looking for the highest index that >= `threshold` validators have
+    /// returned.
     ///
     /// Attempts to find a quorum of signed checkpoints from that index,
     /// iterating backwards if unsuccessful, until the (optional) index is
@@ -210,23 +122,45 @@ impl MultisigCheckpointSyncer {
         minimum_index: u32,
         maximum_index: u32,
     ) -> Result<Option<MultisigSignedCheckpoint<CheckpointWithMessageId>>> {
-        // Get the latest_index from each validator's checkpoint syncer.
-        let mut latest_indices = Vec::with_capacity(validators.len());
-        for validator in validators {
-            let address = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&address) {
-                // Gracefully handle errors getting the latest_index
-                match checkpoint_syncer.latest_index().await {
-                    Ok(Some(index)) => {
-                        trace!(?address, ?index, "Validator returned latest index");
-                        latest_indices.push(index);
-                    }
-                    err => {
-                        debug!(?address, ?err, "Failed to get latest index from validator");
-                    }
-                }
-            }
-        }
+        self.fetch_quorum_in_range(
+            validators,
+            threshold,
+            minimum_index,
+            maximum_index,
+            |syncer, index| async move { syncer.fetch_checkpoint(index).await },
+        )
+        .await
+    }
+
+    /// Fetches a MultisigSignedCheckpointWithMessageId if there is a quorum.
+    /// Returns Ok(None) if there is no quorum.
+    #[instrument(err, skip(self))]
+    pub async fn fetch_checkpoint(
+        &self,
+        validators: &[H256],
+        threshold: usize,
+        index: u32,
+    ) -> Result<Option<MultisigSignedCheckpoint<CheckpointWithMessageId>>> {
+        self.fetch_quorum(validators, threshold, index, |syncer| async move {
+            syncer.fetch_checkpoint(index).await
+        })
+        .await
+    }
+
+    async fn fetch_quorum_in_range<T, F, Fut>(
+        &self,
+        validators: &[H256],
+        threshold: usize,
+        minimum_index: u32,
+        maximum_index: u32,
+        fetch: F,
+    ) -> Result<Option<MultisigSignedCheckpoint<T>>>
+    where
+        T: QuorumCheckpoint,
+        F: Fn(Arc<dyn CheckpointSyncer>, u32) -> Fut,
+        Fut: Future<Output = Result<Option<SignedType<T>>>>,
+    {
+        let mut latest_indices = self.latest_indices(validators).await;
         debug!(
             ?latest_indices,
             "Fetched latest indices from checkpoint syncers"
@@ -249,113 +183,191 @@ impl MultisigCheckpointSyncer {
                 debug!(%start_index, %highest_quorum_index, "Highest quorum index is below the minimum index");
                 return Ok(None);
             }
-            for index in (minimum_index..=start_index).rev() {
-                if let Ok(Some(checkpoint)) =
-                    self.fetch_checkpoint(validators, threshold, index).await
-                {
+
+            // Usually there is a quorum at the start index, so only look
+            // at more indices at once if there isn't.
+            let mut indices = (minimum_index..=start_index).rev();
+            let mut batch_size = 1;
+            loop {
+                let batch: Vec<u32> = indices.by_ref().take(batch_size).collect();
+                if batch.is_empty() {
+                    break;
+                }
+                let results = join_all(batch.iter().map(|&index| {
+                    let fetch = &fetch;
+                    self.fetch_quorum(validators, threshold, index, move |syncer| {
+                        fetch(syncer, index)
+                    })
+                }))
+                .await;
+                // The batch is in descending order, so the first quorum found
+                // is for the highest index
+                if let Some(checkpoint) = results.into_iter().find_map(|r| r.ok().flatten()) {
                     return Ok(Some(checkpoint));
                 }
+                batch_size = RANGE_FETCH_CONCURRENCY;
             }
         }
         debug!("No checkpoint found in range");
         Ok(None)
     }
 
-    /// Fetches a MultisigSignedCheckpointWithMessageId if there is a quorum.
-    /// Returns Ok(None) if there is no quorum.
-    #[instrument(err, skip(self))]
-    pub async fn fetch_checkpoint(
+    /// Fetches the signed checkpoints at `index` from all validators
+    /// concurrently, returning as soon as `threshold` of them agree on a
+    /// root. Returns Ok(None) if there is no quorum.
+    async fn fetch_quorum<T, F, Fut>(
         &self,
         validators: &[H256],
         threshold: usize,
         index: u32,
-    ) -> Result<Option<MultisigSignedCheckpoint<CheckpointWithMessageId>>> {
+        fetch: F,
+    ) -> Result<Option<MultisigSignedCheckpoint<T>>>
+    where
+        T: QuorumCheckpoint,
+        F: Fn(Arc<dyn CheckpointSyncer>) -> Fut,
+        Fut: Future<Output = Result<Option<SignedType<T>>>>,
+    {
+        let mut fetches = validators
+            .iter()
+            .filter_map(|validator| {
+                let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&H160::from(*validator)) else {
+                    debug!(%validator, "Unable to find checkpoint syncer");
+                    return None;
+                };
+                let fetch = timeout(FETCH_TIMEOUT, fetch(checkpoint_syncer.clone()));
+                Some(async move { (validator, fetch.await) })
+            })
+            .collect::<FuturesUnordered<_>>();
+
         // Keeps track of signed validator checkpoints for a particular root.
         // In practice, it's likely that validators will all sign the same root for a
         // particular index, but we'd like to be robust to this not being the case
-        let mut signed_checkpoints_per_root: HashMap<
-            H256,
-            Vec<SignedCheckpointWithSigner<CheckpointWithMessageId>>,
-        > = HashMap::new();
-
-        for validator in validators.iter() {
-            let addr = H160::from(*validator);
-            if let Some(checkpoint_syncer) = self.checkpoint_syncers.get(&addr) {
-                // Gracefully ignore an error fetching the checkpoint from a validator's
-                // checkpoint syncer, which can happen if the validator has not
-                // signed the checkpoint at `index`.
-                if let Ok(Some(signed_checkpoint)) = checkpoint_syncer.fetch_checkpoint(index).await
-                {
-                    // If the signed checkpoint is for a different index, ignore it
-                    if signed_checkpoint.value.index != index {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            checkpoint_index = signed_checkpoint.value.index,
-                            "Checkpoint index mismatch"
-                        );
-                        continue;
-                    }
-                    // Ensure that the signature is actually by the validator
-                    let signer = signed_checkpoint.recover()?;
-                    if H256::from(signer) != *validator {
-                        debug!(
-                            validator = format!("{:#x}", validator),
-                            index = index,
-                            "Checkpoint signature mismatch"
-                        );
-                        continue;
-                    }
+        let mut signed_checkpoints_per_root: HashMap<H256, Vec<SignedCheckpointWithSigner<T>>> =
+            HashMap::new();
 
-                    // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
-                    let signed_checkpoint_with_signer =
-                        SignedCheckpointWithSigner::<CheckpointWithMessageId> {
-                            signer,
-                            signed_checkpoint,
-                        };
-                    let root = signed_checkpoint_with_signer.signed_checkpoint.value.root;
-
-                    let signature_count = match signed_checkpoints_per_root.entry(root) {
-                        Entry::Occupied(mut entry) => {
-                            let vec = entry.get_mut();
-                            vec.push(signed_checkpoint_with_signer);
-                            vec.len()
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(vec![signed_checkpoint_with_signer]);
-                            1 // length of 1
-                        }
-                    };
+        while let Some((validator, result)) = fetches.next().await {
+            // Gracefully ignore an error fetching the checkpoint from a validator's
+            // checkpoint syncer, which can happen if the validator has not
+            // signed the checkpoint at `index`.
+            let signed_checkpoint = match result {
+                Ok(Ok(Some(signed_checkpoint))) => signed_checkpoint,
+                Ok(_) => {
                     debug!(
-                        validator = format!("{:#x}", validator),
-                        index = index,
-                        root = format!("{:#x}", root),
-                        signature_count = signature_count,
-                        "Found signed checkpoint"
+                        validator = format!("{validator:#x}"),
+                        index, "Unable to find signed checkpoint"
                     );
-                    // If we've hit a quorum, create a MultisigSignedCheckpoint
-                    if signature_count >= threshold {
-                        if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
-                            let checkpoint =
-                                MultisigSignedCheckpoint::<CheckpointWithMessageId>::try_from(
-                                    signed_checkpoints,
-                                )?;
-                            debug!(checkpoint=?checkpoint, "Fetched multisig checkpoint");
-                            return Ok(Some(checkpoint));
-                        }
-                    }
-                } else {
+                    continue;
+                }
+                Err(_) => {
                     debug!(
-                        validator = format!("{:#x}", validator),
-                        index = index,
-                        "Unable to find signed checkpoint"
+                        validator = format!("{validator:#x}"),
+                        index, "Timed out fetching signed checkpoint"
                     );
+                    continue;
                 }
-            } else {
-                debug!(%validator, "Unable to find checkpoint syncer");
+            };
+
+            // If the signed checkpoint is for a different index, ignore it
+            let checkpoint_index = signed_checkpoint.value.checkpoint().index;
+            if checkpoint_index != index {
+                debug!(
+                    validator = format!("{validator:#x}"),
+                    index, checkpoint_index, "Checkpoint index mismatch"
+                );
                 continue;
             }
+            // Ensure that the signature is actually by the validator
+            let signer = signed_checkpoint.recover()?;
+            if H256::from(signer) != *validator {
+                debug!(
+                    validator = format!("{validator:#x}"),
+                    index, "Checkpoint signature mismatch"
+                );
+                continue;
+            }
+
+            // Insert the SignedCheckpointWithSigner into signed_checkpoints_per_root
+            let root = signed_checkpoint.value.checkpoint().root;
+            let signed_checkpoint_with_signer = SignedCheckpointWithSigner {
+                signer,
+                signed_checkpoint,
+            };
+
+            let signature_count = match signed_checkpoints_per_root.entry(root) {
+                Entry::Occupied(mut entry) => {
+                    let vec = entry.get_mut();
+                    vec.push(signed_checkpoint_with_signer);
+                    vec.len()
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(vec![signed_checkpoint_with_signer]);
+                    1 // length of 1
+                }
+            };
+            debug!(
+                validator = format!("{validator:#x}"),
+                index,
+                root = format!("{root:#x}"),
+                signature_count,
+                "Found signed checkpoint"
+            );
+            // If we've hit a quorum, create a MultisigSignedCheckpoint. Any
+            // fetches still in flight are dropped.
+            if signature_count >= threshold {
+                if let Some(signed_checkpoints) = signed_checkpoints_per_root.get(&root) {
+                    let checkpoint = MultisigSignedCheckpoint::<T>::try_from(signed_checkpoints)?;
+                    debug!(index, "Fetched multisig checkpoint");
+                    return Ok(Some(checkpoint));
+                }
+            }
         }
         Ok(None)
     }
+
+    /// Fetches the latest index of each validator concurrently, reusing
+    /// recently fetched indices.
+    async fn latest_indices(&self, validators: &[H256]) -> Vec<u32> {
+        join_all(validators.iter().filter_map(|validator| {
+            let address = H160::from(*validator);
+            let checkpoint_syncer = self.checkpoint_syncers.get(&address)?;
+            Some(self.latest_index(address, checkpoint_syncer))
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    async fn latest_index(
+        &self,
+        address: H160,
+        checkpoint_syncer: &Arc<dyn CheckpointSyncer>,
+    ) -> Option<u32> {
+        let cached = self.latest_indices.lock().unwrap().get(&address).copied();
+        if let Some((fetched_at, index)) = cached {
+            if fetched_at.elapsed() < LATEST_INDEX_TTL {
+                return Some(index);
+            }
+        }
+
+        // Gracefully handle errors getting the latest_index
+        match timeout(FETCH_TIMEOUT, checkpoint_syncer.latest_index()).await {
+            Ok(Ok(Some(index))) => {
+                trace!(?address, ?index, "Validator returned latest index");
+                self.latest_indices
+                    .lock()
+                    .unwrap()
+                    .insert(address, (Instant::now(), index));
+                Some(index)
+            }
+            Ok(err) => {
+                debug!(?address, ?err, "Failed to get latest index from validator");
+                None
+            }
+            Err(_) => {
+                debug!(?address, "Timed out getting latest index from validator");
+                None
+            }
+        }
+    }
 }
@@ -36,6 +36,8 @@ pub enum MerkleTree {
     ///
     /// It represents a Merkle tree of 2^depth zero leaves.
     Zero(usize),
+    /// Full subtree of which only the hash is known, its leaves were pruned.
+    Finalized(H256),
 }
 
 /// A merkle proof object. The leaf, its path to the root, and its index in the
@@ -117,6 +119,7 @@ impl MerkleTree {
             MerkleTree::Leaf(h) => h,
             MerkleTree::Node(h, _, _) => h,
             MerkleTree::Zero(depth) => ZERO_HASHES[depth],
+            MerkleTree::Finalized(h) => h,
         }
     }
 
@@ -151,6 +154,39 @@ impl MerkleTree {
         }
     }
 
+    /// Create a Merkle tree of a fixed depth from the frontier of an
+    /// incremental Merkle tree with `count` leaves, see
+    /// [`IncrementalMerkle::branch`](crate::accumulator::incremental::IncrementalMerkle::branch).
+    ///
+    /// The full subtrees left of the next leaf are [`MerkleTree::Finalized`],
+    /// so only the leaves pushed afterwards can be proven.
+    pub fn from_frontier(branch: &[H256], count: usize, depth: usize) -> Self {
+        use MerkleTree::*;
+
+        if count == 0 {
+            return Zero(depth);
+        }
+        if count == 1 << depth {
+            return Finalized(branch[depth]);
+        }
+
+        let subtree_capacity = 1 << (depth - 1);
+        let (left_subtree, right_subtree) = if count <= subtree_capacity {
+            (
+                MerkleTree::from_frontier(branch, count, depth - 1),
+                Zero(depth - 1),
+            )
+        } else {
+            (
+                Finalized(branch[depth - 1]),
+                MerkleTree::from_frontier(branch, count - subtree_capacity, depth - 1),
+            )
+        };
+        let hash = hash_concat(left_subtree.hash(), right_subtree.hash());
+
+        Node(hash, Box::new(left_subtree), Box::new(right_subtree))
+    }
+
     /// Push an element in the MerkleTree.
     /// MerkleTree and depth must be correct, as the algorithm expects valid data.
     pub fn push_leaf(&mut self, elem: H256, depth: usize) -> Result<(), MerkleTreeError> {
@@ -162,6 +198,7 @@ impl MerkleTree {
 
         match self {
             Leaf(_) => return Err(MerkleTreeError::LeafReached),
+            Finalized(_) => return Err(MerkleTreeError::MerkleTreeFull),
             Zero(_) => {
                 *self = MerkleTree::create(&[elem], depth);
             }
@@ -170,15 +207,19 @@ impl MerkleTree {
                 let right: &mut MerkleTree = &mut *right;
                 match (&*left, &*right) {
                     // Tree is full
-                    (Leaf(_), Leaf(_)) => return Err(MerkleTreeError::MerkleTreeFull),
+                    (Leaf(_), Leaf(_)) | (Finalized(_), Leaf(_) | Finalized(_)) => {
+                        return Err(MerkleTreeError::MerkleTreeFull)
+                    }
                     // There is a right node so insert in right node
-                    (Node(_, _, _), Node(_, _, _)) => right.push_leaf(elem, depth - 1)?,
+                    (Node(_, _, _) | Finalized(_), Node(_, _, _)) => {
+                        right.push_leaf(elem, depth - 1)?
+                    }
                     // Both branches are zero, insert in left one
                     (Zero(_), Zero(_)) => {
                         *left = MerkleTree::create(&[elem], depth - 1);
                     }
-                    // Leaf on left branch and zero on right branch, insert on right side
-                    (Leaf(_), Zero(_)) => {
+                    // Full left branch and zero on right branch, insert on right side
+                    (Leaf(_) | Finalized(_), Zero(_)) => {
                         *right = MerkleTree::create(&[elem], depth - 1);
                     }
                     // Try inserting on the left node -> if it fails because it is full, insert in
@@ -205,7 +246,7 @@ impl MerkleTree {
     /// Get a reference to the left and right subtrees if they exist.
     pub fn left_and_right_branches(&self) -> Option<(&Self, &Self)> {
         match *self {
-            MerkleTree::Leaf(_) | MerkleTree::Zero(0) => None,
+            MerkleTree::Leaf(_) | MerkleTree::Finalized(_) | MerkleTree::Zero(0) => None,
             MerkleTree::Node(_, ref l, ref r) => Some((l, r)),
             MerkleTree::Zero(depth) => Some((&ZERO_NODES[depth - 1], &ZERO_NODES[depth - 1])),
         }
@@ -220,14 +261,19 @@ impl MerkleTree {
     ///
     /// The Merkle proof is in "bottom-up" order, starting with a leaf node
     /// and moving up the tree. Its length will be exactly equal to `depth`.
+    ///
+    /// Panics if the leaf is in a [`MerkleTree::Finalized`] subtree.
     pub fn generate_proof(&self, index: usize, depth: usize) -> (H256, Vec<H256>) {
         let mut proof = vec![];
         let mut current_node = self;
         let mut current_depth = depth;
         while current_depth > 0 {
             let ith_bit = (index >> (current_depth - 1)) & 0x01;
-            // Note: unwrap is safe because leaves are only ever constructed at depth == 0.
-            let (left, right) = current_node.left_and_right_branches().unwrap();
+            // Note: leaves are only ever constructed at depth == 0, so only finalized
+            // subtrees have no branches.
+            let (left, right) = current_node
+                .left_and_right_branches()
+                .expect("leaf is in a finalized subtree");
 
             // Go right, include the left branch in the proof.
             if ith_bit == 1 {
@@ -491,6 +537,32 @@ mod tests {
         assert_eq!(second.hash(), incr.root());
         assert_eq!(full.hash(), incr.root());
     }
+
+    #[test]
+    fn it_resumes_from_incremental_frontier() {
+        let leaves: Vec<H256> = (0..70u64).map(H256::from_low_u64_be).collect();
+
+        for snapshot_count in [0, 1, 2, 5, 32, 33, 64] {
+            let mut incr = incremental::IncrementalMerkle::default();
+            leaves[..snapshot_count]
+                .iter()
+                .for_each(|leaf| incr.ingest(*leaf));
+
+            let mut full = MerkleTree::create(&leaves[..snapshot_count], TREE_DEPTH);
+            let mut resumed = MerkleTree::from_frontier(incr.branch(), incr.count(), TREE_DEPTH);
+            assert_eq!(resumed.hash(), incr.root());
+
+            for (i, leaf) in leaves.iter().enumerate().skip(snapshot_count) {
+                full.push_leaf(*leaf, TREE_DEPTH).unwrap();
+                resumed.push_leaf(*leaf, TREE_DEPTH).unwrap();
+                assert_eq!(resumed.hash(), full.hash());
+                assert_eq!(
+                    resumed.generate_proof(i, TREE_DEPTH),
+                    full.generate_proof(i, TREE_DEPTH)
+                );
+            }
+        }
+    }
 }
 
 /*
@@ -44,6 +44,7 @@ impl SparseMerkleTree {
             SparseMerkleTree(MerkleTree::Leaf(h)) => h,
             SparseMerkleTree(MerkleTree::Node(h, _, _)) => h,
             SparseMerkleTree(MerkleTree::Zero(depth)) => ZERO_HASHES[depth],
+            SparseMerkleTree(MerkleTree::Finalized(h)) => h,
         }
     }
 
@@ -54,7 +55,7 @@ impl SparseMerkleTree {
     fn merge(self, b: SparseMerkleTree) -> SparseMerkleTree {
         match self {
             SparseMerkleTree(MerkleTree::Zero(_)) => self,
-            SparseMerkleTree(MerkleTree::Leaf(_)) => {
+            SparseMerkleTree(MerkleTree::Leaf(_) | MerkleTree::Finalized(_)) => {
                 if self.hash().eq(&b.hash()) {
                     b
                 } else {
@@ -62,7 +63,7 @@ impl SparseMerkleTree {
                 }
             }
             SparseMerkleTree(MerkleTree::Node(a_hash, ref a_left, ref a_right)) => match b {
-                SparseMerkleTree(MerkleTree::Leaf(_)) => self,
+                SparseMerkleTree(MerkleTree::Leaf(_) | MerkleTree::Finalized(_)) => self,
                 SparseMerkleTree(MerkleTree::Zero(_)) => self,
                 SparseMerkleTree(MerkleTree::Node(_, ref b_left, ref b_right)) => {
                     let aleft: SparseMerkleTree = a_left.into();
@@ -0,0 +1,407 @@
+//! Decoders for the bodies of messages sent by common Hyperlane applications,
+//! so they can be shown as readable fields instead of raw hex.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use ethers_core::abi::{self, ParamType, Token};
+use eyre::eyre;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::{utils::fmt_bytes, HyperlaneMessage, H256, U256};
+
+/// Decodes the body of messages sent to a recipient into named fields.
+pub trait MessageBodyDecoder: Debug + Send + Sync {
+    /// Name of the decoder, used to select it in configuration.
+    fn name(&self) -> &'static str;
+
+    /// Decode a message body. Returns None if the body is not in the format
+    /// this decoder understands.
+    fn decode(&self, body: &[u8]) -> Option<DecodedBody>;
+}
+
+/// The fields of a decoded message body, in the order they appear in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBody {
+    /// Name of the decoder which produced this
+    pub kind: &'static str,
+    /// Field names and their formatted values
+    pub fields: Vec<(String, String)>,
+}
+
+impl DecodedBody {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            fields: Vec::new(),
+        }
+    }
+
+    fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl Display for DecodedBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{ ", self.kind)?;
+        for (i, (name, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{name}: {value}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// Serialized as an object with a `kind` key followed by the fields.
+impl Serialize for DecodedBody {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len() + 1))?;
+        map.serialize_entry("kind", self.kind)?;
+        for (name, value) in &self.fields {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Body of a warp route token transfer: the 32 byte recipient followed by
+/// the 32 byte amount and optional metadata.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WarpRouteTransferDecoder;
+
+impl MessageBodyDecoder for WarpRouteTransferDecoder {
+    fn name(&self) -> &'static str {
+        "warpRouteTransfer"
+    }
+
+    fn decode(&self, body: &[u8]) -> Option<DecodedBody> {
+        if body.len() < 64 {
+            return None;
+        }
+        let decoded = DecodedBody::new(self.name())
+            .with("recipient", format!("{:?}", H256::from_slice(&body[..32])))
+            .with("amount", U256::from_big_endian(&body[32..64]).to_string());
+        Some(if body.len() > 64 {
+            decoded.with("metadata", fmt_bytes(&body[64..]))
+        } else {
+            decoded
+        })
+    }
+}
+
+/// Body of an interchain account call: the ABI encoded owner, ISM and list
+/// of calls to make from the account.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InterchainAccountCallDecoder;
+
+impl MessageBodyDecoder for InterchainAccountCallDecoder {
+    fn name(&self) -> &'static str {
+        "interchainAccountCall"
+    }
+
+    fn decode(&self, body: &[u8]) -> Option<DecodedBody> {
+        let call = ParamType::Tuple(vec![
+            ParamType::FixedBytes(32),
+            ParamType::Uint(256),
+            ParamType::Bytes,
+        ]);
+        let tokens = abi::decode(
+            &[
+                ParamType::FixedBytes(32),
+                ParamType::FixedBytes(32),
+                ParamType::Array(Box::new(call)),
+            ],
+            body,
+        )
+        .ok()?;
+        let (owner, ism, calls) = match &tokens[..] {
+            [Token::FixedBytes(owner), Token::FixedBytes(ism), Token::Array(calls)] => {
+                (owner, ism, calls)
+            }
+            _ => return None,
+        };
+
+        let mut decoded = DecodedBody::new(self.name())
+            .with("owner", format_bytes32(owner))
+            .with("ism", format_bytes32(ism));
+        for (i, call) in calls.iter().enumerate() {
+            let Token::Tuple(call) = call else { return None };
+            let [Token::FixedBytes(to), Token::Uint(value), Token::Bytes(data)] = &call[..] else {
+                return None;
+            };
+            decoded = decoded
+                .with(format!("calls[{i}].to"), format_bytes32(to))
+                .with(format!("calls[{i}].value"), value.to_string())
+                .with(format!("calls[{i}].data"), fmt_bytes(data));
+        }
+        Some(decoded)
+    }
+}
+
+/// Body of an interchain query or of the response to one: the ABI encoded
+/// sender and message type, followed by the calls to make with their
+/// callbacks for a query or the raw results for a response.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InterchainQueryDecoder;
+
+impl InterchainQueryDecoder {
+    const QUERY: u8 = 0;
+    const RESPONSE: u8 = 1;
+}
+
+impl MessageBodyDecoder for InterchainQueryDecoder {
+    fn name(&self) -> &'static str {
+        "interchainQuery"
+    }
+
+    fn decode(&self, body: &[u8]) -> Option<DecodedBody> {
+        // the message type is a left padded uint8 in the second word
+        if body.len() < 64 || body[32..63].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let payload = match body[63] {
+            Self::QUERY => ParamType::Tuple(vec![
+                ParamType::Tuple(vec![ParamType::FixedBytes(32), ParamType::Bytes]),
+                ParamType::Bytes,
+            ]),
+            Self::RESPONSE => ParamType::Bytes,
+            _ => return None,
+        };
+        let tokens = abi::decode(
+            &[
+                ParamType::FixedBytes(32),
+                ParamType::Uint(8),
+                ParamType::Array(Box::new(payload)),
+            ],
+            body,
+        )
+        .ok()?;
+        let [Token::FixedBytes(sender), _, Token::Array(items)] = &tokens[..] else {
+            return None;
+        };
+
+        let mut decoded = DecodedBody::new(self.name()).with("sender", format_bytes32(sender));
+        if body[63] == Self::QUERY {
+            decoded = decoded.with("type", "query");
+            for (i, item) in items.iter().enumerate() {
+                let Token::Tuple(item) = item else { return None };
+                let [Token::Tuple(call), Token::Bytes(callback)] = &item[..] else {
+                    return None;
+                };
+                let [Token::FixedBytes(to), Token::Bytes(data)] = &call[..] else {
+                    return None;
+                };
+                decoded = decoded
+                    .with(format!("calls[{i}].to"), format_bytes32(to))
+                    .with(format!("calls[{i}].data"), fmt_bytes(data))
+                    .with(format!("calls[{i}].callback"), fmt_bytes(callback));
+            }
+        } else {
+            decoded = decoded.with("type", "response");
+            for (i, item) in items.iter().enumerate() {
+                let Token::Bytes(result) = item else { return None };
+                decoded = decoded.with(format!("results[{i}]"), fmt_bytes(result));
+            }
+        }
+        Some(decoded)
+    }
+}
+
+fn format_bytes32(bytes: &[u8]) -> String {
+    format!("{:?}", H256::from_slice(bytes))
+}
+
+/// The body decoders to use for messages sent to each recipient.
+#[derive(Debug, Clone, Default)]
+pub struct MessageBodyDecoders {
+    by_recipient: HashMap<H256, Arc<dyn MessageBodyDecoder>>,
+}
+
+impl PartialEq for MessageBodyDecoders {
+    fn eq(&self, other: &Self) -> bool {
+        self.by_recipient.len() == other.by_recipient.len()
+            && self.by_recipient.iter().all(|(recipient, decoder)| {
+                other
+                    .by_recipient
+                    .get(recipient)
+                    .map_or(false, |other| other.name() == decoder.name())
+            })
+    }
+}
+
+impl MessageBodyDecoders {
+    /// The decoders which come with the agents.
+    pub fn builtin() -> Vec<Arc<dyn MessageBodyDecoder>> {
+        vec![
+            Arc::new(WarpRouteTransferDecoder),
+            Arc::new(InterchainAccountCallDecoder),
+            Arc::new(InterchainQueryDecoder),
+        ]
+    }
+
+    /// Decode the bodies of messages sent to `recipient` with `decoder`.
+    pub fn register(&mut self, recipient: H256, decoder: Arc<dyn MessageBodyDecoder>) {
+        self.by_recipient.insert(recipient, decoder);
+    }
+
+    /// Decode the bodies of messages sent to `recipient` with the builtin
+    /// decoder called `name`.
+    pub fn register_builtin(&mut self, recipient: H256, name: &str) -> eyre::Result<()> {
+        let builtin = Self::builtin();
+        let decoder = builtin
+            .iter()
+            .find(|d| d.name() == name)
+            .ok_or_else(|| {
+                eyre!(
+                    "Unknown message body decoder `{name}`, expected one of: {}",
+                    builtin
+                        .iter()
+                        .map(|d| d.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?
+            .clone();
+        self.register(recipient, decoder);
+        Ok(())
+    }
+
+    /// Whether no decoders are registered.
+    pub fn is_empty(&self) -> bool {
+        self.by_recipient.is_empty()
+    }
+
+    /// Decode the body of a message with the decoder registered for its
+    /// recipient. Returns None if there is no such decoder or it could not
+    /// decode the body.
+    pub fn decode(&self, message: &HyperlaneMessage) -> Option<DecodedBody> {
+        self.by_recipient
+            .get(&message.recipient)?
+            .decode(&message.body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn word(value: u64) -> Token {
+        Token::Uint(value.into())
+    }
+
+    fn bytes32(byte: u8) -> Token {
+        Token::FixedBytes(vec![byte; 32])
+    }
+
+    #[test]
+    fn test_warp_route_transfer() {
+        let mut body = vec![0x11; 32];
+        body.extend_from_slice(&abi::encode(&[word(1000)]));
+        let decoded = WarpRouteTransferDecoder.decode(&body).unwrap();
+        assert_eq!(
+            decoded.to_string(),
+            format!(
+                "warpRouteTransfer {{ recipient: {:?}, amount: 1000 }}",
+                H256::repeat_byte(0x11)
+            )
+        );
+
+        body.extend_from_slice(&[0xab, 0xcd]);
+        let decoded = WarpRouteTransferDecoder.decode(&body).unwrap();
+        assert_eq!(
+            decoded.fields.last().unwrap(),
+            &("metadata".to_owned(), "0xabcd".to_owned())
+        );
+
+        assert!(WarpRouteTransferDecoder.decode(&body[..63]).is_none());
+    }
+
+    #[test]
+    fn test_interchain_account_call() {
+        let call = Token::Tuple(vec![bytes32(3), word(5), Token::Bytes(vec![0xde, 0xad])]);
+        let body = abi::encode(&[bytes32(1), bytes32(2), Token::Array(vec![call])]);
+        let decoded = InterchainAccountCallDecoder.decode(&body).unwrap();
+        let fields: Vec<_> = decoded.fields.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "owner",
+                "ism",
+                "calls[0].to",
+                "calls[0].value",
+                "calls[0].data"
+            ]
+        );
+        assert_eq!(decoded.fields[3].1, "5");
+        assert_eq!(decoded.fields[4].1, "0xdead");
+
+        assert!(InterchainAccountCallDecoder.decode(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn test_interchain_query() {
+        let call = Token::Tuple(vec![
+            Token::Tuple(vec![bytes32(3), Token::Bytes(vec![0x01])]),
+            Token::Bytes(vec![0x02]),
+        ]);
+        let query = abi::encode(&[bytes32(1), word(0), Token::Array(vec![call])]);
+        let decoded = InterchainQueryDecoder.decode(&query).unwrap();
+        assert_eq!(decoded.fields[1], ("type".to_owned(), "query".to_owned()));
+        assert_eq!(decoded.fields[4].1, "0x02");
+
+        let response = abi::encode(&[
+            bytes32(1),
+            word(1),
+            Token::Array(vec![Token::Bytes(vec![0x03])]),
+        ]);
+        let decoded = InterchainQueryDecoder.decode(&response).unwrap();
+        assert_eq!(
+            decoded.fields[1..],
+            [
+                ("type".to_owned(), "response".to_owned()),
+                ("results[0]".to_owned(), "0x03".to_owned())
+            ]
+        );
+
+        let unknown_type = abi::encode(&[bytes32(1), word(2), Token::Array(vec![])]);
+        assert!(InterchainQueryDecoder.decode(&unknown_type).is_none());
+    }
+
+    #[test]
+    fn test_decode_by_recipient() {
+        let mut decoders = MessageBodyDecoders::default();
+        decoders
+            .register_builtin(H256::repeat_byte(1), "warpRouteTransfer")
+            .unwrap();
+        assert!(decoders
+            .register_builtin(H256::repeat_byte(1), "unknown")
+            .is_err());
+
+        let message = HyperlaneMessage {
+            recipient: H256::repeat_byte(1),
+            body: vec![0; 64],
+            ..Default::default()
+        };
+        let decoded = decoders.decode(&message).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::json!({
+                "kind": "warpRouteTransfer",
+                "recipient": format!("{:?}", H256::zero()),
+                "amount": "0",
+            })
+        );
+
+        let other_recipient = HyperlaneMessage {
+            recipient: H256::repeat_byte(2),
+            ..message
+        };
+        assert!(decoders.decode(&other_recipient).is_none());
+    }
+}
@@ -2,13 +2,13 @@
 
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
-use strum::{EnumIter, EnumString, IntoStaticStr};
+use serde::Deserialize;
+use strum::{EnumString, IntoStaticStr};
 
-use crate::utils::many_to_one;
-use crate::{ChainResult, HyperlaneProtocolError, H160, H256};
+use crate::{ChainResult, ChainTraits, DomainInfo, DomainRegistry, H160, H256};
 
 #[derive(Debug, Clone)]
 pub struct Address(pub bytes::Bytes);
@@ -57,70 +57,10 @@ impl From<&'_ Address> for H160 {
     }
 }
 
-/// All domains supported by Hyperlane.
-#[derive(
-    FromPrimitive,
-    EnumString,
-    IntoStaticStr,
-    strum::Display,
-    EnumIter,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Debug,
-    Clone,
-    Copy,
-    Hash,
-)]
-#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
-pub enum KnownHyperlaneDomain {
-    Ethereum = 1,
-    Goerli = 5,
-    Sepolia = 11155111,
-
-    Polygon = 137,
-    Mumbai = 80001,
-
-    Avalanche = 43114,
-    Fuji = 43113,
-
-    Arbitrum = 42161,
-    ArbitrumGoerli = 421613,
-
-    Optimism = 10,
-    OptimismGoerli = 420,
-
-    #[strum(serialize = "bsc")]
-    BinanceSmartChain = 56,
-    #[strum(serialize = "bsctestnet")]
-    BinanceSmartChainTestnet = 97,
-
-    Celo = 42220,
-    Alfajores = 44787,
-
-    Moonbeam = 1284,
-    MoonbaseAlpha = 1287,
-
-    Gnosis = 100,
-
-    Zksync2Testnet = 280,
-
-    // -- Local test chains --
-    /// Test1 local chain
-    Test1 = 13371,
-    /// Test2 local chain
-    Test2 = 13372,
-    /// Test3 local chain
-    Test3 = 13373,
-
-    /// Fuel1 local chain
-    FuelTest1 = 13374,
-}
-
 #[derive(Clone)]
 pub enum HyperlaneDomain {
-    Known(KnownHyperlaneDomain),
+    /// A domain from the [`DomainRegistry`]
+    Known(Arc<DomainInfo>),
     Unknown {
         domain_id: u32,
         domain_name: String,
@@ -129,17 +69,6 @@ pub enum HyperlaneDomain {
     },
 }
 
-impl HyperlaneDomain {
-    pub fn is_arbitrum_nitro(&self) -> bool {
-        matches!(
-            self,
-            HyperlaneDomain::Known(
-                KnownHyperlaneDomain::Arbitrum | KnownHyperlaneDomain::ArbitrumGoerli,
-            )
-        )
-    }
-}
-
 #[cfg(any(test, feature = "test-utils"))]
 impl HyperlaneDomain {
     pub fn new_test_domain(name: &str) -> Self {
@@ -154,9 +83,19 @@ impl HyperlaneDomain {
 
 /// Types of Hyperlane domains.
 #[derive(
-    FromPrimitive, EnumString, IntoStaticStr, strum::Display, Copy, Clone, Eq, PartialEq, Debug,
+    FromPrimitive,
+    EnumString,
+    IntoStaticStr,
+    strum::Display,
+    Deserialize,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
 )]
 #[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
 pub enum HyperlaneDomainType {
     /// A mainnet.
     Mainnet,
@@ -170,9 +109,19 @@ pub enum HyperlaneDomainType {
 
 /// A selector for which base library should handle this domain.
 #[derive(
-    FromPrimitive, EnumString, IntoStaticStr, strum::Display, Copy, Clone, Eq, PartialEq, Debug,
+    FromPrimitive,
+    EnumString,
+    IntoStaticStr,
+    strum::Display,
+    Deserialize,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
 )]
 #[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
 pub enum HyperlaneDomainProtocol {
     /// An EVM-based chain type which uses hyperlane-ethereum.
     Ethereum,
@@ -190,42 +139,6 @@ impl HyperlaneDomainProtocol {
     }
 }
 
-impl KnownHyperlaneDomain {
-    pub fn as_str(self) -> &'static str {
-        self.into()
-    }
-
-    pub const fn domain_type(self) -> HyperlaneDomainType {
-        use self::{HyperlaneDomainType::*, KnownHyperlaneDomain::*};
-
-        many_to_one!(match self {
-            Mainnet: [
-                Ethereum, Avalanche, Arbitrum, Polygon, Optimism, BinanceSmartChain, Celo,
-                Moonbeam,
-                Gnosis
-            ],
-            Testnet: [
-                Goerli, Mumbai, Fuji, ArbitrumGoerli, OptimismGoerli, BinanceSmartChainTestnet,
-                Alfajores, MoonbaseAlpha, Zksync2Testnet, Sepolia
-            ],
-            LocalTestChain: [Test1, Test2, Test3, FuelTest1],
-        })
-    }
-
-    pub const fn domain_protocol(self) -> HyperlaneDomainProtocol {
-        use KnownHyperlaneDomain::*;
-
-        many_to_one!(match self {
-            HyperlaneDomainProtocol::Ethereum: [
-                Ethereum, Goerli, Sepolia, Polygon, Mumbai, Avalanche, Fuji, Arbitrum, ArbitrumGoerli,
-                Optimism, OptimismGoerli, BinanceSmartChain, BinanceSmartChainTestnet, Celo, Gnosis,
-                Alfajores, Moonbeam, MoonbaseAlpha, Zksync2Testnet, Test1, Test2, Test3
-            ],
-            HyperlaneDomainProtocol::Fuel: [FuelTest1],
-        })
-    }
-}
-
 impl PartialEq<Self> for HyperlaneDomain {
     fn eq(&self, other: &Self) -> bool {
         self.id() == other.id()
@@ -252,14 +165,6 @@ impl From<&HyperlaneDomain> for u32 {
     }
 }
 
-impl TryFrom<u32> for KnownHyperlaneDomain {
-    type Error = HyperlaneProtocolError;
-
-    fn try_from(domain_id: u32) -> Result<Self, Self::Error> {
-        FromPrimitive::from_u32(domain_id).ok_or(HyperlaneProtocolError::UnknownDomainId(domain_id))
-    }
-}
-
 impl From<&HyperlaneDomain> for HyperlaneDomainType {
     fn from(d: &HyperlaneDomain) -> Self {
         d.domain_type()
@@ -299,13 +204,14 @@ impl HyperlaneDomain {
         protocol: HyperlaneDomainProtocol,
     ) -> Result<Self, HyperlaneDomainConfigError> {
         let name = name.to_ascii_lowercase();
-        if let Ok(domain) = KnownHyperlaneDomain::try_from(domain_id) {
-            if name == domain.as_str() {
+        let registry = DomainRegistry::global();
+        if let Some(domain) = registry.by_id(domain_id) {
+            if name == domain.name {
                 Ok(HyperlaneDomain::Known(domain))
             } else {
                 Err(HyperlaneDomainConfigError::UnknownDomainName(name))
             }
-        } else if name.as_str().parse::<KnownHyperlaneDomain>().is_ok() {
+        } else if registry.by_name(&name).is_some() {
             Err(HyperlaneDomainConfigError::DomainNameMismatch(
                 name, domain_id,
             ))
@@ -320,10 +226,18 @@ impl HyperlaneDomain {
         }
     }
 
+    /// The domain with the given id from the global [`DomainRegistry`], if
+    /// it is in there.
+    pub fn from_registry(domain_id: u32) -> Option<Self> {
+        DomainRegistry::global()
+            .by_id(domain_id)
+            .map(HyperlaneDomain::Known)
+    }
+
     /// The chain name
     pub fn name(&self) -> &str {
         match self {
-            HyperlaneDomain::Known(domain) => domain.as_str(),
+            HyperlaneDomain::Known(domain) => domain.name.as_str(),
             HyperlaneDomain::Unknown {
                 domain_name: chain_name,
                 ..
@@ -332,86 +246,76 @@ impl HyperlaneDomain {
     }
 
     /// The domain id
-    pub const fn id(&self) -> u32 {
+    pub fn id(&self) -> u32 {
         match self {
-            HyperlaneDomain::Known(domain) => *domain as u32,
+            HyperlaneDomain::Known(domain) => domain.id,
             HyperlaneDomain::Unknown { domain_id, .. } => *domain_id,
         }
     }
 
     /// Type of domain this is
-    pub const fn domain_type(&self) -> HyperlaneDomainType {
+    pub fn domain_type(&self) -> HyperlaneDomainType {
         match self {
-            HyperlaneDomain::Known(domain) => domain.domain_type(),
+            HyperlaneDomain::Known(domain) => domain.domain_type,
             HyperlaneDomain::Unknown { domain_type, .. } => *domain_type,
         }
     }
 
     /// Backend implementation for this domain
-    pub const fn domain_protocol(&self) -> HyperlaneDomainProtocol {
+    pub fn domain_protocol(&self) -> HyperlaneDomainProtocol {
         match self {
-            HyperlaneDomain::Known(domain) => domain.domain_protocol(),
+            HyperlaneDomain::Known(domain) => domain.protocol,
             HyperlaneDomain::Unknown {
                 domain_protocol, ..
             } => *domain_protocol,
         }
     }
+
+    /// Chain specific behaviour of this domain, the defaults if it is not in
+    /// the registry
+    pub fn chain_traits(&self) -> ChainTraits {
+        match self {
+            HyperlaneDomain::Known(domain) => domain.traits.clone(),
+            HyperlaneDomain::Unknown { .. } => ChainTraits::default(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::KnownHyperlaneDomain;
-    use std::str::FromStr;
+    use crate::{HyperlaneDomain, HyperlaneDomainConfigError, HyperlaneDomainProtocol, L2FeeModel};
 
     #[test]
-    fn domain_strings() {
-        assert_eq!(
-            KnownHyperlaneDomain::from_str("ethereum").unwrap(),
-            KnownHyperlaneDomain::Ethereum,
-        );
-        assert_eq!(
-            KnownHyperlaneDomain::Ethereum.to_string(),
-            "ethereum".to_string(),
-        );
+    fn test_domain_from_config() {
+        let ethereum =
+            HyperlaneDomain::from_config(1, "EthEreum", HyperlaneDomainProtocol::Ethereum).unwrap();
+        assert!(matches!(ethereum, HyperlaneDomain::Known(_)));
+        assert_eq!(ethereum.name(), "ethereum");
+        assert_eq!(ethereum.id(), 1);
+
+        assert!(matches!(
+            HyperlaneDomain::from_config(1, "goerli", HyperlaneDomainProtocol::Ethereum),
+            Err(HyperlaneDomainConfigError::UnknownDomainName(_))
+        ));
+        assert!(matches!(
+            HyperlaneDomain::from_config(0xf00, "bsc", HyperlaneDomainProtocol::Ethereum),
+            Err(HyperlaneDomainConfigError::DomainNameMismatch(_, 0xf00))
+        ));
+
+        let unknown =
+            HyperlaneDomain::from_config(0xf00, "foo", HyperlaneDomainProtocol::Fuel).unwrap();
+        assert!(matches!(unknown, HyperlaneDomain::Unknown { .. }));
+        assert_eq!(unknown.domain_protocol(), HyperlaneDomainProtocol::Fuel);
     }
 
     #[test]
-    fn domain_ids() {
-        assert_eq!(
-            KnownHyperlaneDomain::try_from(1).unwrap(),
-            KnownHyperlaneDomain::Ethereum,
-        );
-
-        assert_eq!(KnownHyperlaneDomain::Ethereum as u32, 1);
-    }
-
-    #[test]
-    fn test_name_from_domain_id() {
-        assert_eq!(
-            KnownHyperlaneDomain::try_from(1).unwrap().to_string(),
-            "ethereum"
-        );
-        assert_eq!(
-            KnownHyperlaneDomain::try_from(1).unwrap().as_str(),
-            "ethereum"
-        );
-        assert!(KnownHyperlaneDomain::try_from(0xf00u32).is_err());
-    }
-
-    #[test]
-    fn test_domain_id_from_name() {
-        assert_eq!(
-            "ethereum".parse::<KnownHyperlaneDomain>().map(|v| v as u32),
-            Ok(1)
-        );
-        assert_eq!(
-            "EthEreum".parse::<KnownHyperlaneDomain>().map(|v| v as u32),
-            Ok(1)
-        );
+    fn test_domain_from_registry() {
+        let arbitrum = HyperlaneDomain::from_registry(42161).unwrap();
+        assert_eq!(arbitrum.name(), "arbitrum");
         assert_eq!(
-            "Bsc".parse::<KnownHyperlaneDomain>().map(|v| v as u32),
-            Ok(56)
+            arbitrum.chain_traits().l2_fee_model,
+            L2FeeModel::ArbitrumNitro
         );
-        assert!("foo".parse::<KnownHyperlaneDomain>().is_err());
+        assert!(HyperlaneDomain::from_registry(0xf00).is_none());
     }
 }
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::{HyperlaneDomainProtocol, HyperlaneDomainType, U256};
+
+/// The domains bundled with the agents, extended or overridden at runtime
+/// with [`DomainRegistry::extend_global`].
+const BUNDLED_DOMAINS: &str = include_str!("domains.json");
+
+lazy_static! {
+    static ref GLOBAL_REGISTRY: RwLock<Arc<DomainRegistry>> =
+        RwLock::new(Arc::new(DomainRegistry::bundled()));
+}
+
+/// How a rollup charges for the L1 part of its transaction costs, which
+/// changes how their gas is estimated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum L2FeeModel {
+    /// Gas estimates only cover execution on the chain itself.
+    #[default]
+    None,
+    /// Gas estimates include the L1 calldata costs, which can be isolated
+    /// through the Arbitrum Nitro NodeInterface.
+    ArbitrumNitro,
+}
+
+/// Chain specific behaviour of a domain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainTraits {
+    /// How the L1 part of transaction costs is charged
+    #[serde(default, alias = "l2feemodel")]
+    pub l2_fee_model: L2FeeModel,
+    /// Whether transactions may be priced with EIP-1559 fees. Legacy pricing
+    /// is still used if the fees can't be estimated.
+    #[serde(default = "default_eip1559")]
+    pub eip1559: bool,
+    /// Lower bound for the max priority fee of EIP-1559 transactions, in the
+    /// smallest unit of the native token
+    #[serde(default, alias = "minpriorityfee")]
+    pub min_priority_fee: Option<u64>,
+}
+
+fn default_eip1559() -> bool {
+    true
+}
+
+impl Default for ChainTraits {
+    fn default() -> Self {
+        Self {
+            l2_fee_model: L2FeeModel::None,
+            eip1559: default_eip1559(),
+            min_priority_fee: None,
+        }
+    }
+}
+
+impl ChainTraits {
+    /// The minimum max priority fee of EIP-1559 transactions.
+    pub fn min_priority_fee(&self) -> Option<U256> {
+        self.min_priority_fee.map(U256::from)
+    }
+}
+
+/// A domain known to the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DomainInfo {
+    /// The domain id
+    pub id: u32,
+    /// The chain name, always lowercase
+    pub name: String,
+    /// Type of domain this is
+    #[serde(rename = "type")]
+    pub domain_type: HyperlaneDomainType,
+    /// Backend implementation for this domain
+    pub protocol: HyperlaneDomainProtocol,
+    /// Chain specific behaviour
+    #[serde(default)]
+    pub traits: ChainTraits,
+}
+
+/// The format of domain registry files.
+#[derive(Debug, Deserialize)]
+pub struct RawDomainRegistry {
+    /// The domains in the registry
+    pub domains: Vec<DomainInfo>,
+}
+
+/// Errors when building a domain registry.
+#[derive(thiserror::Error, Debug)]
+pub enum DomainRegistryError {
+    /// Two different domains have the same name.
+    #[error("Domains {0} and {1} are both named `{2}`")]
+    DuplicateName(u32, u32, String),
+    /// A registry file could not be parsed.
+    #[error("Invalid domain registry: {0}")]
+    Invalid(#[from] serde_json::Error),
+}
+
+/// The domains agents know the name, type, protocol and chain traits of,
+/// by id.
+#[derive(Debug, Clone, Default)]
+pub struct DomainRegistry {
+    by_id: HashMap<u32, Arc<DomainInfo>>,
+}
+
+impl DomainRegistry {
+    /// The registry of domains bundled with the agents.
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_DOMAINS).expect("bundled domain registry is invalid")
+    }
+
+    /// Parse a registry from JSON.
+    pub fn from_json(json: &str) -> Result<Self, DomainRegistryError> {
+        let raw: RawDomainRegistry = serde_json::from_str(json)?;
+        let mut registry = Self::default();
+        registry.extend(raw.domains)?;
+        Ok(registry)
+    }
+
+    /// Add domains to the registry, replacing those with the same id.
+    pub fn extend(
+        &mut self,
+        domains: impl IntoIterator<Item = DomainInfo>,
+    ) -> Result<(), DomainRegistryError> {
+        for mut domain in domains {
+            domain.name = domain.name.to_ascii_lowercase();
+            if let Some(existing) = self.by_name(&domain.name).filter(|d| d.id != domain.id) {
+                return Err(DomainRegistryError::DuplicateName(
+                    existing.id,
+                    domain.id,
+                    domain.name,
+                ));
+            }
+            self.by_id.insert(domain.id, Arc::new(domain));
+        }
+        Ok(())
+    }
+
+    /// Look up a domain by id.
+    pub fn by_id(&self, id: u32) -> Option<Arc<DomainInfo>> {
+        self.by_id.get(&id).cloned()
+    }
+
+    /// Look up a domain by name, ignoring case.
+    pub fn by_name(&self, name: &str) -> Option<Arc<DomainInfo>> {
+        self.by_id
+            .values()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// All domains in the registry, sorted by id.
+    pub fn domains(&self) -> Vec<Arc<DomainInfo>> {
+        let mut domains: Vec<_> = self.by_id.values().cloned().collect();
+        domains.sort_by_key(|d| d.id);
+        domains
+    }
+
+    /// The registry used by the agents.
+    pub fn global() -> Arc<DomainRegistry> {
+        GLOBAL_REGISTRY
+            .read()
+            .expect("domain registry lock poisoned")
+            .clone()
+    }
+
+    /// Add domains to the global registry, replacing those with the same id.
+    /// Domains which were already resolved keep the info they were created
+    /// with, so this should be done before any configuration is parsed.
+    pub fn extend_global(
+        domains: impl IntoIterator<Item = DomainInfo>,
+    ) -> Result<(), DomainRegistryError> {
+        let mut global = GLOBAL_REGISTRY
+            .write()
+            .expect("domain registry lock poisoned");
+        let mut registry = DomainRegistry::clone(&global);
+        registry.extend(domains)?;
+        *global = Arc::new(registry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bundled_domains() {
+        let registry = DomainRegistry::bundled();
+
+        let ethereum = registry.by_id(1).unwrap();
+        assert_eq!(ethereum.name, "ethereum");
+        assert_eq!(ethereum.domain_type, HyperlaneDomainType::Mainnet);
+        assert_eq!(ethereum.protocol, HyperlaneDomainProtocol::Ethereum);
+        assert_eq!(ethereum.traits, ChainTraits::default());
+
+        assert_eq!(registry.by_name("Bsc").unwrap().id, 56);
+        assert_eq!(
+            registry
+                .by_name("arbitrumgoerli")
+                .unwrap()
+                .traits
+                .l2_fee_model,
+            L2FeeModel::ArbitrumNitro
+        );
+        assert_eq!(
+            registry
+                .by_name("polygon")
+                .unwrap()
+                .traits
+                .min_priority_fee(),
+            Some(U256::from(30_000_000_000u64))
+        );
+        assert_eq!(
+            registry.by_name("fueltest1").unwrap().protocol,
+            HyperlaneDomainProtocol::Fuel
+        );
+        assert!(registry.by_id(0xf00).is_none());
+        assert!(registry.by_name("foo").is_none());
+    }
+
+    #[test]
+    fn test_extend_registry() {
+        let mut registry = DomainRegistry::from_json(
+            r#"{
+                "domains": [
+                    { "id": 1, "name": "ethereum", "type": "mainnet", "protocol": "ethereum" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let domain = |id: u32, name: &str| DomainInfo {
+            id,
+            name: name.to_owned(),
+            domain_type: HyperlaneDomainType::Testnet,
+            protocol: HyperlaneDomainProtocol::Ethereum,
+            traits: ChainTraits {
+                eip1559: false,
+                ..Default::default()
+            },
+        };
+
+        registry
+            .extend([domain(1, "Ethereum"), domain(2, "newchain")])
+            .unwrap();
+        assert_eq!(
+            registry.by_id(1).unwrap().domain_type,
+            HyperlaneDomainType::Testnet
+        );
+        assert!(!registry.by_name("newchain").unwrap().traits.eip1559);
+        assert!(matches!(
+            registry.extend([domain(3, "newchain")]),
+            Err(DomainRegistryError::DuplicateName(2, 3, _))
+        ));
+        assert_eq!(
+            registry.domains().iter().map(|d| d.id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+}
@@ -122,4 +122,19 @@ pub enum HyperlaneProtocolError {
     /// Expected a gas limit and none was provided
     #[error("A gas limit was expected for `process` contract call")]
     ProcessGasLimitRequired,
+    /// Tried to process a batch of messages on a mailbox without batching
+    /// support
+    #[error("Batch processing is not supported by this mailbox")]
+    BatchingNotSupported,
+    /// A raw message was too short to contain a message header
+    #[error("Message of {len} bytes is shorter than the {expected} byte header")]
+    MessageTooShort {
+        /// Length of the raw message
+        len: usize,
+        /// Minimum length of a message
+        expected: usize,
+    },
+    /// A message has a version the agents do not know how to handle
+    #[error("Unsupported message version {0}")]
+    UnsupportedMessageVersion(u8),
 }
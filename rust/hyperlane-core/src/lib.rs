@@ -8,6 +8,7 @@
 extern crate core;
 
 pub use chain::*;
+pub use domain_registry::*;
 pub use error::{ChainCommunicationError, ChainResult, HyperlaneProtocolError};
 pub use identifiers::HyperlaneIdentifier;
 pub use traits::*;
@@ -16,6 +17,8 @@ pub use types::*;
 /// Accumulator management
 pub mod accumulator;
 
+pub mod body;
+
 /// Async Traits for contract instances for use in applications
 mod traits;
 /// Utilities to match contract values
@@ -31,6 +34,7 @@ pub mod config;
 mod types;
 
 mod chain;
+mod domain_registry;
 mod error;
 
 /// Enum for validity of a list of messages
@@ -2,9 +2,9 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use eyre::Result;
+use eyre::{eyre, Result};
 
-use crate::{HyperlaneMessage, LogMeta};
+use crate::{HyperlaneMessage, LogMeta, H256};
 
 /// Interface for a HyperlaneLogStore that ingests logs.
 #[async_trait]
@@ -23,6 +23,19 @@ pub trait HyperlaneMessageStore: HyperlaneLogStore<HyperlaneMessage> {
     async fn retrieve_message_by_nonce(&self, nonce: u32) -> Result<Option<HyperlaneMessage>>;
     /// Gets the block number at which a message was dispatched.
     async fn retrieve_dispatched_block_number(&self, nonce: u32) -> Result<Option<u64>>;
+    /// Gets the hash of the block in which a message was dispatched. Reorgs
+    /// are only detected for stores which return it.
+    async fn retrieve_dispatched_block_hash(&self, _nonce: u32) -> Result<Option<H256>> {
+        Ok(None)
+    }
+    /// Removes the message with the given nonce and the messages stored
+    /// contiguously after it, e.g. because they were reorged out. Returns the
+    /// number of messages removed.
+    async fn rollback_messages(&self, _from_nonce: u32) -> Result<u32> {
+        Err(eyre!(
+            "Rolling back messages is not supported by this store"
+        ))
+    }
 }
 
 /// Extension of HyperlaneLogStore trait that supports a high watermark for the highest indexed block number.
@@ -5,7 +5,7 @@ use auto_impl::auto_impl;
 use num_derive::FromPrimitive;
 use strum::Display;
 
-use crate::{ChainResult, HyperlaneContract};
+use crate::{ChainResult, HyperlaneContract, HyperlaneMessage};
 
 /// Enumeration of all known module types
 #[derive(FromPrimitive, Clone, Debug, Default, Display, Copy, PartialEq, Eq)]
@@ -25,6 +25,16 @@ pub enum ModuleType {
     MessageIdMultisig,
 }
 
+/// Outcome of simulating an ISM verifying a message with some metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IsmVerification {
+    /// The ISM accepted the metadata
+    Verified,
+    /// The ISM rejected the metadata, with the decoded revert reason where
+    /// there was one
+    Rejected(String),
+}
+
 /// Interface for the InterchainSecurityModule chain contract. Allows abstraction over
 /// different chains
 #[async_trait]
@@ -33,4 +43,12 @@ pub trait InterchainSecurityModule: HyperlaneContract + Send + Sync + Debug {
     /// Returns the module type of the ISM compliant with the corresponding
     /// metadata offchain fetching and onchain formatting standard.
     async fn module_type(&self) -> ChainResult<ModuleType>;
+
+    /// Simulates the ISM verifying the message with the given metadata,
+    /// without submitting a transaction.
+    async fn dry_run_verify(
+        &self,
+        message: &HyperlaneMessage,
+        metadata: &[u8],
+    ) -> ChainResult<IsmVerification>;
 }
@@ -64,4 +64,30 @@ pub trait Mailbox: HyperlaneContract + Send + Sync + Debug {
     /// Get the calldata for a transaction to process a message with a proof
     /// against the provided signed checkpoint
     fn process_calldata(&self, message: &HyperlaneMessage, metadata: &[u8]) -> Vec<u8>;
+
+    /// Whether this mailbox is able to process several messages in a single
+    /// transaction.
+    fn supports_batching(&self) -> bool;
+
+    /// Simulate processing a batch of messages in a single transaction and
+    /// return whether each message would have been processed successfully, in
+    /// the same order as the batch.
+    async fn simulate_process_batch(&self, batch: &[BatchItem]) -> ChainResult<Vec<bool>>;
+
+    /// Process a batch of messages in a single transaction. The transaction
+    /// reverts if any of the messages fails to be processed.
+    async fn process_batch(
+        &self,
+        batch: &[BatchItem],
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome>;
+}
+
+/// A message and the metadata needed to process it as part of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    /// The message to process
+    pub message: HyperlaneMessage,
+    /// The ISM metadata for the message
+    pub metadata: Vec<u8>,
 }
@@ -1,3 +1,4 @@
+pub use aggregation_ism::*;
 pub use cursor::*;
 pub use db::*;
 pub use deployed::*;
@@ -12,6 +13,7 @@ pub use routing_ism::*;
 pub use signing::*;
 pub use validator_announce::*;
 
+mod aggregation_ism;
 mod cursor;
 mod db;
 mod deployed;
@@ -37,6 +39,10 @@ pub struct TxOutcome {
     pub gas_used: crate::U256,
     /// Price paid for the gas
     pub gas_price: crate::U256,
+    /// Number of the block the transaction was included in, if known
+    pub block_number: Option<u64>,
+    /// Hash of the block the transaction was included in, if known
+    pub block_hash: Option<crate::H256>,
     // TODO: more? What can be abstracted across all chains?
 }
 
@@ -47,6 +53,8 @@ impl From<ethers_core::types::TransactionReceipt> for TxOutcome {
             executed: t.status.unwrap().low_u32() == 1,
             gas_used: t.gas_used.unwrap_or(crate::U256::zero()),
             gas_price: t.effective_gas_price.unwrap_or(crate::U256::zero()),
+            block_number: t.block_number.map(|n| n.as_u64()),
+            block_hash: t.block_hash,
         }
     }
 }
@@ -19,6 +19,12 @@ pub trait HyperlaneProvider: HyperlaneChain + Send + Sync + Debug {
     /// Get block info for a given block hash
     async fn get_block_by_hash(&self, hash: &H256) -> ChainResult<BlockInfo>;
 
+    /// Get block info for a given block number of the canonical chain
+    async fn get_block_by_number(&self, number: u64) -> ChainResult<BlockInfo>;
+
+    /// Get the number of the latest block
+    async fn get_block_number(&self) -> ChainResult<u64>;
+
     /// Get txn info for a given txn hash
     async fn get_txn_by_hash(&self, hash: &H256) -> ChainResult<TxnInfo>;
 
@@ -38,4 +44,7 @@ pub enum HyperlaneProviderError {
     /// Could not find a transaction, block, or other object
     #[error("Could not find object from provider with hash {0:?}")]
     CouldNotFindObjectByHash(H256),
+    /// Could not find a block with the given number
+    #[error("Could not find block from provider with number {0}")]
+    CouldNotFindBlockByNumber(u64),
 }
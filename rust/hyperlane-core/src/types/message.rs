@@ -6,6 +6,10 @@ use crate::{Decode, Encode, HyperlaneProtocolError, H256};
 
 const HYPERLANE_MESSAGE_PREFIX_LEN: usize = 77;
 
+/// Message versions the agents know how to handle. Messages with any other
+/// version are skipped instead of being relayed or signed.
+pub const SUPPORTED_MESSAGE_VERSIONS: &[u8] = &[0];
+
 /// A message ID that has been delivered to the destination
 pub type Delivery = H256;
 
@@ -67,30 +71,25 @@ impl Display for HyperlaneMessage {
     }
 }
 
-impl From<RawHyperlaneMessage> for HyperlaneMessage {
-    fn from(m: RawHyperlaneMessage) -> Self {
-        HyperlaneMessage::from(&m)
+impl TryFrom<RawHyperlaneMessage> for HyperlaneMessage {
+    type Error = HyperlaneProtocolError;
+
+    fn try_from(m: RawHyperlaneMessage) -> Result<Self, Self::Error> {
+        HyperlaneMessage::try_from(&m)
     }
 }
 
-impl From<&RawHyperlaneMessage> for HyperlaneMessage {
-    fn from(m: &RawHyperlaneMessage) -> Self {
-        let version = m[0];
-        let nonce: [u8; 4] = m[1..5].try_into().unwrap();
-        let origin: [u8; 4] = m[5..9].try_into().unwrap();
-        let sender: [u8; 32] = m[9..41].try_into().unwrap();
-        let destination: [u8; 4] = m[41..45].try_into().unwrap();
-        let recipient: [u8; 32] = m[45..77].try_into().unwrap();
-        let body = m[77..].try_into().unwrap();
-        Self {
-            version,
-            nonce: u32::from_be_bytes(nonce),
-            origin: u32::from_be_bytes(origin),
-            sender: H256::from(sender),
-            destination: u32::from_be_bytes(destination),
-            recipient: H256::from(recipient),
-            body,
+impl TryFrom<&RawHyperlaneMessage> for HyperlaneMessage {
+    type Error = HyperlaneProtocolError;
+
+    fn try_from(m: &RawHyperlaneMessage) -> Result<Self, Self::Error> {
+        if m.len() < HYPERLANE_MESSAGE_PREFIX_LEN {
+            return Err(HyperlaneProtocolError::MessageTooShort {
+                len: m.len(),
+                expected: HYPERLANE_MESSAGE_PREFIX_LEN,
+            });
         }
+        Self::read_from(&mut m.as_slice())
     }
 }
 
@@ -153,4 +152,82 @@ impl HyperlaneMessage {
     pub fn id(&self) -> H256 {
         H256::from_slice(Keccak256::new().chain(self.to_vec()).finalize().as_slice())
     }
+
+    /// Whether the version of the message is one the agents know how to
+    /// handle, see [`SUPPORTED_MESSAGE_VERSIONS`].
+    pub fn has_supported_version(&self) -> bool {
+        SUPPORTED_MESSAGE_VERSIONS.contains(&self.version)
+    }
+
+    /// Return an error if the version of the message is not one the agents
+    /// know how to handle.
+    pub fn check_version(&self) -> Result<(), HyperlaneProtocolError> {
+        if self.has_supported_version() {
+            Ok(())
+        } else {
+            Err(HyperlaneProtocolError::UnsupportedMessageVersion(
+                self.version,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message() -> HyperlaneMessage {
+        HyperlaneMessage {
+            version: 0,
+            nonce: 7,
+            origin: 1000,
+            sender: H256::repeat_byte(1),
+            destination: 2000,
+            recipient: H256::repeat_byte(2),
+            body: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_raw_message_round_trip() {
+        let message = message();
+        let raw = RawHyperlaneMessage::from(&message);
+        let decoded = HyperlaneMessage::try_from(&raw).unwrap();
+        assert_eq!(decoded.id(), message.id());
+        assert_eq!(decoded.body, message.body);
+
+        let header_only = RawHyperlaneMessage::from(&HyperlaneMessage {
+            body: vec![],
+            ..message
+        });
+        assert!(HyperlaneMessage::try_from(header_only)
+            .unwrap()
+            .body
+            .is_empty());
+    }
+
+    #[test]
+    fn test_truncated_raw_message_is_rejected() {
+        let raw = RawHyperlaneMessage::from(&message());
+        for len in [0, 1, 41, HYPERLANE_MESSAGE_PREFIX_LEN - 1] {
+            assert!(matches!(
+                HyperlaneMessage::try_from(raw[..len].to_vec()),
+                Err(HyperlaneProtocolError::MessageTooShort { len: l, .. }) if l == len
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_version() {
+        assert!(message().check_version().is_ok());
+        let message = HyperlaneMessage {
+            version: 1,
+            ..message()
+        };
+        assert!(!message.has_supported_version());
+        assert!(matches!(
+            message.check_version(),
+            Err(HyperlaneProtocolError::UnsupportedMessageVersion(1))
+        ));
+    }
 }
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use sha3::{digest::Update, Digest, Keccak256};
 
-use crate::{KnownHyperlaneDomain, H256};
+use crate::{DomainRegistry, H256};
 
 /// Strips the '0x' prefix off of hex string so it can be deserialized.
 ///
@@ -43,9 +43,10 @@ pub fn announcement_domain_hash(address: H256, domain: impl Into<u32>) -> H256 {
 
 /// Pretty print an address based on the domain it is for.
 pub fn fmt_address_for_domain(domain: u32, addr: H256) -> String {
-    KnownHyperlaneDomain::try_from(domain)
-        .map(|d| d.domain_protocol().fmt_address(addr))
-        .unwrap_or_else(|_| format!("{addr:?}"))
+    DomainRegistry::global()
+        .by_id(domain)
+        .map(|d| d.protocol.fmt_address(addr))
+        .unwrap_or_else(|| format!("{addr:?}"))
 }
 
 /// Pretty print a byte slice for logging
@@ -55,9 +56,10 @@ pub fn fmt_bytes(bytes: &[u8]) -> String {
 
 /// Format a domain id as a name if it is known or just the number if not.
 pub fn fmt_domain(domain: u32) -> String {
-    KnownHyperlaneDomain::try_from(domain)
-        .map(|d| d.to_string())
-        .unwrap_or_else(|_| domain.to_string())
+    DomainRegistry::global()
+        .by_id(domain)
+        .map(|d| d.name.clone())
+        .unwrap_or_else(|| domain.to_string())
 }
 
 /// Formats the duration in the most appropriate time units.
@@ -90,33 +92,3 @@ pub fn fmt_sync_time(dur: Duration) -> String {
         fmt_duration(dur)
     }
 }
-
-/// Shortcut for many-to-one match statements that get very redundant. Flips the
-/// order such that the thing which is mapped to is listed first.
-///
-/// ```ignore
-/// match v {
-///   V1 => A,
-///   V2 => A,
-///   V3 => B,
-///   V4 => B,
-/// }
-///
-/// // becomes
-///
-/// many_to_one!(match v {
-///     A: [V1, V2],
-///     B: [v3, V4],
-/// })
-/// ```
-macro_rules! many_to_one {
-    (match $v:ident {
-        $($result:path: [$($source:path),*$(,)?]),*$(,)?
-    }) => {
-        match $v {
-            $($( $source => $result, )*)*
-        }
-    }
-}
-
-pub(crate) use many_to_one;
@@ -8,7 +8,7 @@ use walkdir::WalkDir;
 
 use hyperlane_base::{RawSettings, Settings};
 use hyperlane_core::config::*;
-use hyperlane_core::KnownHyperlaneDomain;
+use hyperlane_core::DomainRegistry;
 
 /// Relative path to the `hyperlane-monorepo/rust/config/`
 /// directory, which is where the agent's config files
@@ -99,17 +99,14 @@ fn chain_name_domain_records() -> BTreeSet<ChainCoordinate> {
 
 #[test]
 fn agent_json_config_consistency_checks() {
-    // Verify that the hard-coded, macro-maintained
-    // mapping in `hyperlane-core/src/chain.rs` named
-    // by the macro `domain_and_chain` is complete
+    // Verify that the bundled domain registry in
+    // `hyperlane-core/src/domains.json` is complete
     // and in agreement with our on-disk json-based
     // configuration data.
+    let registry = DomainRegistry::bundled();
     let chain_coords = chain_name_domain_records();
     for ChainCoordinate { name, domain } in chain_coords.into_iter() {
-        assert_eq!(
-            KnownHyperlaneDomain::try_from(domain).unwrap().to_string(),
-            name
-        );
-        assert_eq!(name.parse::<KnownHyperlaneDomain>().unwrap() as u32, domain);
+        assert_eq!(registry.by_id(domain).unwrap().name, name);
+        assert_eq!(registry.by_name(&name).unwrap().id, domain);
     }
 }
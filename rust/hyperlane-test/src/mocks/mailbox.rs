@@ -57,6 +57,16 @@ mock! {
             message: &HyperlaneMessage,
             metadata: &[u8],
         ) -> Vec<u8> {}
+
+        pub fn _supports_batching(&self) -> bool {}
+
+        pub fn _simulate_process_batch(&self, batch: &[BatchItem]) -> ChainResult<Vec<bool>> {}
+
+        pub fn _process_batch(
+            &self,
+            batch: &[BatchItem],
+            tx_gas_limit: Option<U256>,
+        ) -> ChainResult<TxOutcome> {}
     }
 }
 
@@ -112,6 +122,22 @@ impl Mailbox for MockMailboxContract {
     fn process_calldata(&self, message: &HyperlaneMessage, metadata: &[u8]) -> Vec<u8> {
         self.process_calldata(message, metadata)
     }
+
+    fn supports_batching(&self) -> bool {
+        self._supports_batching()
+    }
+
+    async fn simulate_process_batch(&self, batch: &[BatchItem]) -> ChainResult<Vec<bool>> {
+        self._simulate_process_batch(batch)
+    }
+
+    async fn process_batch(
+        &self,
+        batch: &[BatchItem],
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome> {
+        self._process_batch(batch, tx_gas_limit)
+    }
 }
 
 impl HyperlaneChain for MockMailboxContract {
@@ -1,4 +1,10 @@
 /// Mock mailbox contract
 pub mod mailbox;
+/// Mock provider
+pub mod provider;
+/// Mock validator announce contract
+pub mod validator_announce;
 
 pub use mailbox::MockMailboxContract;
+pub use provider::MockHyperlaneProvider;
+pub use validator_announce::MockValidatorAnnounceContract;
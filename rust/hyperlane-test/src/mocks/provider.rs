@@ -0,0 +1,63 @@
+#![allow(non_snake_case)]
+
+use async_trait::async_trait;
+use mockall::*;
+
+use hyperlane_core::*;
+
+mock! {
+    pub HyperlaneProvider {
+        pub fn _domain(&self) -> &HyperlaneDomain {}
+
+        pub fn _provider(&self) -> Box<dyn HyperlaneProvider> {}
+
+        pub fn _get_block_by_hash(&self, hash: &H256) -> ChainResult<BlockInfo> {}
+
+        pub fn _get_block_by_number(&self, number: u64) -> ChainResult<BlockInfo> {}
+
+        pub fn _get_block_number(&self) -> ChainResult<u64> {}
+
+        pub fn _get_txn_by_hash(&self, hash: &H256) -> ChainResult<TxnInfo> {}
+
+        pub fn _is_contract(&self, address: &H256) -> ChainResult<bool> {}
+    }
+}
+
+impl std::fmt::Debug for MockHyperlaneProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockHyperlaneProvider")
+    }
+}
+
+#[async_trait]
+impl HyperlaneProvider for MockHyperlaneProvider {
+    async fn get_block_by_hash(&self, hash: &H256) -> ChainResult<BlockInfo> {
+        self._get_block_by_hash(hash)
+    }
+
+    async fn get_block_by_number(&self, number: u64) -> ChainResult<BlockInfo> {
+        self._get_block_by_number(number)
+    }
+
+    async fn get_block_number(&self) -> ChainResult<u64> {
+        self._get_block_number()
+    }
+
+    async fn get_txn_by_hash(&self, hash: &H256) -> ChainResult<TxnInfo> {
+        self._get_txn_by_hash(hash)
+    }
+
+    async fn is_contract(&self, address: &H256) -> ChainResult<bool> {
+        self._is_contract(address)
+    }
+}
+
+impl HyperlaneChain for MockHyperlaneProvider {
+    fn domain(&self) -> &HyperlaneDomain {
+        self._domain()
+    }
+
+    fn provider(&self) -> Box<dyn HyperlaneProvider> {
+        self._provider()
+    }
+}
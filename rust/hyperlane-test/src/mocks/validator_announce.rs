@@ -0,0 +1,79 @@
+#![allow(non_snake_case)]
+
+use async_trait::async_trait;
+use mockall::*;
+
+use hyperlane_core::*;
+
+mock! {
+    pub ValidatorAnnounceContract {
+        pub fn _address(&self) -> H256 {}
+
+        pub fn _domain(&self) -> &HyperlaneDomain {}
+
+        pub fn _provider(&self) -> Box<dyn HyperlaneProvider> {}
+
+        pub fn _get_announced_storage_locations(
+            &self,
+            validators: &[H256],
+        ) -> ChainResult<Vec<Vec<String>>> {}
+
+        pub fn _announce(
+            &self,
+            announcement: SignedType<Announcement>,
+            tx_gas_limit: Option<U256>,
+        ) -> ChainResult<TxOutcome> {}
+
+        pub fn _announce_tokens_needed(
+            &self,
+            announcement: SignedType<Announcement>,
+        ) -> ChainResult<U256> {}
+    }
+}
+
+impl std::fmt::Debug for MockValidatorAnnounceContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MockValidatorAnnounceContract")
+    }
+}
+
+#[async_trait]
+impl ValidatorAnnounce for MockValidatorAnnounceContract {
+    async fn get_announced_storage_locations(
+        &self,
+        validators: &[H256],
+    ) -> ChainResult<Vec<Vec<String>>> {
+        self._get_announced_storage_locations(validators)
+    }
+
+    async fn announce(
+        &self,
+        announcement: SignedType<Announcement>,
+        tx_gas_limit: Option<U256>,
+    ) -> ChainResult<TxOutcome> {
+        self._announce(announcement, tx_gas_limit)
+    }
+
+    async fn announce_tokens_needed(
+        &self,
+        announcement: SignedType<Announcement>,
+    ) -> ChainResult<U256> {
+        self._announce_tokens_needed(announcement)
+    }
+}
+
+impl HyperlaneChain for MockValidatorAnnounceContract {
+    fn domain(&self) -> &HyperlaneDomain {
+        self._domain()
+    }
+
+    fn provider(&self) -> Box<dyn HyperlaneProvider> {
+        self._provider()
+    }
+}
+
+impl HyperlaneContract for MockValidatorAnnounceContract {
+    fn address(&self) -> H256 {
+        self._address()
+    }
+}
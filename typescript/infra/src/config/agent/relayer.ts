@@ -20,6 +20,11 @@ export interface MatchingListElement {
   senderAddress?: '*' | string | string[];
   destinationDomain?: '*' | number | number[];
   recipientAddress?: '*' | string | string[];
+  bodySelector?: '*' | string | string[];
+  bodyPrefix?: string;
+  bodyRegex?: string;
+  maxBodyLength?: number;
+  exclude?: boolean;
 }
 
 export enum GasPaymentEnforcementPolicyType {